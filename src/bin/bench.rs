@@ -0,0 +1,109 @@
+// Times every registered benchmark over growing sizes and checks how it grows (see `dsa::bench`)
+//
+//   bench [--csv <output>] [--baseline <previous csv>] [--repeats <r>] [filter ...]
+//
+// A filter keeps the benchmarks whose name contains it. Run it with --release.
+//
+// Exit codes: 0 when nothing regressed, 1 when something grows faster than its bound (or than in
+// the baseline), 2 on errors.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, BufWriter},
+    process::ExitCode,
+};
+
+use anyhow::Context;
+use rust_typescript::dsa::bench::{read_fits, registry, write_csv};
+
+const USAGE: &str =
+    "usage: bench [--csv <output>] [--baseline <previous csv>] [--repeats <r>] [filter ...]";
+
+struct Args {
+    csv: Option<String>,
+    baseline: Option<String>,
+    repeats: usize,
+    filters: Vec<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> anyhow::Result<Args> {
+    let mut parsed = Args {
+        csv: None,
+        baseline: None,
+        repeats: 5,
+        filters: vec![],
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| anyhow::anyhow!("{arg} needs a value"))
+        };
+        match arg.as_str() {
+            "--csv" => parsed.csv = Some(value()?),
+            "--baseline" => parsed.baseline = Some(value()?),
+            "--repeats" => {
+                let repeats = value()?;
+                parsed.repeats = repeats
+                    .parse()
+                    .with_context(|| format!("invalid --repeats `{repeats}`"))?;
+            }
+            _ if arg.starts_with('-') => anyhow::bail!("unknown option `{arg}`"),
+            _ => parsed.filters.push(arg),
+        }
+    }
+    Ok(parsed)
+}
+
+fn run(args: Args) -> anyhow::Result<bool> {
+    let baseline = match &args.baseline {
+        Some(path) => {
+            let file = File::open(path).with_context(|| format!("opening {path}"))?;
+            read_fits(BufReader::new(file)).with_context(|| format!("reading {path}"))?
+        }
+        None => HashMap::new(),
+    };
+
+    let mut reports = vec![];
+    let mut regressions = vec![];
+    for benchmark in registry() {
+        if !args.filters.is_empty()
+            && !args
+                .filters
+                .iter()
+                .any(|f| benchmark.name.contains(f.as_str()))
+        {
+            continue;
+        }
+        let Some(report) = benchmark.run(args.repeats) else {
+            eprintln!("{}: needs at least two sizes, skipped", benchmark.name);
+            continue;
+        };
+        println!(
+            "{:<28} {:<8} (bound {}, {:.2} ns per unit, error {:.4})",
+            report.name, report.fit.complexity, report.bound, report.fit.constant, report.fit.error
+        );
+        regressions.extend(report.regression(baseline.get(&report.name).copied()));
+        reports.push(report);
+    }
+
+    if let Some(path) = &args.csv {
+        let file = File::create(path).with_context(|| format!("creating {path}"))?;
+        write_csv(&reports, BufWriter::new(file)).with_context(|| format!("writing {path}"))?;
+    }
+    for regression in &regressions {
+        println!("REGRESSION {regression}");
+    }
+    Ok(regressions.is_empty())
+}
+
+fn main() -> ExitCode {
+    match parse_args(std::env::args().skip(1)).and_then(run) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(e) => {
+            eprintln!("error: {e:#}\n{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
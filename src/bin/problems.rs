@@ -0,0 +1,98 @@
+// Checks every registered solution against its case files (see `dsa::problems`)
+//
+//   problems [--cases <dir>] [filter ...]
+//
+// A filter keeps the solutions whose `problem/solution` name contains it. Cases default to
+// `cases/` at the root of the crate.
+//
+// Exit codes: 0 when everything passes, 1 when some case fails, 2 on errors (unreadable cases,
+// unparsable input, ..).
+
+use std::{path::PathBuf, process::ExitCode, time::Duration};
+
+use anyhow::Context;
+use rust_typescript::dsa::problems::{
+    default_cases_dir, diff_lines, load_cases, registry, Solution, Verdict,
+};
+
+const USAGE: &str = "usage: problems [--cases <dir>] [filter ...]";
+
+struct Args {
+    cases: PathBuf,
+    filters: Vec<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> anyhow::Result<Args> {
+    let mut parsed = Args {
+        cases: default_cases_dir(),
+        filters: vec![],
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--cases" => {
+                parsed.cases = args
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("--cases needs a directory"))?
+                    .into();
+            }
+            _ if arg.starts_with('-') => anyhow::bail!("unknown option `{arg}`"),
+            _ => parsed.filters.push(arg),
+        }
+    }
+    Ok(parsed)
+}
+
+#[derive(Default)]
+struct Totals {
+    passed: usize,
+    failed: usize,
+    time: Duration,
+}
+
+fn check(solution: &Solution, args: &Args, totals: &mut Totals) -> anyhow::Result<()> {
+    let label = format!("{}/{}", solution.problem, solution.name);
+    let cases = load_cases(&args.cases.join(solution.problem))
+        .with_context(|| format!("loading the cases of {label}"))?;
+    for case in cases {
+        let outcome = solution.check(&case)?;
+        totals.time += outcome.elapsed;
+        match outcome.verdict {
+            Verdict::Passed => {
+                totals.passed += 1;
+                println!("ok    {label} {} ({:?})", case.name, outcome.elapsed);
+            }
+            Verdict::Failed { expected, got } => {
+                totals.failed += 1;
+                println!("FAIL  {label} {} ({:?})", case.name, outcome.elapsed);
+                print!("{}", diff_lines(&expected, &got));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run(args: Args) -> anyhow::Result<bool> {
+    let mut totals = Totals::default();
+    for solution in registry() {
+        let label = format!("{}/{}", solution.problem, solution.name);
+        if args.filters.is_empty() || args.filters.iter().any(|f| label.contains(f.as_str())) {
+            check(&solution, &args, &mut totals)?;
+        }
+    }
+    println!(
+        "\n{} passed, {} failed, {:?} solving",
+        totals.passed, totals.failed, totals.time
+    );
+    Ok(totals.failed == 0)
+}
+
+fn main() -> ExitCode {
+    match parse_args(std::env::args().skip(1)).and_then(run) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(e) => {
+            eprintln!("error: {e:#}\n{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
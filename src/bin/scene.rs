@@ -0,0 +1,126 @@
+// Command line front end for diffing and merging scene files (same format as `shapes_input`)
+//
+//   scene diff <before> <after> [--json] [--max-distance <d>]
+//   scene merge <base> <ours> <theirs> [--json] [--max-distance <d>] [-o <output>]
+//
+// Exit codes follow diff(1) / git merge-file: 0 when there's nothing to report, 1 when the scenes
+// differ (diff) or the merge has conflicts (merge), 2 on errors.
+
+use std::{io::Write, process::ExitCode};
+
+use anyhow::Context;
+use rust_typescript::shapes::{
+    diff::{diff, merge, DiffOptions},
+    scene_reader::SceneReader,
+    shape::Shape,
+};
+
+const USAGE: &str = "usage:
+  scene diff <before> <after> [--json] [--max-distance <d>]
+  scene merge <base> <ours> <theirs> [--json] [--max-distance <d>] [-o <output>]";
+
+struct Args {
+    command: String,
+    files: Vec<String>,
+    json: bool,
+    output: Option<String>,
+    options: DiffOptions,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> anyhow::Result<Args> {
+    let command = args
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("missing command"))?;
+    let mut parsed = Args {
+        command,
+        files: vec![],
+        json: false,
+        output: None,
+        options: DiffOptions::default(),
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => parsed.json = true,
+            "--max-distance" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("--max-distance needs a value"))?;
+                parsed.options.max_distance = value
+                    .parse()
+                    .with_context(|| format!("invalid --max-distance `{value}`"))?;
+            }
+            "-o" | "--output" => {
+                parsed.output = Some(
+                    args.next()
+                        .ok_or_else(|| anyhow::anyhow!("{arg} needs a file name"))?,
+                );
+            }
+            _ if arg.starts_with('-') => anyhow::bail!("unknown option `{arg}`"),
+            _ => parsed.files.push(arg),
+        }
+    }
+    Ok(parsed)
+}
+
+// strict: a diff of a half-parsed scene would be misleading
+fn load_scene(path: &str) -> anyhow::Result<Vec<Shape>> {
+    SceneReader::open(path)?
+        .collect::<anyhow::Result<Vec<_>>>()
+        .with_context(|| format!("reading {path}"))
+}
+
+fn run(args: Args) -> anyhow::Result<bool> {
+    match (args.command.as_str(), args.files.as_slice()) {
+        ("diff", [before, after]) => {
+            let result = diff(&load_scene(before)?, &load_scene(after)?, &args.options);
+            if args.json {
+                println!("{}", result.to_json());
+            } else {
+                println!("{result}");
+            }
+            Ok(result.is_empty())
+        }
+        ("merge", [base, ours, theirs]) => {
+            let result = merge(
+                &load_scene(base)?,
+                &load_scene(ours)?,
+                &load_scene(theirs)?,
+                &args.options,
+            );
+
+            let mut out: Box<dyn Write> = match &args.output {
+                Some(path) => Box::new(
+                    std::fs::File::create(path).with_context(|| format!("creating {path}"))?,
+                ),
+                None => Box::new(std::io::stdout()),
+            };
+            if args.json {
+                writeln!(out, "{}", result.to_json())?;
+            } else {
+                result
+                    .merged
+                    .iter()
+                    .try_for_each(|shape| writeln!(out, "{shape}"))?;
+                result
+                    .conflicts
+                    .iter()
+                    .for_each(|conflict| eprintln!("{conflict}"));
+            }
+            Ok(!result.has_conflicts())
+        }
+        ("diff" | "merge", _) => anyhow::bail!("wrong number of files for `{}`", args.command),
+        (command, _) => anyhow::bail!("unknown command `{command}`"),
+    }
+}
+
+fn main() -> ExitCode {
+    match parse_args(std::env::args().skip(1)).and_then(run) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(e) => {
+            eprintln!("error: {e:#}\n{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,43 @@
+// Interactive shell for poking at scenes, see `help` inside it (or `shapes::repl::HELP`)
+//
+//   shapes [scene file to load]
+
+use std::io::{BufRead, Write};
+
+use rust_typescript::shapes::repl::Repl;
+
+fn main() {
+    let mut repl = Repl::new();
+    if let Some(path) = std::env::args().nth(1) {
+        match repl.execute(&format!("load {path}")) {
+            Ok(reply) => println!("{reply}"),
+            Err(e) => eprintln!("error: {e:#}"),
+        }
+    }
+
+    let stdin = std::io::stdin();
+    let mut line = String::new();
+    loop {
+        print!("shapes> ");
+        std::io::stdout().flush().expect("Unable to flush stdout");
+
+        line.clear();
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) => break, // EOF (Ctrl-D)
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("error: {e}");
+                break;
+            }
+        }
+
+        match repl.execute(&line) {
+            Ok(reply) if reply.is_empty() => {}
+            Ok(reply) => println!("{reply}"),
+            Err(e) => eprintln!("error: {e:#}"),
+        }
+        if repl.is_finished() {
+            break;
+        }
+    }
+}
@@ -179,6 +179,20 @@ mod closures_and_fn_pointers {
             ];
             assert_eq!(handlers.iter().fold(0, |acc, f| f(acc)), 1);
         }
+
+        #[test]
+        fn recursive_closures() {
+            // let fib = |n: u64| if n < 2 { n } else { fib(n - 1) + fib(n - 2) };
+            // ^ ERROR: cannot find function `fib`, the closure isn't named until the `let` is done.
+            //   And a closure capturing itself would contain itself, an infinitely sized type.
+            //
+            // So the recursive call gets passed in as an argument instead (as a &mut dyn FnMut,
+            // which has a known size), that's what dsa::dp::memoize does, caching every result
+            use rust_typescript::dsa::dp::memoize;
+
+            let mut fib = memoize(|fib, n: u64| if n < 2 { n } else { fib(n - 1) + fib(n - 2) });
+            assert_eq!(fib(50), 12586269025);
+        }
     }
 
     mod fn_pointers {
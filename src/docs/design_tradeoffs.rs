@@ -53,6 +53,10 @@ mod design_tradeoffs {
         Option 3 returns a Rc<Asset> which is a smart pointer, and lifetime is NOT tied.
         Doubt: in Option 4
         */
+        // NOTE: Option 4 does too, a handle is Copy and borrows nothing, it's only turned into a
+        // &Asset for the duration of a `get`. rust_typescript::dsa::arena::Arena is that handle
+        // type with a generation added, so a handle to an unloaded asset is detected instead of
+        // silently pointing at whatever took its slot.
 
         /*
         == Requirement == It is important that all assets be deallocated at a single, predictable time.
@@ -35,6 +35,8 @@ mod ownership_inventory {
 
         #[test]
         fn sliced_refs() {
+            use rust_typescript::dsa::search::nth_element;
+
             fn find_nth<T: Ord + Clone>(elems: &[T], n: usize) -> T {
                 // elems.sort();
                 /* ^ ERROR: cannot borrowed as mutable since elems is behind & ref
@@ -42,11 +44,14 @@ mod ownership_inventory {
 
                 let mut elem_refs: Vec<&T> = elems.iter().collect();
                 // collect the refs in another vector and sort that instead
-                elem_refs.sort();
+                // NOTE: sorting everything (and then reading elems[n], not elem_refs[n]) is more
+                // than needed, selecting the n-th ref is O(n), see dsa::search
+                let nth = *nth_element(&mut elem_refs, n);
 
-                let t = &elems[n];
-                return t.clone();
+                return nth.clone();
             }
+
+            assert_eq!(find_nth(&[5, 1, 4, 2, 3], 1), 2);
         }
     }
 
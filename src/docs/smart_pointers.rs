@@ -217,6 +217,22 @@ mod tests {
             }
             assert_eq!(get_ref_counts(), [3, 1, 1]); // reference count reduce since _d died
         }
+
+        // The same shared tails, generic: rust_typescript::dsa::persistent_list::PList
+        #[test]
+        fn test_plist() {
+            use rust_typescript::dsa::persistent_list::PList;
+
+            let a = PList::new().cons(4);
+            let b = a.cons(3);
+            let c = a.cons(1);
+            assert_eq!(
+                [b.to_string(), c.to_string()],
+                ["3 -> 4 -> Nil", "1 -> 4 -> Nil"]
+            );
+            // both tails are a itself, not copies of it
+            assert!(b.tail().unwrap().ptr_eq(&a) && c.tail().unwrap().ptr_eq(&a));
+        }
     }
 
     mod interior_mutability {
@@ -691,6 +707,20 @@ mod tests {
             );
         }
 
+        // The same tree without Rc/Weak: nodes are indices and the parent links a Vec, so there are
+        // no counts to keep right and no parent that can be dropped under a child
+        // (see rust_typescript::dsa::tree)
+        #[test]
+        fn same_tree_with_indices() {
+            use rust_typescript::dsa::tree::Tree;
+
+            let edges = [(1, 2), (1, 5), (2, 3), (2, 4), (5, 6)].map(|(u, v)| (u - 1, v - 1));
+            let mut tree = Tree::from_edges((1..=6).collect(), 0, edges).unwrap();
+            let seven = tree.add_child(5, 7);
+            let values_till_root = tree.ancestors(seven).map(|node| tree[node]);
+            assert_eq!(values_till_root.collect::<Vec<_>>(), vec![7, 6, 5, 1]);
+        }
+
         #[test]
         fn test_parent_dropped() {
             let leaf = Rc::new(TreeNode::new(2));
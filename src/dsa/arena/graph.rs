@@ -0,0 +1,176 @@
+// == Arena graph ==
+/* A directed graph whose vertices live in an Arena, so they can be removed (which dsa::graph,
+ * with its fixed 0..n vertices, can't do). Edges are handles to their target; removing a vertex
+ * doesn't go looking for the edges into it, they just go stale and are skipped. Once as many
+ * vertices have been removed as are left, one pass over every edge list drops the stale edges, so
+ * they don't pile up and a removal costs O(average degree) amortised. A new vertex reusing the
+ * slot gets a new generation, so a stale edge never leads to it.
+ *
+ * With Rc<RefCell<_>> vertices the same graph needs Weak edges (strong ones in a cycle would
+ * never be freed) and an owner keeping the strong Rcs somewhere, this is that owner.
+ */
+
+use std::collections::VecDeque;
+
+use super::{Arena, Handle};
+
+#[derive(Debug, Clone)]
+pub struct Vertex<V> {
+    value: V,
+    edges: Vec<VertexId<V>>,
+}
+
+pub type VertexId<V> = Handle<Vertex<V>>;
+
+#[derive(Debug, Clone)]
+pub struct ArenaGraph<V> {
+    vertices: Arena<Vertex<V>>,
+    /// since stale edges were last dropped
+    removed: usize,
+}
+
+impl<V> ArenaGraph<V> {
+    pub fn new() -> Self {
+        ArenaGraph {
+            vertices: Arena::new(),
+            removed: 0,
+        }
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub fn add_vertex(&mut self, value: V) -> VertexId<V> {
+        self.vertices.insert(Vertex {
+            value,
+            edges: vec![],
+        })
+    }
+
+    /// Panics if either end is stale
+    pub fn add_edge(&mut self, from: VertexId<V>, to: VertexId<V>) {
+        assert!(self.contains(to), "edge to stale vertex {to:?}");
+        self.vertices[from].edges.push(to);
+    }
+
+    /// Removes the vertex and its edges, the edges into it go stale
+    pub fn remove_vertex(&mut self, vertex: VertexId<V>) -> Option<V> {
+        let removed = self.vertices.remove(vertex)?;
+        self.removed += 1;
+        if self.removed > self.vertices.len() {
+            self.drop_stale_edges();
+        }
+        Some(removed.value)
+    }
+
+    fn drop_stale_edges(&mut self) {
+        let live = self.vertices.iter().map(|(id, _)| id).collect::<Vec<_>>();
+        for id in live {
+            let mut edges = std::mem::take(&mut self.vertices[id].edges);
+            edges.retain(|&to| self.contains(to));
+            self.vertices[id].edges = edges;
+        }
+        self.removed = 0;
+    }
+
+    pub fn contains(&self, vertex: VertexId<V>) -> bool {
+        self.vertices.contains(vertex)
+    }
+
+    pub fn value(&self, vertex: VertexId<V>) -> Option<&V> {
+        self.vertices.get(vertex).map(|vertex| &vertex.value)
+    }
+
+    pub fn value_mut(&mut self, vertex: VertexId<V>) -> Option<&mut V> {
+        self.vertices
+            .get_mut(vertex)
+            .map(|vertex| &mut vertex.value)
+    }
+
+    /// Targets of the live edges leaving `vertex`, in insertion order
+    pub fn neighbours(&self, vertex: VertexId<V>) -> impl Iterator<Item = VertexId<V>> + '_ {
+        let edges = self.vertices.get(vertex).map_or(&[][..], |v| &v.edges);
+        edges.iter().copied().filter(|&to| self.contains(to))
+    }
+
+    /// Vertices reachable from `start` (itself included), breadth first
+    pub fn bfs(&self, start: VertexId<V>) -> Vec<VertexId<V>> {
+        if !self.contains(start) {
+            return vec![];
+        }
+        // handles index side tables by slot
+        let mut seen = vec![false; self.vertices.capacity()];
+        seen[start.index()] = true;
+        let mut order = vec![];
+        let mut queue = VecDeque::from([start]);
+        while let Some(vertex) = queue.pop_front() {
+            order.push(vertex);
+            for next in self.neighbours(vertex) {
+                if !seen[next.index()] {
+                    seen[next.index()] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+        order
+    }
+}
+
+impl<V> Default for ArenaGraph<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removed_vertices_cut_paths() {
+        let mut graph = ArenaGraph::new();
+        let [a, b, c, d] = ["a", "b", "c", "d"].map(|name| graph.add_vertex(name));
+        for (from, to) in [(a, b), (b, c), (c, a), (a, d)] {
+            graph.add_edge(from, to);
+        }
+        let names = |graph: &ArenaGraph<&str>, found: Vec<_>| {
+            found
+                .into_iter()
+                .map(|v| *graph.value(v).unwrap())
+                .collect::<String>()
+        };
+        assert_eq!(names(&graph, graph.bfs(a)), "abdc");
+
+        assert_eq!(graph.remove_vertex(b), Some("b"));
+        assert_eq!(names(&graph, graph.bfs(a)), "ad");
+        assert_eq!(names(&graph, graph.bfs(c)), "cad");
+
+        // e takes b's slot, the stale a -> b edge doesn't lead to it
+        let e = graph.add_vertex("e");
+        assert_eq!(e.index(), b.index());
+        assert_eq!(names(&graph, graph.bfs(a)), "ad");
+        graph.add_edge(e, c);
+        assert_eq!(names(&graph, graph.bfs(e)), "ecad");
+        assert_eq!(graph.vertex_count(), 4);
+        assert!(graph.bfs(b).is_empty());
+    }
+
+    #[test]
+    fn stale_edges_are_dropped_eventually() {
+        let mut graph = ArenaGraph::new();
+        let hub = graph.add_vertex(0);
+        let spokes = (1..=10).map(|i| graph.add_vertex(i)).collect::<Vec<_>>();
+        spokes.iter().for_each(|&spoke| graph.add_edge(hub, spoke));
+
+        // the first removals leave their edges behind, they are only skipped
+        for &spoke in &spokes[..5] {
+            graph.remove_vertex(spoke);
+        }
+        assert_eq!(graph.vertices[hub].edges.len(), 10);
+        assert_eq!(graph.neighbours(hub).count(), 5);
+        // the sixth makes more removed vertices than live ones (5), and they're gone
+        graph.remove_vertex(spokes[5]);
+        assert_eq!(graph.vertices[hub].edges, spokes[6..]);
+    }
+}
@@ -0,0 +1,348 @@
+// == Generational arena ==
+/* Values stored in one Vec and referred to by handles, the `AssetHandle(usize)` option of
+ * docs::design_tradeoffs: a handle is Copy, borrows nothing, and everything goes away at once
+ * when the arena is dropped. It's also how linked structures get away from Rc<RefCell<_>> (see
+ * the trees in docs::smart_pointers): no reference counts, no runtime borrow flags, no Weak to
+ * upgrade, and a cycle is just two handles.
+ *
+ * The problem with a plain index is reuse: remove a value, insert another one in its slot, and
+ * an old index now silently means the new value. So every slot has a generation, bumped when
+ * its value is removed, and a handle remembers the generation it was created with. A handle to
+ * a removed value (stale) never matches again, `get` returns None for it.
+ *
+ * Removed slots are kept in a free list and reused by the next inserts. A slot whose generation
+ * would wrap around (after 2^32 removals) is retired instead of reused.
+ *
+ * - tree: a parent/children tree of arena nodes, the smart_pointers TreeNode without Rc/Weak
+ * - graph: a directed graph whose vertices can be removed, edges to them go stale
+ */
+
+pub mod graph;
+pub mod tree;
+
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ops::{Index, IndexMut},
+};
+
+/// A reference to a value in an `Arena<T>`, 8 bytes and Copy whatever T is
+pub struct Handle<T> {
+    index: u32,
+    generation: u32,
+    // fn() -> T: no ownership of a T (Send/Sync whatever T is), just the type
+    marker: PhantomData<fn() -> T>,
+}
+
+// by hand, derives would require T: Clone, T: PartialEq, ...
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.index, self.generation) == (other.index, other.generation)
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.index, self.generation).hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Handle({}v{})", self.index, self.generation)
+    }
+}
+
+impl<T> Handle<T> {
+    /// The slot, in 0..arena.capacity(): live handles never share one, so it can index side
+    /// tables (visited flags, distances, ...)
+    pub fn index(self) -> usize {
+        self.index as usize
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Slot<T> {
+    Occupied {
+        generation: u32,
+        value: T,
+    },
+    /// `generation` is the one the next value in this slot gets
+    Free {
+        generation: u32,
+        next: Option<u32>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Arena<T> {
+    slots: Vec<Slot<T>>,
+    /// head of the free list, threaded through the Free slots
+    free: Option<u32>,
+    len: usize,
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Arena {
+            slots: vec![],
+            free: None,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of slots, live or not
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn insert(&mut self, value: T) -> Handle<T> {
+        let (index, generation) = match self.free {
+            Some(index) => {
+                let Slot::Free { generation, next } = self.slots[index as usize] else {
+                    unreachable!("occupied slot {index} in the free list");
+                };
+                self.free = next;
+                (index, generation)
+            }
+            None => {
+                let index = u32::try_from(self.slots.len()).expect("more than 2^32 slots");
+                self.slots.push(Slot::Free {
+                    generation: 0,
+                    next: None,
+                });
+                (index, 0)
+            }
+        };
+        self.slots[index as usize] = Slot::Occupied { generation, value };
+        self.len += 1;
+        Handle {
+            index,
+            generation,
+            marker: PhantomData,
+        }
+    }
+
+    /// Takes the value out, every handle to it goes stale. None if it already was
+    pub fn remove(&mut self, handle: Handle<T>) -> Option<T> {
+        self.get(handle)?;
+        let next_generation = handle.generation.checked_add(1);
+        let free = Slot::Free {
+            generation: next_generation.unwrap_or(u32::MAX),
+            next: self.free,
+        };
+        let Slot::Occupied { value, .. } =
+            std::mem::replace(&mut self.slots[handle.index as usize], free)
+        else {
+            unreachable!("checked by get");
+        };
+        // a slot out of generations stays Free, but out of the free list
+        if next_generation.is_some() {
+            self.free = Some(handle.index);
+        }
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Whether `handle` still refers to a value
+    pub fn contains(&self, handle: Handle<T>) -> bool {
+        self.get(handle).is_some()
+    }
+
+    pub fn get(&self, handle: Handle<T>) -> Option<&T> {
+        match self.slots.get(handle.index as usize)? {
+            Slot::Occupied { generation, value } if *generation == handle.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, handle: Handle<T>) -> Option<&mut T> {
+        match self.slots.get_mut(handle.index as usize)? {
+            Slot::Occupied { generation, value } if *generation == handle.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Live values with their handles, in slot order
+    pub fn iter(&self) -> impl Iterator<Item = (Handle<T>, &T)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| match slot {
+                Slot::Occupied { generation, value } => Some((
+                    Handle {
+                        index: index as u32,
+                        generation: *generation,
+                        marker: PhantomData,
+                    },
+                    value,
+                )),
+                Slot::Free { .. } => None,
+            })
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Index<Handle<T>> for Arena<T> {
+    type Output = T;
+
+    fn index(&self, handle: Handle<T>) -> &T {
+        self.get(handle)
+            .unwrap_or_else(|| panic!("stale handle {handle:?}"))
+    }
+}
+
+impl<T> IndexMut<Handle<T>> for Arena<T> {
+    fn index_mut(&mut self, handle: Handle<T>) -> &mut T {
+        self.get_mut(handle)
+            .unwrap_or_else(|| panic!("stale handle {handle:?}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsa::stress::{check, Config, Gen};
+    use rand::Rng;
+
+    // (insert or remove, which handle to remove, which handle to read after the operation)
+    type Op = (bool, usize, usize);
+
+    fn operations(g: &mut Gen) -> Vec<Op> {
+        g.vec(0..=300, |g| {
+            (g.rng().gen_bool(0.55), g.int(0..=300), g.int(0..=300))
+        })
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Done {
+        /// fresh: never a handle handed out before, even in a reused slot
+        Inserted {
+            fresh: bool,
+        },
+        Removed(Option<u32>),
+    }
+
+    // per operation: (what it did, the value read, len), then the live values and the capacity
+    type Seen = (Vec<(Done, Option<u32>, usize)>, Vec<u32>, usize);
+
+    // model: every handle ever handed out, in order, with its value (None once removed); slots
+    // are reused, so the capacity is the most values ever live at once
+    fn run_model(ops: &[Op]) -> Seen {
+        let mut values: Vec<Option<u32>> = vec![];
+        let (mut log, mut most_live) = (vec![], 0);
+        for (value, &(insert, remove, read)) in ops.iter().enumerate() {
+            let value = value as u32;
+            let result = if insert || values.is_empty() {
+                values.push(Some(value));
+                Done::Inserted { fresh: true }
+            } else {
+                let i = remove % values.len();
+                Done::Removed(values[i].take())
+            };
+            let live = values.iter().flatten().count();
+            most_live = most_live.max(live);
+            log.push((result, values[read % values.len()], live));
+        }
+        let live = values.into_iter().flatten().collect();
+        (log, live, most_live)
+    }
+
+    fn run_arena(ops: &[Op]) -> Seen {
+        let mut arena = Arena::new();
+        let mut handles = vec![];
+        let mut log = vec![];
+        for (value, &(insert, remove, read)) in ops.iter().enumerate() {
+            let value = value as u32;
+            let result = if insert || handles.is_empty() {
+                let handle = arena.insert(value);
+                let fresh = !handles.contains(&handle);
+                handles.push(handle);
+                Done::Inserted { fresh }
+            } else {
+                Done::Removed(arena.remove(handles[remove % handles.len()]))
+            };
+            let read = arena.get(handles[read % handles.len()]).copied();
+            log.push((result, read, arena.len()));
+        }
+        let mut live = arena.iter().map(|(_, v)| *v).collect::<Vec<_>>();
+        live.sort();
+        (log, live, arena.capacity())
+    }
+
+    #[test]
+    fn matches_handle_list_model() {
+        check(
+            "Arena",
+            Config::default(),
+            operations,
+            |ops| run_model(ops),
+            |ops| run_arena(ops),
+        );
+    }
+
+    #[test]
+    fn stale_handles() {
+        let mut arena = Arena::new();
+        let first = arena.insert("first");
+        assert_eq!(arena.remove(first), Some("first"));
+        let second = arena.insert("second");
+        // same slot, different generation
+        assert_eq!(first.index(), second.index());
+        assert_ne!(first, second);
+        assert_eq!(arena.get(first), None);
+        arena[second] = "changed";
+        assert_eq!(arena[second], "changed");
+        assert_eq!(std::mem::size_of::<Handle<String>>(), 8);
+    }
+
+    #[test]
+    fn retires_slots_out_of_generations() {
+        let mut arena = Arena::new();
+        let handle = arena.insert(1);
+        let Slot::Occupied { generation, .. } = &mut arena.slots[0] else {
+            unreachable!();
+        };
+        *generation = u32::MAX;
+        let last = Handle {
+            generation: u32::MAX,
+            ..handle
+        };
+        assert_eq!(arena.remove(last), Some(1));
+        assert_ne!(arena.insert(2).index(), last.index());
+        assert_eq!(arena.capacity(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "stale handle")]
+    fn indexing_with_a_stale_handle_panics() {
+        let mut arena = Arena::new();
+        let handle = arena.insert(1);
+        arena.remove(handle);
+        let _ = arena[handle];
+    }
+}
@@ -0,0 +1,200 @@
+// == Arena tree ==
+/* The TreeNode of docs::smart_pointers (children owned by their parent, a back link to the
+ * parent) with the nodes in an Arena: children and parent are handles, so there is no
+ * RefCell<Vec<Rc<_>>> to borrow, no Weak to upgrade, and removing a subtree makes every handle
+ * into it stale instead of leaving nodes alive as long as someone holds an Rc.
+ *
+ * A tree here is a forest: nodes start as roots and `join` hangs one under another.
+ */
+
+use super::{Arena, Handle};
+
+#[derive(Debug, Clone)]
+pub struct TreeNode<T> {
+    value: T,
+    parent: Option<NodeId<T>>,
+    children: Vec<NodeId<T>>,
+}
+
+pub type NodeId<T> = Handle<TreeNode<T>>;
+
+#[derive(Debug, Clone)]
+pub struct ArenaTree<T> {
+    nodes: Arena<TreeNode<T>>,
+}
+
+impl<T> ArenaTree<T> {
+    pub fn new() -> Self {
+        ArenaTree {
+            nodes: Arena::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// A new node without parent or children
+    pub fn add(&mut self, value: T) -> NodeId<T> {
+        self.nodes.insert(TreeNode {
+            value,
+            parent: None,
+            children: vec![],
+        })
+    }
+
+    /// Adds `value` as the last child of `parent`, panics if parent is stale. A new node can't
+    /// close a cycle, so unlike `join` this doesn't walk the ancestors: O(1)
+    pub fn add_child(&mut self, parent: NodeId<T>, value: T) -> NodeId<T> {
+        assert!(self.contains(parent), "stale handle {parent:?}");
+        let child = self.nodes.insert(TreeNode {
+            value,
+            parent: Some(parent),
+            children: vec![],
+        });
+        self.nodes[parent].children.push(child);
+        child
+    }
+
+    /// Makes `child` (a root) the last child of `parent`. Panics on stale handles, if child
+    /// already has a parent, or if it's an ancestor of parent (that would be a cycle). The cycle
+    /// check walks up from parent, O(depth)
+    pub fn join(&mut self, parent: NodeId<T>, child: NodeId<T>) {
+        assert!(
+            self.nodes[child].parent.is_none(),
+            "{child:?} already has a parent"
+        );
+        assert!(
+            self.ancestors(parent).all(|ancestor| ancestor != child),
+            "{child:?} is an ancestor of {parent:?}"
+        );
+        self.nodes[parent].children.push(child);
+        self.nodes[child].parent = Some(parent);
+    }
+
+    pub fn contains(&self, node: NodeId<T>) -> bool {
+        self.nodes.contains(node)
+    }
+
+    pub fn value(&self, node: NodeId<T>) -> Option<&T> {
+        self.nodes.get(node).map(|node| &node.value)
+    }
+
+    pub fn value_mut(&mut self, node: NodeId<T>) -> Option<&mut T> {
+        self.nodes.get_mut(node).map(|node| &mut node.value)
+    }
+
+    pub fn parent(&self, node: NodeId<T>) -> Option<NodeId<T>> {
+        self.nodes.get(node)?.parent
+    }
+
+    /// Empty for a stale handle
+    pub fn children(&self, node: NodeId<T>) -> &[NodeId<T>] {
+        self.nodes
+            .get(node)
+            .map_or(&[], |node| node.children.as_slice())
+    }
+
+    /// `node`, its parent, ... up to its root (nothing for a stale handle)
+    pub fn ancestors(&self, node: NodeId<T>) -> impl Iterator<Item = NodeId<T>> + '_ {
+        let start = Some(node).filter(|&node| self.contains(node));
+        std::iter::successors(start, |&node| self.parent(node))
+    }
+
+    /// `node` and everything under it, in preorder
+    pub fn descendants(&self, node: NodeId<T>) -> impl Iterator<Item = NodeId<T>> + '_ {
+        let mut stack = Vec::from_iter(Some(node).filter(|&node| self.contains(node)));
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(self.children(node).iter().rev());
+            Some(node)
+        })
+    }
+
+    /// Detaches `node` from its parent and removes it with everything under it, returning
+    /// their values in preorder. Every handle into the subtree goes stale
+    pub fn remove_subtree(&mut self, node: NodeId<T>) -> Vec<T> {
+        if let Some(parent) = self.parent(node) {
+            self.nodes[parent].children.retain(|&child| child != node);
+        }
+        let removed = self.descendants(node).collect::<Vec<_>>();
+        removed
+            .into_iter()
+            .filter_map(|node| self.nodes.remove(node))
+            .map(|node| node.value)
+            .collect()
+    }
+}
+
+impl<T: Clone> ArenaTree<T> {
+    /// What `get_values_till_root` does for the Rc tree
+    pub fn values_till_root(&self, node: NodeId<T>) -> Vec<T> {
+        self.ancestors(node)
+            .map(|node| self.nodes[node].value.clone())
+            .collect()
+    }
+}
+
+impl<T> Default for ArenaTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smart_pointers_tree() {
+        let mut tree = ArenaTree::new();
+        let nodes = (1..=6).map(|value| tree.add(value)).collect::<Vec<_>>();
+        for (u, v) in [(1, 2), (1, 5), (2, 3), (2, 4), (5, 6)] {
+            tree.join(nodes[u - 1], nodes[v - 1]);
+        }
+        let seven = tree.add_child(nodes[5], 7);
+        assert_eq!(tree.values_till_root(seven), [7, 6, 5, 1]);
+        let preorder = tree.descendants(nodes[0]).map(|n| tree.value(n).copied());
+        assert_eq!(
+            preorder.flatten().collect::<Vec<_>>(),
+            [1, 2, 3, 4, 5, 6, 7]
+        );
+
+        // the whole subtree goes, and its handles with it
+        assert_eq!(tree.remove_subtree(nodes[4]), [5, 6, 7]);
+        assert_eq!(tree.len(), 4);
+        assert!(!tree.contains(seven));
+        assert_eq!(tree.values_till_root(seven), Vec::<i32>::new());
+        assert_eq!(tree.children(nodes[0]), [nodes[1]]);
+
+        // a new node reusing a slot isn't reachable through the old handles
+        let eight = tree.add(8);
+        assert!(![nodes[4], nodes[5], seven].contains(&eight));
+        assert_eq!(tree.value(nodes[5]), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "is an ancestor of")]
+    fn join_rejects_cycles() {
+        let mut tree = ArenaTree::new();
+        let root = tree.add("root");
+        let leaf = tree.add_child(root, "leaf");
+        let other = tree.add("other");
+        tree.join(leaf, other);
+        // root is a root, but joining it under its own descendant would loop
+        tree.join(other, root);
+    }
+
+    #[test]
+    #[should_panic(expected = "stale handle")]
+    fn add_child_rejects_stale_parents() {
+        let mut tree = ArenaTree::new();
+        let root = tree.add(1);
+        tree.remove_subtree(root);
+        tree.add_child(root, 2);
+    }
+}
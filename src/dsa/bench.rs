@@ -0,0 +1,607 @@
+// == Growth benchmarks ==
+/* Times an algorithm on growing input sizes and fits the timings against n, n log n and n², so
+ * a change that makes something asymptotically worse fails instead of just being "a bit slower".
+ *
+ * Fitting: for a model f, t ≈ c·f(n) means ln t - ln f(n) ≈ ln c is the same for every sample.
+ * The error of a model is the variance of ln t - ln f(n) over the samples (ln, so that the big n
+ * don't drown the small ones). n and n log n are close (log n barely moves over a few doublings)
+ * and real timings are noisy, so the simplest model wins unless a more complex one is clearly
+ * better (error less than half, and not just by rounding).
+ *
+ * Every benchmark declares its bound, a fit above the bound (or above the fit of a previous run,
+ * read back from its CSV) is a regression. The `bench` binary runs everything:
+ *
+ *     cargo run --release --bin bench -- --csv bench.csv --baseline previous.csv
+ *
+ * Timings in debug builds mostly measure overflow checks, use --release.
+ */
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, VecDeque},
+    fmt,
+    hint::black_box,
+    io::{BufRead, Write},
+    rc::{Rc, Weak},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::dsa::arena::{graph::ArenaGraph, tree::ArenaTree};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Complexity {
+    Linear,
+    Linearithmic,
+    Quadratic,
+}
+
+impl Complexity {
+    /// Simplest first
+    pub const ALL: [Complexity; 3] = [
+        Complexity::Linear,
+        Complexity::Linearithmic,
+        Complexity::Quadratic,
+    ];
+
+    pub fn cost(self, n: usize) -> f64 {
+        let n = n.max(2) as f64;
+        match self {
+            Complexity::Linear => n,
+            Complexity::Linearithmic => n * n.log2(),
+            Complexity::Quadratic => n * n,
+        }
+    }
+}
+
+impl fmt::Display for Complexity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Complexity::Linear => "n",
+            Complexity::Linearithmic => "n log n",
+            Complexity::Quadratic => "n^2",
+        })
+    }
+}
+
+impl FromStr for Complexity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        Complexity::ALL
+            .into_iter()
+            .find(|complexity| complexity.to_string() == s.trim())
+            .ok_or_else(|| anyhow::anyhow!("unknown complexity `{s}`"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sample {
+    pub n: usize,
+    pub time: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fit {
+    pub complexity: Complexity,
+    /// nanoseconds per unit of `complexity.cost(n)`
+    pub constant: f64,
+    /// variance of ln(time / cost), 0 for a perfect fit
+    pub error: f64,
+}
+
+fn fit_one(complexity: Complexity, samples: &[Sample]) -> Fit {
+    let logs = samples
+        .iter()
+        .map(|s| (s.time.as_nanos().max(1) as f64).ln() - complexity.cost(s.n).ln())
+        .collect::<Vec<_>>();
+    let mean = logs.iter().sum::<f64>() / logs.len() as f64;
+    let error = logs.iter().map(|l| (l - mean).powi(2)).sum::<f64>() / logs.len() as f64;
+    Fit {
+        complexity,
+        constant: mean.exp(),
+        error,
+    }
+}
+
+/// The simplest model that fits about as well as the best one, None under 2 distinct sizes
+pub fn fit(samples: &[Sample]) -> Option<Fit> {
+    let first = samples.first()?;
+    if samples.iter().all(|s| s.n == first.n) {
+        return None;
+    }
+    let fits = Complexity::ALL.map(|complexity| fit_one(complexity, samples));
+    let best = fits.iter().map(|f| f.error).fold(f64::INFINITY, f64::min);
+    fits.into_iter().find(|f| f.error <= 2.0 * best + 1e-4)
+}
+
+type Routine = Box<dyn Fn(usize) -> Duration>;
+
+pub struct Benchmark {
+    pub name: &'static str,
+    /// the fit must not be worse than this
+    pub bound: Complexity,
+    pub sizes: Vec<usize>,
+    run: Routine,
+}
+
+impl Benchmark {
+    /// `setup` builds the input for a size (not timed), `routine` is timed on it
+    pub fn new<I, O>(
+        name: &'static str,
+        bound: Complexity,
+        sizes: Vec<usize>,
+        setup: impl Fn(usize) -> I + 'static,
+        routine: impl Fn(I) -> O + 'static,
+    ) -> Self {
+        let run = move |n| {
+            let input = setup(n);
+            let start = Instant::now();
+            black_box(routine(black_box(input)));
+            start.elapsed()
+        };
+        Benchmark {
+            name,
+            bound,
+            sizes,
+            run: Box::new(run),
+        }
+    }
+
+    /// The fastest of `repeats` runs for every size, the others are the machine being busy
+    pub fn measure(&self, repeats: usize) -> Vec<Sample> {
+        self.sizes
+            .iter()
+            .map(|&n| Sample {
+                n,
+                time: (0..repeats.max(1)).map(|_| (self.run)(n)).min().unwrap(),
+            })
+            .collect()
+    }
+
+    pub fn run(&self, repeats: usize) -> Option<Report> {
+        let samples = self.measure(repeats);
+        Some(Report {
+            name: self.name.to_string(),
+            bound: self.bound,
+            fit: fit(&samples)?,
+            samples,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub name: String,
+    pub bound: Complexity,
+    pub fit: Fit,
+    pub samples: Vec<Sample>,
+}
+
+impl Report {
+    /// Why this is a regression, if it is one: worse than its bound, or than `baseline` (the fit
+    /// of a previous run)
+    pub fn regression(&self, baseline: Option<Complexity>) -> Option<String> {
+        let fitted = self.fit.complexity;
+        if fitted > self.bound {
+            return Some(format!(
+                "{}: grows like {fitted}, bound is {}",
+                self.name, self.bound
+            ));
+        }
+        match baseline {
+            Some(previous) if fitted > previous => Some(format!(
+                "{}: grows like {fitted}, used to be {previous}",
+                self.name
+            )),
+            _ => None,
+        }
+    }
+}
+
+const CSV_HEADER: &str = "benchmark,bound,fit,n,nanos";
+
+/// One row per sample, the benchmark's bound and fit repeated on each
+pub fn write_csv(reports: &[Report], mut out: impl Write) -> anyhow::Result<()> {
+    writeln!(out, "{CSV_HEADER}")?;
+    for report in reports {
+        for sample in &report.samples {
+            writeln!(
+                out,
+                "{},{},{},{},{}",
+                report.name,
+                report.bound,
+                report.fit.complexity,
+                sample.n,
+                sample.time.as_nanos()
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// The fit of every benchmark in a CSV from `write_csv`
+pub fn read_fits(input: impl BufRead) -> anyhow::Result<HashMap<String, Complexity>> {
+    let mut fits = HashMap::new();
+    for (number, line) in input.lines().enumerate().skip(1) {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = line.split(',').collect::<Vec<_>>();
+        let [name, _, fit, _, _] = fields[..] else {
+            anyhow::bail!("line {}: expected {CSV_HEADER}, got `{line}`", number + 1);
+        };
+        let fit = fit
+            .parse()
+            .with_context(|| format!("line {}", number + 1))?;
+        fits.insert(name.to_string(), fit);
+    }
+    Ok(fits)
+}
+
+// == Rc<RefCell<_>> baselines for the arena benchmarks ==
+/* The same tree and graph as dsa::arena's, linked the way docs::smart_pointers does it, to see
+ * what the reference counts, borrow flags and Weak upgrades cost.
+ */
+
+struct RcNode {
+    value: u64,
+    parent: RefCell<Weak<RcNode>>,
+    children: RefCell<Vec<Rc<RcNode>>>,
+}
+
+struct RcVertex {
+    // Weak, strong edges on a cycle would never be freed
+    edges: RefCell<Vec<Weak<RcVertex>>>,
+    seen: Cell<bool>,
+}
+
+/// parents[i] < i for every node but the root (0), and edges[v] the targets of the edges
+/// leaving vertex v
+type Links = (Vec<usize>, Vec<Vec<usize>>);
+
+/// A random tree (about ln n deep) and a random graph with 3 edges leaving every vertex
+fn random_links(n: usize) -> Links {
+    let mut rng = StdRng::seed_from_u64(n as u64);
+    let parents = (0..n).map(|i| rng.gen_range(0..i.max(1))).collect();
+    let edges = (0..n)
+        .map(|_| (0..3).map(|_| rng.gen_range(0..n)).collect())
+        .collect();
+    (parents, edges)
+}
+
+/// The shapes random links hardly make: a path (n deep) and a star (every edge leaves vertex 0),
+/// where anything walking the ancestors or the edge list on every insert goes quadratic
+fn path_and_star(n: usize) -> Links {
+    let parents = (0..n).map(|i| i.saturating_sub(1)).collect();
+    let mut edges = vec![vec![]; n];
+    if let Some(first) = edges.first_mut() {
+        first.extend(0..n);
+    }
+    (parents, edges)
+}
+
+// builds the tree, then sums every node's parent value and every value (depth first)
+fn arena_tree_sum(parents: &[usize]) -> u64 {
+    let mut tree = ArenaTree::new();
+    let mut nodes = vec![tree.add(0)];
+    for (i, &parent) in parents.iter().enumerate().skip(1) {
+        nodes.push(tree.add_child(nodes[parent], i as u64));
+    }
+    let parent_values = nodes
+        .iter()
+        .filter_map(|&node| tree.value(tree.parent(node)?))
+        .sum::<u64>();
+    parent_values
+        + tree
+            .descendants(nodes[0])
+            .filter_map(|node| tree.value(node))
+            .sum::<u64>()
+}
+
+fn rc_tree_sum(parents: &[usize]) -> u64 {
+    let new_node = |value| {
+        Rc::new(RcNode {
+            value,
+            parent: RefCell::new(Weak::new()),
+            children: RefCell::new(vec![]),
+        })
+    };
+    let mut nodes = vec![new_node(0)];
+    for (i, &parent) in parents.iter().enumerate().skip(1) {
+        let node = new_node(i as u64);
+        *node.parent.borrow_mut() = Rc::downgrade(&nodes[parent]);
+        nodes[parent].children.borrow_mut().push(Rc::clone(&node));
+        nodes.push(node);
+    }
+    let parent_values = nodes
+        .iter()
+        .filter_map(|node| node.parent.borrow().upgrade())
+        .map(|parent| parent.value)
+        .sum::<u64>();
+    let mut total = parent_values;
+    let mut stack = vec![Rc::clone(&nodes[0])];
+    while let Some(node) = stack.pop() {
+        total += node.value;
+        stack.extend(node.children.borrow().iter().rev().cloned());
+    }
+    total
+}
+
+// builds the graph, then counts the vertices reachable from the first one
+fn arena_graph_reach(edges: &[Vec<usize>]) -> usize {
+    let mut graph = ArenaGraph::new();
+    let vertices = (0..edges.len())
+        .map(|i| graph.add_vertex(i))
+        .collect::<Vec<_>>();
+    for (from, targets) in edges.iter().enumerate() {
+        for &to in targets {
+            graph.add_edge(vertices[from], vertices[to]);
+        }
+    }
+    vertices.first().map_or(0, |&start| graph.bfs(start).len())
+}
+
+fn rc_graph_reach(edges: &[Vec<usize>]) -> usize {
+    let vertices = (0..edges.len())
+        .map(|_| {
+            Rc::new(RcVertex {
+                edges: RefCell::new(vec![]),
+                seen: Cell::new(false),
+            })
+        })
+        .collect::<Vec<_>>();
+    for (from, targets) in edges.iter().enumerate() {
+        for &to in targets {
+            let edge = Rc::downgrade(&vertices[to]);
+            vertices[from].edges.borrow_mut().push(edge);
+        }
+    }
+    let Some(start) = vertices.first() else {
+        return 0;
+    };
+    start.seen.set(true);
+    let mut queue = VecDeque::from([Rc::clone(start)]);
+    let mut reached = 0;
+    while let Some(vertex) = queue.pop_front() {
+        reached += 1;
+        for next in vertex.edges.borrow().iter().filter_map(Weak::upgrade) {
+            if !next.seen.replace(true) {
+                queue.push_back(next);
+            }
+        }
+    }
+    reached
+}
+
+/// Everything the `bench` binary runs
+pub fn registry() -> Vec<Benchmark> {
+    use crate::dsa::{
+        fenwick_tree::FenwickTree, merge::merge, monoid::Sum, problems::max_area,
+        segment_tree::SegmentTree,
+    };
+
+    let sizes = || (12..=17).map(|shift| 1 << shift).collect::<Vec<usize>>();
+    let numbers = |n: usize| {
+        let mut rng = StdRng::seed_from_u64(n as u64);
+        (0..n)
+            .map(|_| rng.gen_range(0..n as i64))
+            .collect::<Vec<_>>()
+    };
+    let heights = move |n: usize| numbers(n).into_iter().map(|h| h as i32).collect::<Vec<_>>();
+
+    vec![
+        Benchmark::new(
+            "sort_unstable",
+            Complexity::Linearithmic,
+            sizes(),
+            numbers,
+            |mut numbers| numbers.sort_unstable(),
+        ),
+        Benchmark::new(
+            "segment_tree/sums",
+            Complexity::Linearithmic,
+            sizes(),
+            numbers,
+            |numbers| {
+                let n = numbers.len();
+                let tree = SegmentTree::<i64, Sum>::from_vec(numbers);
+                (0..n).map(|i| tree.query(i / 2..i + 1)).sum::<i64>()
+            },
+        ),
+        Benchmark::new(
+            "fenwick_tree/sums",
+            Complexity::Linearithmic,
+            sizes(),
+            numbers,
+            |numbers| {
+                let n = numbers.len();
+                let tree = FenwickTree::from_vec(numbers);
+                (0..n).map(|i| tree.range_sum(i / 2..i + 1)).sum::<i64>()
+            },
+        ),
+        Benchmark::new(
+            "merge/sqrt_n_rows",
+            Complexity::Linearithmic,
+            sizes(),
+            move |n| {
+                let mut rows = numbers(n)
+                    .chunks(n.isqrt().max(1))
+                    .map(<[i64]>::to_vec)
+                    .collect::<Vec<_>>();
+                rows.iter_mut().for_each(|row| row.sort_unstable());
+                rows
+            },
+            |rows| merge(rows).count(),
+        ),
+        Benchmark::new(
+            "max_area/two_pointers",
+            Complexity::Linear,
+            sizes(),
+            heights,
+            max_area::Solution::max_area,
+        ),
+        Benchmark::new(
+            "max_area/binary_search",
+            Complexity::Linearithmic,
+            sizes(),
+            heights,
+            max_area::Solution2::max_area,
+        ),
+        // NOTE: the same operations per node on random links and on the path / star, but only
+        // the random ones fit n log n. Measured (release, 2 MiB L2): arena/tree takes 68-74 ns per
+        // node up to 16k nodes, then 89, 150 and 119 ns at 32k-128k, while arena/tree_path stays
+        // at 48-50 ns on every size; the graphs and the Rc versions go the same way. The work is
+        // linear, the random hops stop fitting in the cache, so the path / star benchmarks carry
+        // the Linear bounds (they're also the worst case for any per-insert walk).
+        Benchmark::new(
+            "arena/tree",
+            Complexity::Linearithmic,
+            sizes(),
+            random_links,
+            |(parents, _)| arena_tree_sum(&parents),
+        ),
+        Benchmark::new(
+            "arena/tree_path",
+            Complexity::Linear,
+            sizes(),
+            path_and_star,
+            |(parents, _)| arena_tree_sum(&parents),
+        ),
+        // NOTE: no path for the Rc tree, dropping a long chain of Rc children recurses once per
+        // node and overflows the stack (see PList's Drop)
+        Benchmark::new(
+            "rc_refcell/tree",
+            Complexity::Linearithmic,
+            sizes(),
+            random_links,
+            |(parents, _)| rc_tree_sum(&parents),
+        ),
+        Benchmark::new(
+            "arena/graph",
+            Complexity::Linearithmic,
+            sizes(),
+            random_links,
+            |(_, edges)| arena_graph_reach(&edges),
+        ),
+        Benchmark::new(
+            "arena/graph_star",
+            Complexity::Linear,
+            sizes(),
+            path_and_star,
+            |(_, edges)| arena_graph_reach(&edges),
+        ),
+        Benchmark::new(
+            "rc_refcell/graph",
+            Complexity::Linearithmic,
+            sizes(),
+            random_links,
+            |(_, edges)| rc_graph_reach(&edges),
+        ),
+        Benchmark::new(
+            "rc_refcell/graph_star",
+            Complexity::Linear,
+            sizes(),
+            path_and_star,
+            |(_, edges)| rc_graph_reach(&edges),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // c·f(n), times a deterministic wobble of up to ±`noise` (relative)
+    fn synthetic(complexity: Complexity, c: f64, noise: f64) -> Vec<Sample> {
+        let mut rng = StdRng::seed_from_u64(42);
+        (10..=20)
+            .map(|shift| {
+                let n = 1 << shift;
+                let wobble = 1.0 + rng.gen_range(-noise..=noise);
+                let nanos = c * complexity.cost(n) * wobble;
+                Sample {
+                    n,
+                    time: Duration::from_nanos(nanos as u64),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fits_synthetic_timings() {
+        for complexity in Complexity::ALL {
+            for noise in [0.0, 0.05] {
+                let fit = fit(&synthetic(complexity, 3.0, noise)).unwrap();
+                assert_eq!(fit.complexity, complexity, "noise {noise}");
+                assert!((fit.constant / 3.0 - 1.0).abs() < 0.1, "{fit:?}");
+            }
+        }
+        assert_eq!(fit(&[]), None);
+        let same_size = [Sample {
+            n: 10,
+            time: Duration::from_nanos(5),
+        }; 3];
+        assert_eq!(fit(&same_size), None);
+    }
+
+    #[test]
+    fn regressions_and_csv_round_trip() {
+        let report = |name: &str, bound, actual| {
+            let samples = synthetic(actual, 1.0, 0.0);
+            Report {
+                name: name.to_string(),
+                bound,
+                fit: fit(&samples).unwrap(),
+                samples,
+            }
+        };
+        let within = report("within", Complexity::Quadratic, Complexity::Linearithmic);
+        let worse = report("worse", Complexity::Linear, Complexity::Quadratic);
+        assert_eq!(within.regression(None), None);
+        assert_eq!(within.regression(Some(Complexity::Linearithmic)), None);
+        assert!(within.regression(Some(Complexity::Linear)).is_some());
+        assert_eq!(
+            worse.regression(None).unwrap(),
+            "worse: grows like n^2, bound is n"
+        );
+
+        let mut csv = vec![];
+        write_csv(&[within, worse], &mut csv).unwrap();
+        let fits = read_fits(csv.as_slice()).unwrap();
+        assert_eq!(fits["within"], Complexity::Linearithmic);
+        assert_eq!(fits["worse"], Complexity::Quadratic);
+        assert!(read_fits("header\nonly,three,fields\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn arena_and_rc_baselines_agree() {
+        for n in [0, 1, 2, 100, 1000] {
+            for (parents, edges) in [random_links(n), path_and_star(n)] {
+                if n > 0 {
+                    assert_eq!(arena_tree_sum(&parents), rc_tree_sum(&parents));
+                }
+                assert_eq!(arena_graph_reach(&edges), rc_graph_reach(&edges));
+            }
+        }
+        // every vertex hangs off the star's center
+        assert_eq!(arena_graph_reach(&path_and_star(1000).1), 1000);
+    }
+
+    #[test]
+    fn measures_every_size() {
+        let benchmark = Benchmark::new(
+            "sum",
+            Complexity::Linear,
+            vec![10, 100, 1000],
+            |n| vec![1u64; n],
+            |numbers| numbers.iter().sum::<u64>(),
+        );
+        let sizes = benchmark.measure(2).iter().map(|s| s.n).collect::<Vec<_>>();
+        assert_eq!(sizes, [10, 100, 1000]);
+    }
+}
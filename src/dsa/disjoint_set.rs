@@ -0,0 +1,366 @@
+// == Disjoint sets (union-find) ==
+/* Elements 0..n in disjoint sets, each set a tree whose root is its representative.
+ *
+ * - DisjointSet: union by rank (the shorter tree goes under the taller one) and path compression
+ *   (find points everything it walks through straight at the root), so any sequence of operations
+ *   is O(α(n)) amortized each, practically constant.
+ * - RollbackDisjointSet: no path compression (find doesn't modify anything), only union by rank,
+ *   so find is O(log n) but every union can be undone exactly, most recent first.
+ *
+ * Rollback is what offline dynamic connectivity needs: edges that come and go over time are put on
+ * a segment tree over time, and a DFS over it unions an edge when entering a node covering its
+ * lifetime and rolls it back when leaving. Each edge is unioned O(log q) times for q operations.
+ */
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct DisjointSet {
+    parents: Vec<usize>,
+    ranks: Vec<u8>,
+    /// only meaningful for roots
+    sizes: Vec<usize>,
+    set_count: usize,
+}
+
+impl DisjointSet {
+    /// `len` singletons
+    pub fn new(len: usize) -> Self {
+        DisjointSet {
+            parents: (0..len).collect(),
+            ranks: vec![0; len],
+            sizes: vec![1; len],
+            set_count: len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    pub fn set_count(&self) -> usize {
+        self.set_count
+    }
+
+    /// Representative of x's set
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        // second pass, everything on the way now points at the root
+        let mut current = x;
+        while self.parents[current] != root {
+            current = std::mem::replace(&mut self.parents[current], root);
+        }
+        root
+    }
+
+    /// Merges the sets of a and b, false if they already were the same set
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.ranks[a] < self.ranks[b] {
+            (a, b) = (b, a);
+        }
+        self.parents[b] = a;
+        self.sizes[a] += self.sizes[b];
+        if self.ranks[a] == self.ranks[b] {
+            self.ranks[a] += 1;
+        }
+        self.set_count -= 1;
+        true
+    }
+
+    pub fn connected(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// Number of elements in x's set
+    pub fn size_of(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.sizes[root]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RollbackDisjointSet {
+    parents: Vec<usize>,
+    ranks: Vec<u8>,
+    sizes: Vec<usize>,
+    set_count: usize,
+    /// one entry per successful union: (root that went under, whether the other root's rank grew)
+    history: Vec<(usize, bool)>,
+}
+
+impl RollbackDisjointSet {
+    pub fn new(len: usize) -> Self {
+        RollbackDisjointSet {
+            parents: (0..len).collect(),
+            ranks: vec![0; len],
+            sizes: vec![1; len],
+            set_count: len,
+            history: vec![],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    pub fn set_count(&self) -> usize {
+        self.set_count
+    }
+
+    pub fn find(&self, mut x: usize) -> usize {
+        while self.parents[x] != x {
+            x = self.parents[x];
+        }
+        x
+    }
+
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.ranks[a] < self.ranks[b] {
+            (a, b) = (b, a);
+        }
+        let grew = self.ranks[a] == self.ranks[b];
+        self.parents[b] = a;
+        self.sizes[a] += self.sizes[b];
+        if grew {
+            self.ranks[a] += 1;
+        }
+        self.set_count -= 1;
+        self.history.push((b, grew));
+        true
+    }
+
+    pub fn connected(&self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    pub fn size_of(&self, x: usize) -> usize {
+        self.sizes[self.find(x)]
+    }
+
+    /// A point to `rollback` to, the number of successful unions so far
+    pub fn snapshot(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Undoes the last successful union, false if there's none
+    pub fn undo(&mut self) -> bool {
+        let Some((child, grew)) = self.history.pop() else {
+            return false;
+        };
+        let root = self.parents[child];
+        self.parents[child] = child;
+        self.sizes[root] -= self.sizes[child];
+        if grew {
+            self.ranks[root] -= 1;
+        }
+        self.set_count += 1;
+        true
+    }
+
+    /// Undoes every union made after `snapshot` was taken
+    pub fn rollback(&mut self, snapshot: usize) {
+        while self.history.len() > snapshot {
+            self.undo();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    AddEdge(usize, usize),
+    /// removes one copy of the edge, which must be there
+    RemoveEdge(usize, usize),
+    Connected(usize, usize),
+}
+
+/// Answers every `Connected` query (in order) of a sequence of edge additions and removals on
+/// `vertex_count` vertices, O(q log q log n) for q operations
+pub fn offline_connectivity(vertex_count: usize, operations: &[Connectivity]) -> Vec<bool> {
+    let q = operations.len();
+    // edges present over [added, removed) of the operation indices
+    let mut lifetimes = vec![];
+    let mut open = HashMap::<(usize, usize), Vec<usize>>::new();
+    for (time, operation) in operations.iter().enumerate() {
+        match *operation {
+            Connectivity::AddEdge(u, v) => open.entry((u.min(v), u.max(v))).or_default().push(time),
+            Connectivity::RemoveEdge(u, v) => {
+                let edge = (u.min(v), u.max(v));
+                let added = open
+                    .get_mut(&edge)
+                    .and_then(Vec::pop)
+                    .unwrap_or_else(|| panic!("removing the missing edge {u} - {v}"));
+                lifetimes.push((added, time, edge));
+            }
+            Connectivity::Connected(..) => {}
+        }
+    }
+    for (edge, times) in open {
+        lifetimes.extend(times.into_iter().map(|added| (added, q, edge)));
+    }
+
+    // edges[node] = edges alive during the node's whole time segment (and not its parent's)
+    fn insert(
+        edges: &mut [Vec<(usize, usize)>],
+        (node, lo, hi): (usize, usize, usize),
+        (from, to): (usize, usize),
+        edge: (usize, usize),
+    ) {
+        if to <= lo || hi <= from {
+            return;
+        }
+        if from <= lo && hi <= to {
+            edges[node].push(edge);
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        insert(edges, (2 * node, lo, mid), (from, to), edge);
+        insert(edges, (2 * node + 1, mid, hi), (from, to), edge);
+    }
+
+    // DFS over the time segments, the edges of a node are unioned for its whole subtree
+    fn answer(
+        edges: &[Vec<(usize, usize)>],
+        operations: &[Connectivity],
+        sets: &mut RollbackDisjointSet,
+        (node, lo, hi): (usize, usize, usize),
+        answers: &mut Vec<bool>,
+    ) {
+        let snapshot = sets.snapshot();
+        for &(u, v) in &edges[node] {
+            sets.union(u, v);
+        }
+        if hi - lo == 1 {
+            if let Connectivity::Connected(u, v) = operations[lo] {
+                answers.push(sets.connected(u, v));
+            }
+        } else {
+            let mid = lo + (hi - lo) / 2;
+            answer(edges, operations, sets, (2 * node, lo, mid), answers);
+            answer(edges, operations, sets, (2 * node + 1, mid, hi), answers);
+        }
+        sets.rollback(snapshot);
+    }
+
+    let mut answers = vec![];
+    if q == 0 {
+        return answers;
+    }
+    let mut edges = vec![vec![]; 4 * q];
+    for (from, to, edge) in lifetimes {
+        insert(&mut edges, (1, 0, q), (from, to), edge);
+    }
+    let mut sets = RollbackDisjointSet::new(vertex_count);
+    answer(&edges, operations, &mut sets, (1, 0, q), &mut answers);
+    answers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    // model: a label per element, union relabels a whole set
+    #[test]
+    fn matches_labels_model() {
+        let mut rng = StdRng::seed_from_u64(38);
+        let n = 40;
+        let mut labels = (0..n).collect::<Vec<usize>>();
+        let mut sets = DisjointSet::new(n);
+        let mut rollback = RollbackDisjointSet::new(n);
+        for _ in 0..2000 {
+            let (a, b) = (rng.gen_range(0..n), rng.gen_range(0..n));
+            if rng.gen_bool(0.3) {
+                let merged = labels[a] != labels[b];
+                let (from, to) = (labels[b], labels[a]);
+                labels
+                    .iter_mut()
+                    .filter(|l| **l == from)
+                    .for_each(|l| *l = to);
+                assert_eq!(sets.union(a, b), merged);
+                assert_eq!(rollback.union(a, b), merged);
+            }
+            let same = labels[a] == labels[b];
+            let size = labels.iter().filter(|&&l| l == labels[a]).count();
+            assert_eq!((sets.connected(a, b), sets.size_of(a)), (same, size));
+            assert_eq!(
+                (rollback.connected(a, b), rollback.size_of(a)),
+                (same, size)
+            );
+            let mut distinct = labels.clone();
+            distinct.sort();
+            distinct.dedup();
+            assert_eq!(sets.set_count(), distinct.len());
+            assert_eq!(rollback.set_count(), distinct.len());
+        }
+    }
+
+    #[test]
+    fn rollback_restores_earlier_states() {
+        let mut sets = RollbackDisjointSet::new(6);
+        sets.union(0, 1);
+        let snapshot = sets.snapshot();
+        sets.union(2, 3);
+        assert!(!sets.union(1, 0)); // not recorded, nothing to undo
+        sets.union(1, 3);
+        assert_eq!((sets.size_of(0), sets.set_count()), (4, 3));
+
+        sets.rollback(snapshot);
+        assert_eq!((sets.size_of(0), sets.set_count()), (2, 5));
+        assert!(sets.connected(0, 1) && !sets.connected(2, 3));
+        assert!(sets.undo());
+        assert!(!sets.undo());
+        assert_eq!(sets.set_count(), 6);
+    }
+
+    #[test]
+    fn offline_connectivity_matches_replaying() {
+        let mut rng = StdRng::seed_from_u64(39);
+        let n = 8;
+        let mut edges = vec![];
+        let mut operations = vec![];
+        let mut expected = vec![];
+        for _ in 0..400 {
+            let (u, v) = (rng.gen_range(0..n), rng.gen_range(0..n));
+            match rng.gen_range(0..3) {
+                0 => {
+                    edges.push((u, v));
+                    operations.push(Connectivity::AddEdge(u, v));
+                }
+                1 if !edges.is_empty() => {
+                    let (u, v) = edges.swap_remove(rng.gen_range(0..edges.len()));
+                    operations.push(Connectivity::RemoveEdge(v, u)); // either direction
+                }
+                _ => {
+                    // replay: union-find from scratch over the current edges
+                    let mut sets = DisjointSet::new(n);
+                    edges.iter().for_each(|&(a, b)| {
+                        sets.union(a, b);
+                    });
+                    expected.push(sets.connected(u, v));
+                    operations.push(Connectivity::Connected(u, v));
+                }
+            }
+        }
+        assert_eq!(offline_connectivity(n, &operations), expected);
+        assert_eq!(offline_connectivity(n, &[]), []);
+    }
+}
@@ -0,0 +1,600 @@
+// == Dynamic programming ==
+/* Two ways to write a DP, plus the classic ones written with them.
+ *
+ * Top down: `memoize`. A closure can't call itself (it has no name to call, and a closure that
+ * captures itself would be an infinitely sized type), so the recursive call is handed to it as
+ * its first argument instead:
+ *
+ *     let mut fib = memoize(|fib, n: u64| if n < 2 { n } else { fib(n - 1) + fib(n - 2) });
+ *     assert_eq!(fib(90), 2880067194370816120);
+ *
+ * Every key is computed once and kept in a HashMap. It's still recursion, so the depth of the
+ * call chain is bounded by the stack (a few 10k levels), the table is better for long chains.
+ *
+ * Bottom up: `Table::build` fills a rows x cols table in row-major order, handing every cell the
+ * table built so far, so cells can only read cells above or to their left (reading any other
+ * one panics instead of reading garbage).
+ *
+ * The solvers return how to get the optimum (indices, edits, counts), not just its value, by
+ * walking back through the table from the last cell.
+ */
+
+use std::{collections::HashMap, hash::Hash, ops::Index};
+
+// the recursion behind `memoize`, the `recurse` handed to `f` is this function again
+fn memoized<K, V, F>(f: &F, cache: &mut HashMap<K, V>, key: K) -> V
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    F: Fn(&mut dyn FnMut(K) -> V, K) -> V,
+{
+    if let Some(value) = cache.get(&key) {
+        return value.clone();
+    }
+    let value = f(&mut |key| memoized(f, cache, key), key.clone());
+    cache.insert(key, value.clone());
+    value
+}
+
+/// Turns `f(recurse, key)` into a function of `key` computing every key once, `recurse` being the
+/// memoized function itself
+pub fn memoize<K, V, F>(f: F) -> impl FnMut(K) -> V
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    F: Fn(&mut dyn FnMut(K) -> V, K) -> V,
+{
+    let mut cache = HashMap::new();
+    move |key| memoized(&f, &mut cache, key)
+}
+
+/// A rows x cols table in one Vec, indexed by (row, col)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table<T> {
+    rows: usize,
+    cols: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Table<T> {
+    /// Fills the table in row-major order, `cell(table, row, col)` can read the cells already
+    /// filled: the rows above, and the cells to the left in the same row
+    pub fn build(
+        rows: usize,
+        cols: usize,
+        mut cell: impl FnMut(&Table<T>, usize, usize) -> T,
+    ) -> Self {
+        let mut table = Table {
+            rows,
+            cols,
+            cells: Vec::with_capacity(rows * cols),
+        };
+        for row in 0..rows {
+            for col in 0..cols {
+                let value = cell(&table, row, col);
+                table.cells.push(value);
+            }
+        }
+        table
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// None outside the table, or for a cell not filled yet
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if col >= self.cols {
+            return None;
+        }
+        self.cells.get(row * self.cols + col)
+    }
+}
+
+impl<T> Index<(usize, usize)> for Table<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        self.get(row, col).unwrap_or_else(|| {
+            panic!(
+                "cell ({row}, {col}) is outside the {}x{} table or not filled yet",
+                self.rows, self.cols
+            )
+        })
+    }
+}
+
+// == Longest increasing subsequence ==
+
+/// Indices of a longest strictly increasing subsequence, O(n log n).
+///
+/// tails[l] is the index of the smallest value ending an increasing subsequence of length l + 1
+/// (smaller is better, more values can follow it), every value extends the longest one it's
+/// greater than, and remembers what it extended
+pub fn longest_increasing_subsequence<T: Ord>(values: &[T]) -> Vec<usize> {
+    let mut tails: Vec<usize> = vec![];
+    let mut previous = vec![None; values.len()];
+    for (i, value) in values.iter().enumerate() {
+        let length = tails.partition_point(|&t| values[t] < *value);
+        previous[i] = length.checked_sub(1).map(|l| tails[l]);
+        if length == tails.len() {
+            tails.push(i);
+        } else {
+            tails[length] = i;
+        }
+    }
+    let mut indices =
+        std::iter::successors(tails.last().copied(), |&i| previous[i]).collect::<Vec<_>>();
+    indices.reverse();
+    indices
+}
+
+// == Edit distance ==
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+    /// a[i] == b[j], free
+    Keep(usize, usize),
+    /// a[i] becomes b[j]
+    Substitute(usize, usize),
+    /// b[j] is inserted
+    Insert(usize),
+    /// a[i] is deleted
+    Delete(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alignment {
+    /// Levenshtein distance: substitutions, insertions and deletions all cost 1
+    pub distance: usize,
+    /// how to turn `a` into `b`, in order
+    pub edits: Vec<Edit>,
+}
+
+/// Levenshtein distance between `a` and `b` with the edits achieving it, O(|a|·|b|).
+/// Use `.chars().collect::<Vec<_>>()` for strings, so that a multi-byte char is one symbol
+pub fn edit_distance<T: PartialEq>(a: &[T], b: &[T]) -> Alignment {
+    // distance[i][j]: a[..i] into b[..j]
+    let distance = Table::build(a.len() + 1, b.len() + 1, |d: &Table<usize>, i, j| {
+        match (i, j) {
+            (0, j) => j,
+            (i, 0) => i,
+            _ => {
+                let substitution = d[(i - 1, j - 1)] + usize::from(a[i - 1] != b[j - 1]);
+                substitution.min(d[(i - 1, j)] + 1).min(d[(i, j - 1)] + 1)
+            }
+        }
+    });
+
+    let mut edits = vec![];
+    let (mut i, mut j) = (a.len(), b.len());
+    while i > 0 || j > 0 {
+        let here = distance[(i, j)];
+        if i > 0 && j > 0 && a[i - 1] == b[j - 1] && here == distance[(i - 1, j - 1)] {
+            edits.push(Edit::Keep(i - 1, j - 1));
+            (i, j) = (i - 1, j - 1);
+        } else if i > 0 && j > 0 && here == distance[(i - 1, j - 1)] + 1 {
+            edits.push(Edit::Substitute(i - 1, j - 1));
+            (i, j) = (i - 1, j - 1);
+        } else if i > 0 && here == distance[(i - 1, j)] + 1 {
+            edits.push(Edit::Delete(i - 1));
+            i -= 1;
+        } else {
+            edits.push(Edit::Insert(j - 1));
+            j -= 1;
+        }
+    }
+    edits.reverse();
+    Alignment {
+        distance: distance[(a.len(), b.len())],
+        edits,
+    }
+}
+
+// == Longest common subsequence ==
+
+/// Index pairs (i, j) with a[i] == b[j] of a longest common subsequence, O(|a|·|b|)
+pub fn longest_common_subsequence<T: PartialEq>(a: &[T], b: &[T]) -> Vec<(usize, usize)> {
+    // length[i][j]: LCS of a[..i] and b[..j]
+    let length = Table::build(a.len() + 1, b.len() + 1, |l: &Table<usize>, i, j| {
+        if i == 0 || j == 0 {
+            0
+        } else if a[i - 1] == b[j - 1] {
+            l[(i - 1, j - 1)] + 1
+        } else {
+            l[(i - 1, j)].max(l[(i, j - 1)])
+        }
+    });
+
+    let mut pairs = vec![];
+    let (mut i, mut j) = (a.len(), b.len());
+    while i > 0 && j > 0 {
+        if a[i - 1] == b[j - 1] {
+            pairs.push((i - 1, j - 1));
+            (i, j) = (i - 1, j - 1);
+        } else if length[(i - 1, j)] >= length[(i, j - 1)] {
+            i -= 1;
+        } else {
+            j -= 1;
+        }
+    }
+    pairs.reverse();
+    pairs
+}
+
+// == Knapsack ==
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Item {
+    pub weight: usize,
+    pub value: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Knapsack {
+    /// the best total value
+    pub value: u64,
+    /// how many of each item (same order as the items) to take for it
+    pub counts: Vec<usize>,
+}
+
+/// Each item at most once, O(items·capacity)
+pub fn knapsack_01(items: &[Item], capacity: usize) -> Knapsack {
+    // best[k][w]: the first k items within weight w
+    let best = Table::build(items.len() + 1, capacity + 1, |best, k, w| {
+        if k == 0 {
+            return 0;
+        }
+        let item = items[k - 1];
+        let without = best[(k - 1, w)];
+        match w.checked_sub(item.weight) {
+            Some(rest) => without.max(best[(k - 1, rest)] + item.value),
+            None => without,
+        }
+    });
+
+    let mut counts = vec![0; items.len()];
+    let mut w = capacity;
+    for k in (1..=items.len()).rev() {
+        if best[(k, w)] != best[(k - 1, w)] {
+            counts[k - 1] = 1;
+            w -= items[k - 1].weight;
+        }
+    }
+    Knapsack {
+        value: best[(items.len(), capacity)],
+        counts,
+    }
+}
+
+/// Each item as many times as wanted, O(items·capacity)
+///
+/// Panics if an item weighs 0, taking it over and over would be worth infinitely much
+pub fn knapsack_unbounded(items: &[Item], capacity: usize) -> Knapsack {
+    if let Some(item) = items.iter().find(|item| item.weight == 0) {
+        panic!("items need a positive weight, got {item:?}");
+    }
+    // best[w] within weight w, and the item taken last for it (None: nothing fits, or nothing
+    // is worth it)
+    let mut best = vec![(0, None); capacity + 1];
+    for w in 1..=capacity {
+        best[w] = best[w - 1];
+        for (i, item) in items.iter().enumerate() {
+            if let Some(rest) = w.checked_sub(item.weight) {
+                let value = best[rest].0 + item.value;
+                if value > best[w].0 {
+                    best[w] = (value, Some(i));
+                }
+            }
+        }
+    }
+
+    let mut counts = vec![0; items.len()];
+    let mut w = capacity;
+    while w > 0 {
+        match best[w].1 {
+            // best[w] is a copy of best[w - 1] unless taking item i (last) did better
+            Some(i) if best[w].0 > best[w - 1].0 => {
+                counts[i] += 1;
+                w -= items[i].weight;
+            }
+            _ => w -= 1,
+        }
+    }
+    Knapsack {
+        value: best[capacity].0,
+        counts,
+    }
+}
+
+/// Item i at most `limits[i]` times. Splits every item into bundles of 1, 2, 4, .. copies (any
+/// count up to the limit is a sum of some of them) and solves that as a 0/1 knapsack, so
+/// O(capacity · Σ log limits) instead of O(capacity · Σ limits)
+pub fn knapsack_bounded(items: &[Item], limits: &[usize], capacity: usize) -> Knapsack {
+    assert_eq!(items.len(), limits.len(), "one limit per item");
+    // (item, copies)
+    let mut bundles = vec![];
+    for (i, &limit) in limits.iter().enumerate() {
+        let mut left = limit;
+        let mut size = 1;
+        while left > 0 {
+            let copies = size.min(left);
+            bundles.push((i, copies));
+            left -= copies;
+            size *= 2;
+        }
+    }
+    let bundled = bundles
+        .iter()
+        .map(|&(i, copies)| Item {
+            weight: items[i].weight * copies,
+            value: items[i].value * copies as u64,
+        })
+        .collect::<Vec<_>>();
+
+    let solution = knapsack_01(&bundled, capacity);
+    let mut counts = vec![0; items.len()];
+    for (&(i, copies), &taken) in bundles.iter().zip(&solution.counts) {
+        counts[i] += copies * taken;
+    }
+    Knapsack {
+        value: solution.value,
+        counts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsa::stress::{check, Config, Gen};
+
+    #[test]
+    fn memoize_recurses() {
+        let mut fib = memoize(|fib, n: u64| if n < 2 { n } else { fib(n - 1) + fib(n - 2) });
+        assert_eq!(fib(90), 2880067194370816120);
+
+        // several arguments go in a tuple: paths in a grid going right or down
+        let mut paths = memoize(|paths, (r, c): (u32, u32)| match (r, c) {
+            (0, _) | (_, 0) => 1u64,
+            _ => paths((r - 1, c)) + paths((r, c - 1)),
+        });
+        assert_eq!(paths((16, 16)), 601080390);
+    }
+
+    #[test]
+    #[should_panic(expected = "not filled yet")]
+    fn table_cells_only_see_filled_cells() {
+        Table::build(2, 2, |t, r, c| if r == 0 { c } else { t[(r, c + 1)] });
+    }
+
+    fn word(g: &mut Gen, max_len: usize, alphabet: i32) -> Vec<i32> {
+        g.vec(0..=max_len, |g| g.int(0..=alphabet - 1))
+    }
+
+    #[test]
+    fn lis_matches_brute_force() {
+        check(
+            "longest_increasing_subsequence",
+            Config::default(),
+            |g| word(g, 12, 10),
+            |values| {
+                (0u32..1 << values.len())
+                    .filter(|mask| {
+                        let picked = (0..values.len()).filter(|i| mask & (1 << i) != 0);
+                        let picked = picked.map(|i| values[i]).collect::<Vec<_>>();
+                        picked.windows(2).all(|w| w[0] < w[1])
+                    })
+                    .map(|mask| mask.count_ones() as usize)
+                    .max()
+                    .unwrap()
+            },
+            |values| {
+                let lis = longest_increasing_subsequence(values);
+                assert!(
+                    lis.windows(2)
+                        .all(|w| w[0] < w[1] && values[w[0]] < values[w[1]]),
+                    "indices {lis:?} aren't an increasing subsequence"
+                );
+                lis.len()
+            },
+        );
+    }
+
+    #[test]
+    fn edit_distance_and_lcs_match_recursion() {
+        // (distance, lcs length), the edits and pairs themselves are checked in place
+        check(
+            "edit_distance and longest_common_subsequence",
+            Config::default(),
+            |g| (word(g, 9, 3), word(g, 9, 3)),
+            |(a, b)| {
+                let mut distance = memoize(|d, (i, j): (usize, usize)| -> usize {
+                    match (i, j) {
+                        (0, j) => j,
+                        (i, 0) => i,
+                        _ => (d((i - 1, j - 1)) + usize::from(a[i - 1] != b[j - 1]))
+                            .min(d((i - 1, j)) + 1)
+                            .min(d((i, j - 1)) + 1),
+                    }
+                });
+                let mut lcs = memoize(|l, (i, j): (usize, usize)| -> usize {
+                    match (i, j) {
+                        (0, _) | (_, 0) => 0,
+                        _ if a[i - 1] == b[j - 1] => l((i - 1, j - 1)) + 1,
+                        _ => l((i - 1, j)).max(l((i, j - 1))),
+                    }
+                });
+                let (n, m) = (a.len(), b.len());
+                (distance((n, m)), lcs((n, m)))
+            },
+            |(a, b)| {
+                let alignment = edit_distance(a, b);
+                // replaying the edits turns a into b, at that cost
+                let mut out = vec![];
+                let mut cost = 0;
+                for edit in &alignment.edits {
+                    match *edit {
+                        Edit::Keep(i, j) => {
+                            assert_eq!(a[i], b[j], "{edit:?} keeps different values");
+                            out.push(a[i]);
+                        }
+                        Edit::Substitute(_, j) | Edit::Insert(j) => {
+                            cost += 1;
+                            out.push(b[j]);
+                        }
+                        Edit::Delete(_) => cost += 1,
+                    }
+                }
+                assert_eq!(out, *b, "replaying {:?} doesn't give b", alignment.edits);
+                assert_eq!(
+                    cost, alignment.distance,
+                    "{:?} don't cost the distance",
+                    alignment.edits
+                );
+
+                let pairs = longest_common_subsequence(a, b);
+                assert!(
+                    pairs.iter().all(|&(i, j)| a[i] == b[j])
+                        && pairs.windows(2).all(|w| w[0].0 < w[1].0 && w[0].1 < w[1].1),
+                    "pairs {pairs:?} aren't a common subsequence"
+                );
+                (alignment.distance, pairs.len())
+            },
+        );
+
+        let kitten = "kitten".chars().collect::<Vec<_>>();
+        let sitting = "sitting".chars().collect::<Vec<_>>();
+        assert_eq!(edit_distance(&kitten, &sitting).distance, 3);
+    }
+
+    // every combination of counts up to the limits
+    fn brute_force_knapsack(items: &[Item], limits: &[usize], capacity: usize) -> u64 {
+        let mut best = 0;
+        let mut counts = vec![0; items.len()];
+        loop {
+            let weight = counts
+                .iter()
+                .zip(items)
+                .map(|(c, i)| c * i.weight)
+                .sum::<usize>();
+            if weight <= capacity {
+                let value = counts.iter().zip(items).map(|(&c, i)| c as u64 * i.value);
+                best = best.max(value.sum());
+            }
+            // next combination, like counting with digit k going up to limits[k]
+            let Some(k) = (0..items.len()).find(|&k| counts[k] < limits[k]) else {
+                return best;
+            };
+            counts[k] += 1;
+            counts[..k].iter_mut().for_each(|c| *c = 0);
+        }
+    }
+
+    // the value of the solution, after checking it fits, respects the limits and is worth that much
+    fn checked(
+        name: &str,
+        items: &[Item],
+        capacity: usize,
+        solution: &Knapsack,
+        limits: &[usize],
+    ) -> u64 {
+        let weight = solution.counts.iter().zip(items).map(|(c, i)| c * i.weight);
+        let weight = weight.sum::<usize>();
+        assert!(weight <= capacity, "{name}: {solution:?} weighs {weight}");
+        let value = solution
+            .counts
+            .iter()
+            .zip(items)
+            .map(|(&c, i)| c as u64 * i.value);
+        let value = value.sum::<u64>();
+        assert_eq!(
+            value, solution.value,
+            "{name}: {solution:?} is worth {value}"
+        );
+        assert!(
+            solution.counts.iter().zip(limits).all(|(c, l)| c <= l),
+            "{name}: {solution:?} goes over the limits {limits:?}"
+        );
+        solution.value
+    }
+
+    // (weight - 1, value, limit) per item, weights stay positive however the input shrinks
+    type KnapsackInput = (Vec<(usize, u64, usize)>, usize);
+
+    fn knapsack_input(g: &mut Gen) -> KnapsackInput {
+        let items = g.vec(0..=4, |g| (g.int(0..=6), g.int(0..=19), g.int(0..=3)));
+        (items, g.int(0..=19))
+    }
+
+    // items, and the limits of the 0/1, unbounded and bounded knapsacks
+    fn knapsacks((raw, capacity): &KnapsackInput) -> (Vec<Item>, [Vec<usize>; 3]) {
+        let items = raw
+            .iter()
+            .map(|&(weight, value, _)| Item {
+                weight: weight + 1,
+                value,
+            })
+            .collect::<Vec<_>>();
+        // capacity / weight copies of anything is as good as unbounded
+        let unbounded = items.iter().map(|i| capacity / i.weight).collect();
+        let limits = raw.iter().map(|&(_, _, limit)| limit).collect();
+        (items, [vec![1; raw.len()], unbounded, limits])
+    }
+
+    #[test]
+    fn knapsacks_match_brute_force() {
+        check(
+            "knapsack_01, knapsack_unbounded and knapsack_bounded",
+            Config::default(),
+            knapsack_input,
+            |input| {
+                let (items, limits) = knapsacks(input);
+                limits.map(|limits| brute_force_knapsack(&items, &limits, input.1))
+            },
+            |input| {
+                let (items, [ones, unbounded, limits]) = knapsacks(input);
+                let capacity = input.1;
+                [
+                    checked(
+                        "knapsack_01",
+                        &items,
+                        capacity,
+                        &knapsack_01(&items, capacity),
+                        &ones,
+                    ),
+                    checked(
+                        "knapsack_unbounded",
+                        &items,
+                        capacity,
+                        &knapsack_unbounded(&items, capacity),
+                        &unbounded,
+                    ),
+                    checked(
+                        "knapsack_bounded",
+                        &items,
+                        capacity,
+                        &knapsack_bounded(&items, &limits, capacity),
+                        &limits,
+                    ),
+                ]
+            },
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "items need a positive weight")]
+    fn unbounded_knapsack_rejects_weightless_items() {
+        // regression: used to loop forever, reconstructing with `w -= 0`
+        knapsack_unbounded(
+            &[Item {
+                weight: 0,
+                value: 1,
+            }],
+            1,
+        );
+    }
+}
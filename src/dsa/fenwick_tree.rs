@@ -0,0 +1,257 @@
+// == Fenwick tree (binary indexed tree) ==
+/* Prefix sums with point updates, both O(log n), in a single Vec of n + 1 values.
+ * With 1-based positions, tree[i] holds the sum of the lowbit(i) values ending at i
+ * (lowbit(i) = i & -i, the lowest set bit), so:
+ * - a prefix sum walks down i -= lowbit(i), collecting disjoint blocks
+ * - a point update walks up i += lowbit(i), through every block that covers i
+ *
+ * Less general than SegmentTree (it needs subtraction for range sums, so it's Sum only) but
+ * smaller and faster. RangeAddFenwick flips it around: range updates with point queries, by keeping
+ * a Fenwick tree over the differences between neighbouring values.
+ */
+
+use std::ops::{Add, Neg, RangeBounds, Sub};
+
+use super::{monoid::Zero, segment_tree::to_half_open};
+
+fn lowbit(i: usize) -> usize {
+    i & i.wrapping_neg()
+}
+
+#[derive(Debug, Clone)]
+pub struct FenwickTree<T> {
+    /// 1-based, tree[0] is unused
+    tree: Vec<T>,
+}
+
+impl<T: Zero + Add<Output = T> + Clone> FenwickTree<T> {
+    /// `len` zeros
+    pub fn new(len: usize) -> Self {
+        FenwickTree {
+            tree: vec![T::zero(); len + 1],
+        }
+    }
+
+    /// O(n), every block pushes its sum to the next block covering it
+    pub fn from_vec(values: Vec<T>) -> Self {
+        let mut tree = Vec::with_capacity(values.len() + 1);
+        tree.push(T::zero());
+        tree.extend(values);
+        for i in 1..tree.len() {
+            let parent = i + lowbit(i);
+            if parent < tree.len() {
+                tree[parent] = tree[parent].clone() + tree[i].clone();
+            }
+        }
+        FenwickTree { tree }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// values[index] += delta
+    pub fn add(&mut self, index: usize, delta: T) {
+        assert!(
+            index < self.len(),
+            "index {index} out of bounds for length {}",
+            self.len()
+        );
+        let mut i = index + 1;
+        while i < self.tree.len() {
+            self.tree[i] = self.tree[i].clone() + delta.clone();
+            i += lowbit(i);
+        }
+    }
+
+    /// Sum of the first `end` values, ie. of [0, end)
+    pub fn prefix_sum(&self, end: usize) -> T {
+        assert!(
+            end <= self.len(),
+            "prefix of {end} out of bounds for length {}",
+            self.len()
+        );
+        let (mut sum, mut i) = (T::zero(), end);
+        while i > 0 {
+            sum = sum + self.tree[i].clone();
+            i -= lowbit(i);
+        }
+        sum
+    }
+}
+
+impl<T: Zero + Add<Output = T> + Sub<Output = T> + Clone> FenwickTree<T> {
+    pub fn range_sum(&self, range: impl RangeBounds<usize>) -> T {
+        let (start, end) = to_half_open(range, self.len());
+        self.prefix_sum(end) - self.prefix_sum(start)
+    }
+
+    pub fn get(&self, index: usize) -> T {
+        self.range_sum(index..=index)
+    }
+
+    pub fn set(&mut self, index: usize, value: T) {
+        let delta = value - self.get(index);
+        self.add(index, delta);
+    }
+}
+
+impl<T: Zero + Add<Output = T> + PartialOrd + Clone> FenwickTree<T> {
+    /// Smallest index i with prefix_sum(i + 1) >= target, None if the total is below it
+    ///
+    /// With counts as values (values[v] = how many times v was seen), `search(k)` is the k-th
+    /// smallest element (1-based k). O(log n), the values must be non-negative so that prefix sums
+    /// only grow.
+    pub fn search(&self, target: T) -> Option<usize> {
+        // descend from the biggest power of two, keeping prefix_sum(position) < target
+        let (mut position, mut sum) = (0, T::zero());
+        let mut step = self.len().checked_next_power_of_two()?;
+        while step > 0 {
+            let next = position + step;
+            if next <= self.len() {
+                let extended = sum.clone() + self.tree[next].clone();
+                if extended < target {
+                    position = next;
+                    sum = extended;
+                }
+            }
+            step /= 2;
+        }
+        // position values sum to < target, so it's the one that reaches it (if any)
+        (position < self.len()).then_some(position)
+    }
+}
+
+impl<T: Zero + Add<Output = T> + Clone> FromIterator<T> for FenwickTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+    }
+}
+
+/// Range updates and point queries: values[i] is the prefix sum of the differences
+///
+/// Signed types (and floats) only, the differences between neighbouring values go negative
+#[derive(Debug, Clone)]
+pub struct RangeAddFenwick<T> {
+    differences: FenwickTree<T>,
+}
+
+impl<T: Zero + Add<Output = T> + Sub<Output = T> + Neg<Output = T> + Clone> RangeAddFenwick<T> {
+    pub fn new(len: usize) -> Self {
+        RangeAddFenwick {
+            differences: FenwickTree::new(len),
+        }
+    }
+
+    pub fn from_vec(values: Vec<T>) -> Self {
+        let mut previous = T::zero();
+        let differences = values
+            .into_iter()
+            .map(|value| {
+                let difference = value.clone() - previous.clone();
+                previous = value;
+                difference
+            })
+            .collect();
+        RangeAddFenwick { differences }
+    }
+
+    pub fn len(&self) -> usize {
+        self.differences.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// values[i] += delta for every i in `range`
+    pub fn add(&mut self, range: impl RangeBounds<usize>, delta: T) {
+        let (start, end) = to_half_open(range, self.len());
+        if start == end {
+            return;
+        }
+        self.differences.add(start, delta.clone());
+        if end < self.len() {
+            self.differences.add(end, -delta);
+        }
+    }
+
+    pub fn get(&self, index: usize) -> T {
+        assert!(
+            index < self.len(),
+            "index {index} out of bounds for length {}",
+            self.len()
+        );
+        self.differences.prefix_sum(index + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for len in [0, 1, 2, 3, 8, 13, 64] {
+            let mut brute = (0..len)
+                .map(|_| rng.gen_range(-100..100))
+                .collect::<Vec<i64>>();
+            let mut tree = FenwickTree::from_vec(brute.clone());
+
+            for _ in 0..200 {
+                if len > 0 && rng.gen_bool(0.3) {
+                    let (i, v) = (rng.gen_range(0..len), rng.gen_range(-100..100));
+                    if rng.gen_bool(0.5) {
+                        brute[i] += v;
+                        tree.add(i, v);
+                    } else {
+                        brute[i] = v;
+                        tree.set(i, v);
+                    }
+                }
+                let l = rng.gen_range(0..=len);
+                let r = rng.gen_range(l..=len);
+                assert_eq!(tree.range_sum(l..r), brute[l..r].iter().sum::<i64>());
+                assert_eq!(tree.prefix_sum(r), brute[..r].iter().sum::<i64>());
+            }
+        }
+    }
+
+    #[test]
+    fn search_finds_the_kth_smallest_from_counts() {
+        // multiset {1, 1, 3, 4, 4, 4, 9} as counts per value
+        let mut counts = FenwickTree::<u32>::new(10);
+        for v in [4, 1, 9, 4, 3, 1, 4] {
+            counts.add(v, 1);
+        }
+        let kth = (1..=7)
+            .map(|k| counts.search(k).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(kth, [1, 1, 3, 4, 4, 4, 9]);
+        assert_eq!(counts.search(8), None);
+        assert_eq!(counts.search(0), Some(0));
+        assert_eq!(FenwickTree::<u32>::new(0).search(1), None);
+    }
+
+    #[test]
+    fn range_add_point_query() {
+        let mut rng = StdRng::seed_from_u64(8);
+        let mut brute = (0..20).map(|_| rng.gen_range(-9..9)).collect::<Vec<i32>>();
+        let mut tree = RangeAddFenwick::from_vec(brute.clone());
+        for _ in 0..200 {
+            let l = rng.gen_range(0..=brute.len());
+            let r = rng.gen_range(l..=brute.len());
+            let delta = rng.gen_range(-9..9);
+            brute[l..r].iter_mut().for_each(|v| *v += delta);
+            tree.add(l..r, delta);
+            let i = rng.gen_range(0..brute.len());
+            assert_eq!(tree.get(i), brute[i]);
+        }
+    }
+}
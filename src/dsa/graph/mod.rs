@@ -0,0 +1,175 @@
+// == Graphs ==
+/* Adjacency-list graphs over vertices 0..n, directed or undirected, with a weight per edge
+ * (`()` for unweighted graphs). An undirected edge is stored in the adjacency lists of both of
+ * its ends, but only once in `edges`.
+ *
+ * They can be built from the same kind of edge list as the trees in docs::smart_pointers:
+ *
+ *     let edges = [(1, 2), (1, 5), (2, 3), (2, 4), (5, 6)];
+ *     let tree = Graph::from_one_based_edges(Kind::Undirected, 6, edges);
+ *
+ * The algorithms live in submodules, as free functions taking the graph:
+ * - traversal: Bfs / Dfs iterators (`graph.bfs(start)`, `graph.dfs(start)`)
+ * - shortest_paths: Dijkstra, Bellman-Ford, Floyd-Warshall
+ * - spanning_tree: Kruskal, Prim
+ * - ordering: topological sort, strongly connected components
+ */
+
+pub mod ordering;
+pub mod shortest_paths;
+pub mod spanning_tree;
+pub mod traversal;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Directed,
+    Undirected,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edge<W> {
+    pub from: usize,
+    pub to: usize,
+    pub weight: W,
+}
+
+/// Vertices in the order they're visited, going back to the first one (which isn't repeated)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle(pub Vec<usize>);
+
+#[derive(Debug, Clone)]
+pub struct Graph<W = ()> {
+    kind: Kind,
+    adjacency: Vec<Vec<(usize, W)>>,
+    edges: Vec<Edge<W>>,
+}
+
+impl<W: Clone> Graph<W> {
+    /// `vertex_count` vertices and no edges
+    pub fn new(kind: Kind, vertex_count: usize) -> Self {
+        Graph {
+            kind,
+            adjacency: vec![vec![]; vertex_count],
+            edges: vec![],
+        }
+    }
+
+    pub fn from_weighted_edges(
+        kind: Kind,
+        vertex_count: usize,
+        edges: impl IntoIterator<Item = (usize, usize, W)>,
+    ) -> Self {
+        let mut graph = Graph::new(kind, vertex_count);
+        for (from, to, weight) in edges {
+            graph.add_edge(from, to, weight);
+        }
+        graph
+    }
+
+    pub fn add_edge(&mut self, from: usize, to: usize, weight: W) {
+        let n = self.vertex_count();
+        assert!(
+            from < n && to < n,
+            "edge {from} -> {to} out of bounds for {n} vertices"
+        );
+        self.adjacency[from].push((to, weight.clone()));
+        if self.kind == Kind::Undirected && from != to {
+            self.adjacency[to].push((from, weight.clone()));
+        }
+        self.edges.push(Edge { from, to, weight });
+    }
+
+    /// Same graph with every edge flipped (the same graph for undirected ones)
+    pub fn reversed(&self) -> Self {
+        Graph::from_weighted_edges(
+            self.kind,
+            self.vertex_count(),
+            self.edges
+                .iter()
+                .map(|edge| (edge.to, edge.from, edge.weight.clone())),
+        )
+    }
+}
+
+impl<W> Graph<W> {
+    pub fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.kind == Kind::Directed
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    /// Every edge once, even for undirected graphs
+    pub fn edges(&self) -> &[Edge<W>] {
+        &self.edges
+    }
+
+    /// (vertex, weight) of the edges leaving `vertex`, in insertion order
+    pub fn neighbours(&self, vertex: usize) -> impl Iterator<Item = (usize, &W)> {
+        self.adjacency[vertex]
+            .iter()
+            .map(|(to, weight)| (*to, weight))
+    }
+}
+
+impl Graph<()> {
+    pub fn from_edges(
+        kind: Kind,
+        vertex_count: usize,
+        edges: impl IntoIterator<Item = (usize, usize)>,
+    ) -> Self {
+        Graph::from_weighted_edges(
+            kind,
+            vertex_count,
+            edges.into_iter().map(|(from, to)| (from, to, ())),
+        )
+    }
+
+    /// Edges between vertices numbered 1..=vertex_count (vertex k becomes k - 1)
+    pub fn from_one_based_edges(
+        kind: Kind,
+        vertex_count: usize,
+        edges: impl IntoIterator<Item = (usize, usize)>,
+    ) -> Self {
+        Graph::from_edges(
+            kind,
+            vertex_count,
+            edges.into_iter().map(|(from, to)| {
+                assert!(
+                    from > 0 && to > 0,
+                    "vertex 0 in one-based edge {from} -> {to}"
+                );
+                (from - 1, to - 1)
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loads_the_smart_pointers_tree() {
+        let edges = [(1, 2), (1, 5), (2, 3), (2, 4), (5, 6)];
+        let tree = Graph::from_one_based_edges(Kind::Undirected, 6, edges);
+        assert_eq!(tree.vertex_count(), 6);
+        assert_eq!(tree.edges().len(), 5);
+
+        let degrees = (0..6)
+            .map(|v| tree.neighbours(v).count())
+            .collect::<Vec<_>>();
+        assert_eq!(degrees, [2, 3, 1, 1, 2, 1]);
+
+        let directed = Graph::from_one_based_edges(Kind::Directed, 6, edges);
+        let in_degrees = (0..6)
+            .map(|v| directed.reversed().neighbours(v).count())
+            .collect::<Vec<_>>();
+        assert_eq!(in_degrees, [0, 1, 1, 1, 1, 1]);
+    }
+}
@@ -0,0 +1,218 @@
+// == Topological order and strongly connected components ==
+/* For directed graphs.
+ *
+ * - topological_sort: Kahn's algorithm, repeatedly taking a vertex nothing points to anymore.
+ *   If it gets stuck, every vertex left has an incoming edge from another vertex left, so walking
+ *   those edges backwards must loop: that loop is the Cycle reported instead of an order.
+ * - strongly_connected_components: Kosaraju, a DFS for finishing order and then one on the
+ *   reversed graph, both iterative. Components come out in topological order of the condensed
+ *   graph (no edge goes from a later component to an earlier one).
+ */
+
+use std::collections::VecDeque;
+
+use super::{Cycle, Graph, Kind};
+
+fn assert_directed<W>(graph: &Graph<W>) {
+    assert_eq!(graph.kind(), Kind::Directed, "needs a directed graph");
+}
+
+/// Every vertex, each before the vertices it has edges to, or one cycle preventing that
+pub fn topological_sort<W: Clone>(graph: &Graph<W>) -> Result<Vec<usize>, Cycle> {
+    assert_directed(graph);
+    let n = graph.vertex_count();
+    let mut in_degrees = vec![0; n];
+    for edge in graph.edges() {
+        in_degrees[edge.to] += 1;
+    }
+
+    let mut ready = (0..n)
+        .filter(|&v| in_degrees[v] == 0)
+        .collect::<VecDeque<_>>();
+    let mut order = Vec::with_capacity(n);
+    while let Some(vertex) = ready.pop_front() {
+        order.push(vertex);
+        for (next, _) in graph.neighbours(vertex) {
+            in_degrees[next] -= 1;
+            if in_degrees[next] == 0 {
+                ready.push_back(next);
+            }
+        }
+    }
+    if order.len() == n {
+        return Ok(order);
+    }
+
+    // walk backwards through the stuck vertices (in_degree > 0) until one repeats
+    let reversed = graph.reversed();
+    let stuck = |v: usize| in_degrees[v] > 0;
+    let mut position = vec![None; n];
+    let mut walk = vec![];
+    let mut vertex = (0..n).find(|&v| stuck(v)).unwrap();
+    while position[vertex].is_none() {
+        position[vertex] = Some(walk.len());
+        walk.push(vertex);
+        vertex = reversed
+            .neighbours(vertex)
+            .map(|(previous, _)| previous)
+            .find(|&previous| stuck(previous))
+            .unwrap();
+    }
+    let mut cycle = walk.split_off(position[vertex].unwrap());
+    cycle.reverse();
+    Err(Cycle(cycle))
+}
+
+/// Vertices ordered by when their DFS finished (postorder), over the whole graph
+fn finishing_order<W>(graph: &Graph<W>) -> Vec<usize> {
+    let n = graph.vertex_count();
+    let mut visited = vec![false; n];
+    let mut order = Vec::with_capacity(n);
+    // (vertex, how many of its neighbours were already looked at)
+    let mut stack = vec![];
+    for root in 0..n {
+        if visited[root] {
+            continue;
+        }
+        visited[root] = true;
+        stack.push((root, 0));
+        while let Some((vertex, next_index)) = stack.last_mut() {
+            match graph.adjacency[*vertex].get(*next_index) {
+                Some(&(next, _)) => {
+                    *next_index += 1;
+                    if !visited[next] {
+                        visited[next] = true;
+                        stack.push((next, 0));
+                    }
+                }
+                None => {
+                    order.push(*vertex);
+                    stack.pop();
+                }
+            }
+        }
+    }
+    order
+}
+
+pub fn strongly_connected_components<W: Clone>(graph: &Graph<W>) -> Vec<Vec<usize>> {
+    assert_directed(graph);
+    let reversed = graph.reversed();
+    let mut assigned = vec![false; graph.vertex_count()];
+    let mut components = vec![];
+    // the last vertex to finish is in a source component, which in the reversed graph can only
+    // reach its own component; the next unassigned one the same once that one is removed, ...
+    for root in finishing_order(graph).into_iter().rev() {
+        if assigned[root] {
+            continue;
+        }
+        let mut component = vec![root];
+        assigned[root] = true;
+        let mut stack = vec![root];
+        while let Some(vertex) = stack.pop() {
+            for (previous, _) in reversed.neighbours(vertex) {
+                if !assigned[previous] {
+                    assigned[previous] = true;
+                    component.push(previous);
+                    stack.push(previous);
+                }
+            }
+        }
+        component.sort();
+        components.push(component);
+    }
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn has_edge(graph: &Graph, from: usize, to: usize) -> bool {
+        graph.neighbours(from).any(|(next, _)| next == to)
+    }
+
+    fn random_graph(rng: &mut StdRng) -> Graph {
+        let n = rng.gen_range(1..12);
+        let edges = (0..rng.gen_range(0..2 * n))
+            .map(|_| (rng.gen_range(0..n), rng.gen_range(0..n)))
+            .collect::<Vec<_>>();
+        Graph::from_edges(Kind::Directed, n, edges)
+    }
+
+    #[test]
+    fn sorts_or_finds_a_cycle() {
+        let mut rng = StdRng::seed_from_u64(37);
+        let (mut sorted, mut cyclic) = (0, 0);
+        for _ in 0..500 {
+            let graph = random_graph(&mut rng);
+            match topological_sort(&graph) {
+                Ok(order) => {
+                    sorted += 1;
+                    let mut position = vec![0; order.len()];
+                    order.iter().enumerate().for_each(|(i, &v)| position[v] = i);
+                    assert!(graph
+                        .edges()
+                        .iter()
+                        .all(|e| position[e.from] < position[e.to]));
+                }
+                Err(Cycle(cycle)) => {
+                    cyclic += 1;
+                    let mut closed = cycle.iter().zip(cycle.iter().cycle().skip(1));
+                    assert!(closed.all(|(&a, &b)| has_edge(&graph, a, b)));
+                }
+            }
+        }
+        assert!(
+            sorted > 50 && cyclic > 50,
+            "{sorted} sorted, {cyclic} cyclic"
+        );
+    }
+
+    #[test]
+    fn components_are_mutually_reachable() {
+        let mut rng = StdRng::seed_from_u64(38);
+        for _ in 0..300 {
+            let graph = random_graph(&mut rng);
+            let n = graph.vertex_count();
+            let reachable = (0..n)
+                .map(|v| {
+                    let mut row = vec![false; n];
+                    graph.dfs(v).for_each(|u| row[u] = true);
+                    row
+                })
+                .collect::<Vec<_>>();
+
+            let components = strongly_connected_components(&graph);
+            let mut component_of = vec![usize::MAX; n];
+            for (c, component) in components.iter().enumerate() {
+                component.iter().for_each(|&v| component_of[v] = c);
+            }
+            for u in 0..n {
+                for v in 0..n {
+                    let same = reachable[u][v] && reachable[v][u];
+                    assert_eq!(component_of[u] == component_of[v], same);
+                    // topological order of the components
+                    if reachable[u][v] {
+                        assert!(component_of[u] <= component_of[v]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn small_examples() {
+        let courses =
+            Graph::from_one_based_edges(Kind::Directed, 4, [(1, 2), (1, 3), (3, 2), (2, 4)]);
+        assert_eq!(topological_sort(&courses), Ok(vec![0, 2, 1, 3]));
+
+        let cyclic = Graph::from_edges(Kind::Directed, 4, [(0, 1), (1, 2), (2, 3), (3, 1)]);
+        assert_eq!(topological_sort(&cyclic), Err(Cycle(vec![2, 3, 1])));
+        assert_eq!(
+            strongly_connected_components(&cyclic),
+            [vec![0], vec![1, 2, 3]]
+        );
+    }
+}
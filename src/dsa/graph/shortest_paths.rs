@@ -0,0 +1,252 @@
+// == Shortest paths ==
+/* - dijkstra: single source, non-negative weights, O((V + E) log V)
+ *   uses IndexedHeap, so a vertex whose distance improves gets its priority changed instead of
+ *   being pushed again (no stale entries to skip when popping)
+ * - bellman_ford: single source, any weights, O(V * E), reports a negative cycle reachable from
+ *   the source (where "shortest" stops making sense)
+ * - floyd_warshall: all pairs, any weights, O(V^3)
+ *
+ * Unreachable vertices have a distance of None. For undirected graphs every edge goes both ways,
+ * so a single negative edge is already a negative cycle.
+ */
+
+use std::ops::Add;
+
+use super::{Cycle, Graph};
+use crate::dsa::{indexed_heap::IndexedHeap, monoid::Zero};
+
+/// What edge weights need for shortest paths (and spanning trees): adding and comparing them
+pub trait Weight: Copy + Ord + Add<Output = Self> + Zero {}
+
+impl<T: Copy + Ord + Add<Output = T> + Zero> Weight for T {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShortestPaths<W> {
+    pub source: usize,
+    pub distances: Vec<Option<W>>,
+    /// the previous vertex on a shortest path from the source, None for the source itself
+    pub parents: Vec<Option<usize>>,
+}
+
+impl<W> ShortestPaths<W> {
+    fn new(source: usize, vertex_count: usize) -> Self {
+        ShortestPaths {
+            source,
+            distances: (0..vertex_count).map(|_| None).collect(),
+            parents: vec![None; vertex_count],
+        }
+    }
+
+    /// Vertices of a shortest path from the source to `target` (both included)
+    pub fn path_to(&self, target: usize) -> Option<Vec<usize>> {
+        self.distances[target].as_ref()?;
+        let mut path = vec![target];
+        while let Some(parent) = self.parents[*path.last().unwrap()] {
+            path.push(parent);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+fn shorter<W: Weight>(candidate: W, current: Option<W>) -> bool {
+    current.is_none_or(|current| candidate < current)
+}
+
+pub fn dijkstra<W: Weight>(graph: &Graph<W>, source: usize) -> ShortestPaths<W> {
+    let mut paths = ShortestPaths::new(source, graph.vertex_count());
+    let mut done = vec![false; graph.vertex_count()];
+    let mut queue = IndexedHeap::min();
+    queue.push(source, W::zero());
+
+    while let Some((vertex, distance)) = queue.pop() {
+        paths.distances[vertex] = Some(distance);
+        done[vertex] = true;
+        for (next, &weight) in graph.neighbours(vertex) {
+            assert!(weight >= W::zero(), "dijkstra needs non-negative weights");
+            let candidate = distance + weight;
+            if !done[next] && shorter(candidate, queue.priority(&next).copied()) {
+                queue.push(next, candidate);
+                paths.parents[next] = Some(vertex);
+            }
+        }
+    }
+    paths
+}
+
+pub fn bellman_ford<W: Weight>(graph: &Graph<W>, source: usize) -> Result<ShortestPaths<W>, Cycle> {
+    let n = graph.vertex_count();
+    let mut paths = ShortestPaths::new(source, n);
+    paths.distances[source] = Some(W::zero());
+
+    // after i rounds, every shortest path with at most i edges is found. A round that still
+    // improves something after n - 1 of them can only be going around a negative cycle
+    for round in 0..n {
+        let mut improved = None;
+        for vertex in 0..n {
+            let Some(distance) = paths.distances[vertex] else {
+                continue;
+            };
+            for (next, &weight) in graph.neighbours(vertex) {
+                if shorter(distance + weight, paths.distances[next]) {
+                    paths.distances[next] = Some(distance + weight);
+                    paths.parents[next] = Some(vertex);
+                    improved = Some(next);
+                }
+            }
+        }
+        match improved {
+            None => return Ok(paths),
+            Some(vertex) if round == n - 1 => return Err(negative_cycle(&paths.parents, vertex)),
+            Some(_) => {}
+        }
+    }
+    Ok(paths)
+}
+
+// `vertex` was improved in the n-th round, so following parents from it n times ends up inside the
+// cycle (possibly not the one through vertex)
+fn negative_cycle(parents: &[Option<usize>], mut vertex: usize) -> Cycle {
+    for _ in 0..parents.len() {
+        vertex = parents[vertex].unwrap();
+    }
+    let mut cycle = vec![vertex];
+    let mut current = parents[vertex].unwrap();
+    while current != vertex {
+        cycle.push(current);
+        current = parents[current].unwrap();
+    }
+    cycle.reverse(); // parents go backwards along the edges
+    Cycle(cycle)
+}
+
+/// distances[u][v] for every pair, or a negative cycle if there's one anywhere in the graph
+pub fn floyd_warshall<W: Weight>(graph: &Graph<W>) -> Result<Vec<Vec<Option<W>>>, Cycle> {
+    let n = graph.vertex_count();
+    let mut distances = vec![vec![None; n]; n];
+    for (vertex, row) in distances.iter_mut().enumerate() {
+        row[vertex] = Some(W::zero());
+        for (next, &weight) in graph.neighbours(vertex) {
+            if shorter(weight, row[next]) {
+                row[next] = Some(weight);
+            }
+        }
+    }
+
+    // after step k, distances only go through intermediate vertices < k
+    for k in 0..n {
+        // (row k itself can only change through a negative cycle, which is reported anyway)
+        let through_k = distances[k].clone();
+        for row in distances.iter_mut() {
+            let Some(to_k) = row[k] else {
+                continue;
+            };
+            for (distance, from_k) in row.iter_mut().zip(&through_k) {
+                if let Some(from_k) = *from_k {
+                    if shorter(to_k + from_k, *distance) {
+                        *distance = Some(to_k + from_k);
+                    }
+                }
+            }
+        }
+    }
+
+    // i is on a negative cycle, and bellman_ford from i knows how to find one
+    match (0..n).find(|&i| distances[i][i] < Some(W::zero())) {
+        Some(i) => Err(bellman_ford(graph, i)
+            .err()
+            .expect("i is on a negative cycle")),
+        None => Ok(distances),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsa::graph::Kind;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn random_graph(rng: &mut StdRng, kind: Kind, weights: std::ops::Range<i64>) -> Graph<i64> {
+        let n = rng.gen_range(1..12);
+        let edges = (0..rng.gen_range(0..3 * n))
+            .map(|_| {
+                let (from, to) = (rng.gen_range(0..n), rng.gen_range(0..n));
+                (from, to, rng.gen_range(weights.clone()))
+            })
+            .collect::<Vec<_>>();
+        Graph::from_weighted_edges(kind, n, edges)
+    }
+
+    fn path_length(graph: &Graph<i64>, path: &[usize]) -> i64 {
+        path.windows(2)
+            .map(|pair| {
+                graph
+                    .neighbours(pair[0])
+                    .filter(|(next, _)| *next == pair[1])
+                    .map(|(_, &weight)| weight)
+                    .min()
+                    .expect("consecutive vertices of a path are joined by an edge")
+            })
+            .sum()
+    }
+
+    #[test]
+    fn all_three_agree() {
+        let mut rng = StdRng::seed_from_u64(37);
+        for case in 0..200 {
+            let kind = [Kind::Directed, Kind::Undirected][case % 2];
+            let graph = random_graph(&mut rng, kind, 0..20);
+            let all_pairs = floyd_warshall(&graph).unwrap();
+            for (source, expected) in all_pairs.iter().enumerate() {
+                let fast = dijkstra(&graph, source);
+                let slow = bellman_ford(&graph, source).unwrap();
+                assert_eq!(&fast.distances, expected);
+                assert_eq!(&slow.distances, expected);
+                for target in 0..graph.vertex_count() {
+                    let path = fast.path_to(target);
+                    assert_eq!(path.is_some(), fast.distances[target].is_some());
+                    if let Some(path) = path {
+                        assert_eq!((path[0], *path.last().unwrap()), (source, target));
+                        assert_eq!(Some(path_length(&graph, &path)), fast.distances[target]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn negative_weights() {
+        let graph = Graph::from_weighted_edges(
+            Kind::Directed,
+            4,
+            [(0, 1, 4), (0, 2, 1), (2, 1, -2), (1, 3, 1)],
+        );
+        let paths = bellman_ford(&graph, 0).unwrap();
+        assert_eq!(paths.distances, [Some(0), Some(-1), Some(1), Some(0)]);
+        assert_eq!(paths.path_to(3), Some(vec![0, 2, 1, 3]));
+
+        // 1 -> 2 -> 3 -> 1 costs -1
+        let mut rng = StdRng::seed_from_u64(38);
+        let cyclic = Graph::from_weighted_edges(
+            Kind::Directed,
+            5,
+            [(0, 1, 1), (1, 2, 2), (2, 3, -4), (3, 1, 1), (3, 4, 0)],
+        );
+        assert_eq!(
+            bellman_ford(&cyclic, 4).map(|p| p.distances[4]),
+            Ok(Some(0))
+        );
+        for result in [
+            bellman_ford(&cyclic, rng.gen_range(0..4)).map(|_| ()),
+            floyd_warshall(&cyclic).map(|_| ()),
+        ] {
+            let Err(Cycle(cycle)) = result else {
+                panic!("the negative cycle wasn't found");
+            };
+            let mut rotated = cycle.clone();
+            let start = rotated.iter().position(|&v| v == 1).unwrap();
+            rotated.rotate_left(start);
+            assert_eq!(rotated, [1, 2, 3]);
+        }
+    }
+}
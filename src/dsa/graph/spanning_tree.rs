@@ -0,0 +1,160 @@
+// == Minimum spanning trees ==
+/* For undirected graphs. A disconnected graph has no spanning tree, so both return a minimum
+ * spanning forest (one tree per connected component, V - components edges).
+ *
+ * - kruskal: edges from lightest to heaviest, keeping those that join two different trees,
+ *   O(E log E). "Different trees" is a DisjointSet (union-find) query.
+ * - prim: grows each tree from a vertex, always adding the lightest edge leaving it,
+ *   O(E log V) with IndexedHeap holding the lightest known edge into every vertex.
+ *
+ * With equal weights there can be several minimum trees, both give the same total but maybe not
+ * the same edges.
+ */
+
+use super::{shortest_paths::Weight, Edge, Graph, Kind};
+use crate::dsa::{disjoint_set::DisjointSet, indexed_heap::IndexedHeap};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanningForest<W> {
+    pub edges: Vec<Edge<W>>,
+    pub total: W,
+}
+
+fn assert_undirected<W>(graph: &Graph<W>) {
+    assert_eq!(
+        graph.kind(),
+        Kind::Undirected,
+        "spanning trees are for undirected graphs"
+    );
+}
+
+pub fn kruskal<W: Weight>(graph: &Graph<W>) -> SpanningForest<W> {
+    assert_undirected(graph);
+    let mut edges = graph.edges().to_vec();
+    edges.sort_by_key(|edge| edge.weight);
+
+    let mut trees = DisjointSet::new(graph.vertex_count());
+    let edges = edges
+        .into_iter()
+        .filter(|edge| trees.union(edge.from, edge.to))
+        .collect::<Vec<_>>();
+    SpanningForest {
+        total: edges
+            .iter()
+            .fold(W::zero(), |total, edge| total + edge.weight),
+        edges,
+    }
+}
+
+pub fn prim<W: Weight>(graph: &Graph<W>) -> SpanningForest<W> {
+    assert_undirected(graph);
+    let n = graph.vertex_count();
+    let mut in_tree = vec![false; n];
+    // lightest known edge into each vertex not in a tree yet
+    let mut lightest: Vec<Option<Edge<W>>> = vec![None; n];
+    let mut forest = SpanningForest {
+        edges: vec![],
+        total: W::zero(),
+    };
+
+    for root in 0..n {
+        if in_tree[root] {
+            continue;
+        }
+        let mut queue = IndexedHeap::min();
+        queue.push(root, W::zero());
+        while let Some((vertex, _)) = queue.pop() {
+            in_tree[vertex] = true;
+            if let Some(edge) = lightest[vertex].take() {
+                forest.total = forest.total + edge.weight;
+                forest.edges.push(edge);
+            }
+            for (next, &weight) in graph.neighbours(vertex) {
+                if in_tree[next] {
+                    continue;
+                }
+                if queue.priority(&next).is_none_or(|&known| weight < known) {
+                    queue.push(next, weight);
+                    lightest[next] = Some(Edge {
+                        from: vertex,
+                        to: next,
+                        weight,
+                    });
+                }
+            }
+        }
+    }
+    forest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    // number of connected components of the graph made of these edges
+    fn components(n: usize, edges: &[Edge<i64>]) -> usize {
+        let forest = Graph::from_edges(
+            Kind::Undirected,
+            n,
+            edges.iter().map(|edge| (edge.from, edge.to)),
+        );
+        let mut seen = vec![false; n];
+        (0..n)
+            .filter(|&v| {
+                let new = !seen[v];
+                forest.dfs(v).for_each(|u| seen[u] = true);
+                new
+            })
+            .count()
+    }
+
+    #[test]
+    fn kruskal_and_prim_agree() {
+        let mut rng = StdRng::seed_from_u64(37);
+        for _ in 0..300 {
+            let n = rng.gen_range(1..15);
+            let edges = (0..rng.gen_range(0..3 * n))
+                .map(|_| {
+                    (
+                        rng.gen_range(0..n),
+                        rng.gen_range(0..n),
+                        rng.gen_range(-5..10),
+                    )
+                })
+                .collect::<Vec<(usize, usize, i64)>>();
+            let graph = Graph::from_weighted_edges(Kind::Undirected, n, edges);
+
+            let (k, p) = (kruskal(&graph), prim(&graph));
+            assert_eq!(k.total, p.total);
+            assert_eq!(k.edges.len(), p.edges.len());
+            // a forest spanning every component: V - components edges, and no component merged
+            let expected_components = components(n, graph.edges());
+            assert_eq!(k.edges.len(), n - expected_components);
+            assert_eq!(components(n, &p.edges), expected_components);
+        }
+    }
+
+    #[test]
+    fn classic_example() {
+        let graph = Graph::from_weighted_edges(
+            Kind::Undirected,
+            5,
+            [
+                (0, 1, 2),
+                (0, 3, 6),
+                (1, 2, 3),
+                (1, 3, 8),
+                (1, 4, 5),
+                (2, 4, 7),
+                (3, 4, 9),
+            ],
+        );
+        let tree = kruskal(&graph);
+        assert_eq!(tree.total, 16);
+        let mut weights = tree.edges.iter().map(|e| e.weight).collect::<Vec<_>>();
+        weights.sort();
+        assert_eq!(weights, [2, 3, 5, 6]);
+        assert_eq!(prim(&graph).total, 16);
+    }
+}
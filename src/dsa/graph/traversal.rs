@@ -0,0 +1,115 @@
+// == Breadth and depth first traversals ==
+/* Both are iterators over the vertices reachable from `start`, each visited once, so they can be
+ * stopped early (`.take_while(..)`, `.find(..)`) without exploring the rest of the graph.
+ *
+ * Dfs is iterative (an explicit stack instead of recursion, which would overflow the call stack
+ * on long paths) and yields vertices in preorder, visiting neighbours in insertion order like the
+ * recursive version would.
+ */
+
+use std::collections::VecDeque;
+
+use super::Graph;
+
+pub struct Bfs<'a, W> {
+    graph: &'a Graph<W>,
+    queue: VecDeque<(usize, usize)>,
+    /// marked when queued, so nothing is queued twice
+    seen: Vec<bool>,
+}
+
+impl<W> Iterator for Bfs<'_, W> {
+    /// (vertex, number of edges from start)
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        let (vertex, depth) = self.queue.pop_front()?;
+        for (next, _) in &self.graph.adjacency[vertex] {
+            if !self.seen[*next] {
+                self.seen[*next] = true;
+                self.queue.push_back((*next, depth + 1));
+            }
+        }
+        Some((vertex, depth))
+    }
+}
+
+pub struct Dfs<'a, W> {
+    graph: &'a Graph<W>,
+    stack: Vec<usize>,
+    /// marked when yielded, a vertex can be on the stack several times
+    visited: Vec<bool>,
+}
+
+impl<W> Iterator for Dfs<'_, W> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while let Some(vertex) = self.stack.pop() {
+            if self.visited[vertex] {
+                continue;
+            }
+            self.visited[vertex] = true;
+            // reversed, so that the first neighbour is on top of the stack
+            let unvisited = self.graph.adjacency[vertex]
+                .iter()
+                .rev()
+                .map(|(next, _)| *next)
+                .filter(|next| !self.visited[*next]);
+            self.stack.extend(unvisited);
+            return Some(vertex);
+        }
+        None
+    }
+}
+
+impl<W> Graph<W> {
+    pub fn bfs(&self, start: usize) -> Bfs<'_, W> {
+        let mut seen = vec![false; self.adjacency.len()];
+        seen[start] = true;
+        Bfs {
+            graph: self,
+            queue: VecDeque::from([(start, 0)]),
+            seen,
+        }
+    }
+
+    pub fn dfs(&self, start: usize) -> Dfs<'_, W> {
+        assert!(start < self.adjacency.len(), "vertex {start} out of bounds");
+        Dfs {
+            graph: self,
+            stack: vec![start],
+            visited: vec![false; self.adjacency.len()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dsa::graph::{Graph, Kind};
+
+    #[test]
+    fn visit_orders() {
+        //     0
+        //    / \
+        //   1   4
+        //  / \   \
+        // 2   3   5     and 6 on its own
+        let graph = Graph::from_edges(
+            Kind::Undirected,
+            7,
+            [(0, 1), (0, 4), (1, 2), (1, 3), (4, 5)],
+        );
+        assert_eq!(
+            graph.bfs(0).collect::<Vec<_>>(),
+            [(0, 0), (1, 1), (4, 1), (2, 2), (3, 2), (5, 2)]
+        );
+        assert_eq!(graph.dfs(0).collect::<Vec<_>>(), [0, 1, 2, 3, 4, 5]);
+        assert_eq!(graph.dfs(6).collect::<Vec<_>>(), [6]);
+
+        // stops early, and follows directions
+        let path = Graph::from_edges(Kind::Directed, 4, [(3, 2), (2, 1), (1, 0)]);
+        assert_eq!(path.bfs(2).map(|(v, _)| v).collect::<Vec<_>>(), [2, 1, 0]);
+        assert_eq!(path.dfs(3).take_while(|&v| v != 1).count(), 2);
+    }
+}
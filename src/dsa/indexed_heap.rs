@@ -0,0 +1,227 @@
+// == Indexed priority queue ==
+/* A binary heap of (key, priority) that also knows where every key sits in it, so a key's
+ * priority can be changed (or the key removed) in O(log n) instead of pushing a duplicate and
+ * skipping stale entries when they're popped (the usual BinaryHeap workaround for decrease-key).
+ *
+ * `positions[key]` is the index of the key in `heap`, and every swap while sifting keeps it in
+ * sync. Each key is in the heap at most once.
+ *
+ * Min or max is picked at construction:
+ *
+ *     let mut distances = IndexedHeap::min();  // pop gives the smallest priority
+ *     let mut scores = IndexedHeap::max();     // pop gives the largest priority
+ */
+
+use std::{collections::HashMap, hash::Hash};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Min,
+    Max,
+}
+
+#[derive(Debug, Clone)]
+pub struct IndexedHeap<K, P> {
+    kind: Kind,
+    heap: Vec<(K, P)>,
+    positions: HashMap<K, usize>,
+}
+
+impl<K: Hash + Eq + Clone, P: Ord> IndexedHeap<K, P> {
+    /// pop/peek return the smallest priority
+    pub fn min() -> Self {
+        Self::with_kind(Kind::Min)
+    }
+
+    /// pop/peek return the largest priority
+    pub fn max() -> Self {
+        Self::with_kind(Kind::Max)
+    }
+
+    fn with_kind(kind: Kind) -> Self {
+        IndexedHeap {
+            kind,
+            heap: vec![],
+            positions: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.positions.contains_key(key)
+    }
+
+    pub fn priority(&self, key: &K) -> Option<&P> {
+        self.positions.get(key).map(|&i| &self.heap[i].1)
+    }
+
+    pub fn peek(&self) -> Option<(&K, &P)> {
+        self.heap.first().map(|(key, priority)| (key, priority))
+    }
+
+    /// Inserts `key`, or changes its priority if it's already there (returning the old one)
+    pub fn push(&mut self, key: K, priority: P) -> Option<P> {
+        if self.contains(&key) {
+            return self.change_priority(&key, priority);
+        }
+        self.positions.insert(key.clone(), self.heap.len());
+        self.heap.push((key, priority));
+        self.sift_up(self.heap.len() - 1);
+        None
+    }
+
+    pub fn pop(&mut self) -> Option<(K, P)> {
+        if self.heap.is_empty() {
+            return None;
+        }
+        self.remove_at(0)
+    }
+
+    /// Returns the old priority, None (and nothing changes) if `key` isn't in the heap
+    pub fn change_priority(&mut self, key: &K, priority: P) -> Option<P> {
+        let i = *self.positions.get(key)?;
+        let old = std::mem::replace(&mut self.heap[i].1, priority);
+        // only one of them moves anything
+        self.sift_up(i);
+        self.sift_down(i);
+        Some(old)
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<P> {
+        let i = *self.positions.get(key)?;
+        self.remove_at(i).map(|(_, priority)| priority)
+    }
+
+    // swaps the last entry into i, then fixes it up in whichever direction it has to go
+    fn remove_at(&mut self, i: usize) -> Option<(K, P)> {
+        let last = self.heap.len() - 1;
+        self.swap(i, last);
+        let (key, priority) = self.heap.pop()?;
+        self.positions.remove(&key);
+        if i < self.heap.len() {
+            self.sift_up(i);
+            self.sift_down(i);
+        }
+        Some((key, priority))
+    }
+
+    /// whether the entry at i should be closer to the top than the one at j
+    fn before(&self, i: usize, j: usize) -> bool {
+        let (a, b) = (&self.heap[i].1, &self.heap[j].1);
+        match self.kind {
+            Kind::Min => a < b,
+            Kind::Max => a > b,
+        }
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        self.heap.swap(i, j);
+        *self.positions.get_mut(&self.heap[i].0).unwrap() = i;
+        *self.positions.get_mut(&self.heap[j].0).unwrap() = j;
+    }
+
+    fn sift_up(&mut self, mut i: usize) {
+        while i > 0 {
+            let parent = (i - 1) / 2;
+            if !self.before(i, parent) {
+                break;
+            }
+            self.swap(i, parent);
+            i = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut i: usize) {
+        loop {
+            let (left, right) = (2 * i + 1, 2 * i + 2);
+            let mut top = i;
+            if left < self.heap.len() && self.before(left, top) {
+                top = left;
+            }
+            if right < self.heap.len() && self.before(right, top) {
+                top = right;
+            }
+            if top == i {
+                break;
+            }
+            self.swap(i, top);
+            i = top;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    // random operations against a HashMap of key -> priority, where the top is found by scanning
+    fn check_against_model(max: bool, seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut heap = if max {
+            IndexedHeap::max()
+        } else {
+            IndexedHeap::min()
+        };
+        let mut model = HashMap::<u8, i32>::new();
+        let top = |model: &HashMap<u8, i32>| {
+            let priorities = model.values().copied();
+            if max {
+                priorities.max()
+            } else {
+                priorities.min()
+            }
+        };
+
+        for _ in 0..5000 {
+            let (key, priority) = (rng.gen_range(0..30), rng.gen_range(-50..50));
+            match rng.gen_range(0..6) {
+                0 | 1 => assert_eq!(heap.push(key, priority), model.insert(key, priority)),
+                2 => {
+                    let expected = model.get_mut(&key).map(|p| std::mem::replace(p, priority));
+                    assert_eq!(heap.change_priority(&key, priority), expected);
+                }
+                3 => assert_eq!(heap.remove(&key), model.remove(&key)),
+                4 => {
+                    // equal priorities can come out in any order, only the priority is fixed
+                    let popped = heap.pop();
+                    assert_eq!(popped.map(|(_, p)| p), top(&model));
+                    if let Some((key, priority)) = popped {
+                        assert_eq!(model.remove(&key), Some(priority));
+                    }
+                }
+                _ => {
+                    assert_eq!(heap.peek().map(|(_, &p)| p), top(&model));
+                    assert_eq!(heap.contains(&key), model.contains_key(&key));
+                    assert_eq!(heap.priority(&key), model.get(&key));
+                }
+            }
+            assert_eq!(heap.len(), model.len());
+        }
+    }
+
+    #[test]
+    fn matches_model() {
+        check_against_model(false, 1);
+        check_against_model(true, 2);
+    }
+
+    #[test]
+    fn decrease_key() {
+        let mut heap = IndexedHeap::min();
+        for (key, priority) in [("a", 5), ("b", 3), ("c", 8)] {
+            heap.push(key, priority);
+        }
+        assert_eq!(heap.change_priority(&"c", 1), Some(8));
+        assert_eq!(heap.change_priority(&"d", 0), None);
+        let order = std::iter::from_fn(|| heap.pop()).collect::<Vec<_>>();
+        assert_eq!(order, [("c", 1), ("b", 3), ("a", 5)]);
+    }
+}
@@ -0,0 +1,307 @@
+// == Interval tree ==
+/* Half-open intervals [start, end) with a value each, answering "which intervals overlap
+ * [a, b)?" and "which intervals contain x?" in O(log n + answers).
+ *
+ * It's a treap (a binary search tree on (start, end) that is also a heap on random priorities,
+ * which keeps it balanced in expectation) where every node also knows the largest end in its
+ * subtree. A search skips:
+ * - a whole subtree when its max_end <= a (everything in it ends before the query starts)
+ * - the right subtree when the node starts at or after b (everything right of it starts later)
+ *
+ * Everything is done with split (by key) and merge (of two treaps where all the keys on the left
+ * are smaller), recursively, which is fine since the depth is O(log n) in expectation.
+ *
+ * The same interval can be inserted several times (with different values), `remove` takes one.
+ */
+
+use std::{cmp::Ordering, ops::Range};
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+#[derive(Debug, Clone)]
+struct Node<K, V> {
+    interval: Range<K>,
+    value: V,
+    priority: u64,
+    /// largest end in this subtree
+    max_end: K,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+#[derive(Debug, Clone)]
+pub struct IntervalTree<K, V> {
+    root: Link<K, V>,
+    len: usize,
+    /// xorshift state for the priorities, deterministic so runs are reproducible
+    seed: u64,
+}
+
+fn key_order<K: Ord>(a: &Range<K>, b: &Range<K>) -> Ordering {
+    (&a.start, &a.end).cmp(&(&b.start, &b.end))
+}
+
+impl<K: Ord + Clone, V> Node<K, V> {
+    fn update(&mut self) {
+        let children = [&self.left, &self.right];
+        self.max_end = children
+            .into_iter()
+            .flatten()
+            .map(|child| &child.max_end)
+            .fold(&self.interval.end, |max, end| max.max(end))
+            .clone();
+    }
+}
+
+/// (nodes going left, the others), `goes_left` must be true for a prefix of the in-order keys
+fn split<K: Ord + Clone, V>(
+    link: Link<K, V>,
+    goes_left: &impl Fn(&Range<K>) -> bool,
+) -> (Link<K, V>, Link<K, V>) {
+    let Some(mut node) = link else {
+        return (None, None);
+    };
+    if goes_left(&node.interval) {
+        let (middle, right) = split(node.right.take(), goes_left);
+        node.right = middle;
+        node.update();
+        (Some(node), right)
+    } else {
+        let (left, middle) = split(node.left.take(), goes_left);
+        node.left = middle;
+        node.update();
+        (left, Some(node))
+    }
+}
+
+/// Every key of `left` must be <= every key of `right`
+fn merge<K: Ord + Clone, V>(left: Link<K, V>, right: Link<K, V>) -> Link<K, V> {
+    match (left, right) {
+        (None, link) | (link, None) => link,
+        (Some(mut l), Some(mut r)) => {
+            if l.priority > r.priority {
+                l.right = merge(l.right.take(), Some(r));
+                l.update();
+                Some(l)
+            } else {
+                r.left = merge(Some(l), r.left.take());
+                r.update();
+                Some(r)
+            }
+        }
+    }
+}
+
+impl<K: Ord + Clone, V> IntervalTree<K, V> {
+    pub fn new() -> Self {
+        IntervalTree {
+            root: None,
+            len: 0,
+            seed: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn next_priority(&mut self) -> u64 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        self.seed
+    }
+
+    /// Panics on an empty interval (start >= end), it couldn't overlap anything
+    pub fn insert(&mut self, interval: Range<K>, value: V) {
+        assert!(interval.start < interval.end, "empty interval");
+        let node = Box::new(Node {
+            max_end: interval.end.clone(),
+            priority: self.next_priority(),
+            interval,
+            value,
+            left: None,
+            right: None,
+        });
+        let (left, right) = split(self.root.take(), &|key| {
+            key_order(key, &node.interval) != Ordering::Greater
+        });
+        self.root = merge(merge(left, Some(node)), right);
+        self.len += 1;
+    }
+
+    /// Removes one copy of `interval`, returning its value
+    pub fn remove(&mut self, interval: &Range<K>) -> Option<V> {
+        let (left, rest) = split(self.root.take(), &|key| {
+            key_order(key, interval) == Ordering::Less
+        });
+        let (equal, right) = split(rest, &|key| key_order(key, interval) == Ordering::Equal);
+        // the root of `equal` is one of the copies, its children are the others
+        let (equal, removed) = match equal {
+            Some(node) => {
+                let Node {
+                    left: l,
+                    right: r,
+                    value,
+                    ..
+                } = *node;
+                (merge(l, r), Some(value))
+            }
+            None => (None, None),
+        };
+        self.root = merge(merge(left, equal), right);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    // visits, in order, the intervals with end > after and starts_before(start)
+    fn search<'a>(
+        link: &'a Link<K, V>,
+        after: &K,
+        starts_before: &impl Fn(&K) -> bool,
+        found: &mut Vec<(&'a Range<K>, &'a V)>,
+    ) {
+        let Some(node) = link else {
+            return;
+        };
+        if node.max_end <= *after {
+            return;
+        }
+        Self::search(&node.left, after, starts_before, found);
+        if starts_before(&node.interval.start) {
+            if node.interval.end > *after {
+                found.push((&node.interval, &node.value));
+            }
+            Self::search(&node.right, after, starts_before, found);
+        }
+    }
+
+    /// Every interval sharing at least a point with `query`, ordered by (start, end)
+    pub fn overlapping(&self, query: Range<K>) -> Vec<(&Range<K>, &V)> {
+        let mut found = vec![];
+        Self::search(
+            &self.root,
+            &query.start,
+            &|start| *start < query.end,
+            &mut found,
+        );
+        found
+    }
+
+    /// Every interval containing `point`, ordered by (start, end)
+    pub fn stabbing(&self, point: &K) -> Vec<(&Range<K>, &V)> {
+        let mut found = vec![];
+        Self::search(&self.root, point, &|start| start <= point, &mut found);
+        found
+    }
+
+    /// Every interval, ordered by (start, end)
+    pub fn iter(&self) -> impl Iterator<Item = (&Range<K>, &V)> {
+        let mut stack = vec![];
+        let mut current = self.root.as_deref();
+        std::iter::from_fn(move || {
+            while let Some(node) = current {
+                stack.push(node);
+                current = node.left.as_deref();
+            }
+            let node = stack.pop()?;
+            current = node.right.as_deref();
+            Some((&node.interval, &node.value))
+        })
+    }
+}
+
+impl<K: Ord + Clone, V> Default for IntervalTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    // model: a plain Vec scanned for every query, sorted the same way as the tree's answers
+    #[test]
+    fn matches_vec_model() {
+        let mut rng = StdRng::seed_from_u64(38);
+        let mut tree = IntervalTree::new();
+        let mut model: Vec<(Range<i32>, u32)> = vec![];
+        let sorted = |mut found: Vec<(Range<i32>, u32)>| {
+            found.sort_by(|a, b| key_order(&a.0, &b.0).then(a.1.cmp(&b.1)));
+            found
+        };
+
+        for id in 0..3000 {
+            let start = rng.gen_range(0..100);
+            let interval = start..start + rng.gen_range(1..20);
+            match rng.gen_range(0..4) {
+                0 | 1 => {
+                    tree.insert(interval.clone(), id);
+                    model.push((interval, id));
+                }
+                2 => {
+                    // with copies, any of them may go: find the one the tree removed by its value
+                    let removed = tree.remove(&interval);
+                    let position = model
+                        .iter()
+                        .position(|(i, v)| *i == interval && Some(*v) == removed);
+                    assert_eq!(removed.is_some(), model.iter().any(|(i, _)| *i == interval));
+                    if let Some(position) = position {
+                        model.swap_remove(position);
+                    }
+                }
+                _ => {
+                    let point = rng.gen_range(0..120);
+                    let stabbed = model
+                        .iter()
+                        .filter(|(i, _)| i.contains(&point))
+                        .cloned()
+                        .collect();
+                    let found = tree.stabbing(&point);
+                    let found = found.into_iter().map(|(i, v)| (i.clone(), *v)).collect();
+                    assert_eq!(sorted(found), sorted(stabbed));
+
+                    let overlapping = model
+                        .iter()
+                        .filter(|(i, _)| i.start < interval.end && interval.start < i.end)
+                        .cloned()
+                        .collect();
+                    let found = tree.overlapping(interval.clone());
+                    let found = found.into_iter().map(|(i, v)| (i.clone(), *v)).collect();
+                    assert_eq!(sorted(found), sorted(overlapping));
+                }
+            }
+            assert_eq!(tree.len(), model.len());
+        }
+        let all = tree
+            .iter()
+            .map(|(i, v)| (i.clone(), *v))
+            .collect::<Vec<_>>();
+        assert!(all.windows(2).all(|w| key_order(&w[0].0, &w[1].0).is_le()));
+        assert_eq!(sorted(all), sorted(model));
+    }
+
+    #[test]
+    fn bookings() {
+        let mut rooms = IntervalTree::new();
+        rooms.insert(9..12, "standup");
+        rooms.insert(11..14, "lunch");
+        rooms.insert(14..15, "review");
+        let at_eleven = rooms.stabbing(&11).into_iter().map(|(_, v)| *v);
+        assert_eq!(at_eleven.collect::<Vec<_>>(), ["standup", "lunch"]);
+        // half-open: 14..15 doesn't overlap 12..14
+        let afternoon = rooms.overlapping(12..14).into_iter().map(|(_, v)| *v);
+        assert_eq!(afternoon.collect::<Vec<_>>(), ["lunch"]);
+        assert_eq!(rooms.remove(&(11..14)), Some("lunch"));
+        assert_eq!(rooms.remove(&(11..14)), None);
+        assert!(rooms.overlapping(12..14).is_empty());
+    }
+}
@@ -0,0 +1,417 @@
+// == Lazy propagation segment tree ==
+/* SegmentTree handles point updates, this one also updates a whole range in O(log n):
+ * an update covering a node's whole segment is applied to the node's aggregate right away and
+ * parked in `lazy[node]`, and only pushed down to the children when a later operation needs to
+ * look inside that segment.
+ *
+ * What an update does to an aggregate depends on the monoid (adding 5 to every element adds 5 to
+ * their min, but 5 * len to their sum), so updates are described per monoid with LazyAction:
+ *
+ *     LazySegmentTree::<i64, Max, RangeAdd>::from_vec(values)   // range add, range max
+ *     LazySegmentTree::<i64, Sum, RangeAssign>::new(n)          // range assign, range sum
+ *
+ * Storage is the classic power-of-two heap layout (node k has children 2k and 2k + 1), since
+ * pushing down needs a fixed parent/child structure. Padding leaves hold the identity and have
+ * length 0, so they never pick up an update.
+ */
+
+use std::{marker::PhantomData, ops::Add, ops::RangeBounds};
+
+use super::{
+    monoid::{Bounded, Max, Min, Monoid, Sum, Zero},
+    segment_tree::to_half_open,
+};
+
+pub trait LazyAction<T, Op: Monoid<T>> {
+    type Update: Clone;
+
+    /// The update that changes nothing
+    fn identity() -> Self::Update;
+    /// A single update doing `older` first and then `newer`
+    fn compose(newer: &Self::Update, older: &Self::Update) -> Self::Update;
+    /// Aggregate of a segment of `len` elements (`value`) after `update` hits all of them
+    fn apply(update: &Self::Update, value: &T, len: usize) -> T;
+}
+
+/// Add the same value to every element of the range
+pub struct RangeAdd;
+/// Overwrite every element of the range with the same value
+pub struct RangeAssign;
+
+// value + value + ... (n times) with only Add, by doubling
+fn times<T: Zero + Add<Output = T> + Clone>(value: &T, mut n: usize) -> T {
+    let (mut result, mut power) = (T::zero(), value.clone());
+    while n > 0 {
+        if n % 2 == 1 {
+            result = result + power.clone();
+        }
+        n /= 2;
+        // only double while bits remain, power would otherwise overflow before result does
+        if n > 0 {
+            power = power.clone() + power;
+        }
+    }
+    result
+}
+
+macro_rules! impl_extremum_actions {
+    ($($op:ty),*) => {
+        $(
+            impl<T: Zero + Add<Output = T> + Bounded + PartialOrd + Clone> LazyAction<T, $op>
+                for RangeAdd
+            {
+                type Update = T;
+
+                fn identity() -> T {
+                    T::zero()
+                }
+
+                fn compose(newer: &T, older: &T) -> T {
+                    older.clone() + newer.clone()
+                }
+
+                fn apply(update: &T, value: &T, len: usize) -> T {
+                    // an empty segment holds the identity (MAX/MIN), adding to it would overflow
+                    if len == 0 {
+                        return value.clone();
+                    }
+                    value.clone() + update.clone()
+                }
+            }
+
+            impl<T: Bounded + PartialOrd + Clone> LazyAction<T, $op> for RangeAssign {
+                type Update = Option<T>;
+
+                fn identity() -> Option<T> {
+                    None
+                }
+
+                fn compose(newer: &Option<T>, older: &Option<T>) -> Option<T> {
+                    newer.clone().or_else(|| older.clone())
+                }
+
+                fn apply(update: &Option<T>, value: &T, len: usize) -> T {
+                    match update {
+                        Some(assigned) if len > 0 => assigned.clone(),
+                        _ => value.clone(),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_extremum_actions!(Min, Max);
+
+impl<T: Zero + Add<Output = T> + Clone> LazyAction<T, Sum> for RangeAdd {
+    type Update = T;
+
+    fn identity() -> T {
+        T::zero()
+    }
+
+    fn compose(newer: &T, older: &T) -> T {
+        older.clone() + newer.clone()
+    }
+
+    fn apply(update: &T, value: &T, len: usize) -> T {
+        value.clone() + times(update, len)
+    }
+}
+
+impl<T: Zero + Add<Output = T> + Clone> LazyAction<T, Sum> for RangeAssign {
+    type Update = Option<T>;
+
+    fn identity() -> Option<T> {
+        None
+    }
+
+    fn compose(newer: &Option<T>, older: &Option<T>) -> Option<T> {
+        newer.clone().or_else(|| older.clone())
+    }
+
+    fn apply(update: &Option<T>, value: &T, len: usize) -> T {
+        match update {
+            Some(assigned) => times(assigned, len),
+            None => value.clone(),
+        }
+    }
+}
+
+pub struct LazySegmentTree<T, Op: Monoid<T>, Act: LazyAction<T, Op>> {
+    len: usize,
+    size: usize,
+    log: u32,
+    tree: Vec<T>,
+    lazy: Vec<Act::Update>,
+    /// number of real (non padding) leaves under each node
+    lens: Vec<usize>,
+    op: PhantomData<Op>,
+}
+
+impl<T, Op, Act> LazySegmentTree<T, Op, Act>
+where
+    T: Clone,
+    Op: Monoid<T>,
+    Act: LazyAction<T, Op>,
+{
+    pub fn new(len: usize) -> Self {
+        Self::from_vec(vec![Op::identity(); len])
+    }
+
+    pub fn from_vec(values: Vec<T>) -> Self {
+        let len = values.len();
+        let size = len.next_power_of_two();
+        let log = size.trailing_zeros();
+
+        let mut tree = vec![Op::identity(); 2 * size];
+        let mut lens = vec![0; 2 * size];
+        for (i, value) in values.into_iter().enumerate() {
+            tree[size + i] = value;
+            lens[size + i] = 1;
+        }
+        for k in (1..size).rev() {
+            tree[k] = Op::combine(&tree[2 * k], &tree[2 * k + 1]);
+            lens[k] = lens[2 * k] + lens[2 * k + 1];
+        }
+        LazySegmentTree {
+            len,
+            size,
+            log,
+            tree,
+            lazy: vec![Act::identity(); size],
+            lens,
+            op: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn pull(&mut self, k: usize) {
+        self.tree[k] = Op::combine(&self.tree[2 * k], &self.tree[2 * k + 1]);
+    }
+
+    fn apply_to_node(&mut self, k: usize, update: &Act::Update) {
+        self.tree[k] = Act::apply(update, &self.tree[k], self.lens[k]);
+        if k < self.size {
+            self.lazy[k] = Act::compose(update, &self.lazy[k]);
+        }
+    }
+
+    fn push(&mut self, k: usize) {
+        let update = std::mem::replace(&mut self.lazy[k], Act::identity());
+        self.apply_to_node(2 * k, &update);
+        self.apply_to_node(2 * k + 1, &update);
+    }
+
+    // pushes the pending updates on the path from the root to leaf p
+    fn push_path(&mut self, p: usize) {
+        for i in (1..=self.log).rev() {
+            self.push(p >> i);
+        }
+    }
+
+    pub fn get(&mut self, index: usize) -> T {
+        assert!(
+            index < self.len,
+            "index {index} out of bounds for length {}",
+            self.len
+        );
+        let p = index + self.size;
+        self.push_path(p);
+        self.tree[p].clone()
+    }
+
+    pub fn set(&mut self, index: usize, value: T) {
+        assert!(
+            index < self.len,
+            "index {index} out of bounds for length {}",
+            self.len
+        );
+        let p = index + self.size;
+        self.push_path(p);
+        self.tree[p] = value;
+        for i in 1..=self.log {
+            self.pull(p >> i);
+        }
+    }
+
+    /// Combination of all the values in `range`, identity for an empty range
+    ///
+    /// NOTE: takes &mut self, pending updates on the boundaries of the range are pushed down
+    pub fn query(&mut self, range: impl RangeBounds<usize>) -> T {
+        let (start, end) = to_half_open(range, self.len);
+        if start == end {
+            return Op::identity();
+        }
+        let (mut l, mut r) = (start + self.size, end + self.size);
+        for i in (1..=self.log).rev() {
+            if (l >> i) << i != l {
+                self.push(l >> i);
+            }
+            if (r >> i) << i != r {
+                self.push((r - 1) >> i);
+            }
+        }
+
+        let (mut left, mut right) = (Op::identity(), Op::identity());
+        while l < r {
+            if l & 1 == 1 {
+                left = Op::combine(&left, &self.tree[l]);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                right = Op::combine(&self.tree[r], &right);
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+        Op::combine(&left, &right)
+    }
+
+    /// Applies `update` to every element in `range`
+    pub fn update(&mut self, range: impl RangeBounds<usize>, update: Act::Update) {
+        let (start, end) = to_half_open(range, self.len);
+        if start == end {
+            return;
+        }
+        let (l0, r0) = (start + self.size, end + self.size);
+        for i in (1..=self.log).rev() {
+            if (l0 >> i) << i != l0 {
+                self.push(l0 >> i);
+            }
+            if (r0 >> i) << i != r0 {
+                self.push((r0 - 1) >> i);
+            }
+        }
+
+        let (mut l, mut r) = (l0, r0);
+        while l < r {
+            if l & 1 == 1 {
+                self.apply_to_node(l, &update);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                self.apply_to_node(r, &update);
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+
+        // recompute the ancestors of the partially covered boundary nodes
+        for i in 1..=self.log {
+            if (l0 >> i) << i != l0 {
+                self.pull(l0 >> i);
+            }
+            if (r0 >> i) << i != r0 {
+                self.pull((r0 - 1) >> i);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    // brute force model: a plain Vec, updated element by element
+    fn check<Op, Act>(
+        seed: u64,
+        random_update: impl Fn(&mut StdRng) -> Act::Update,
+        apply_one: impl Fn(&Act::Update, i64) -> i64,
+    ) where
+        Op: Monoid<i64>,
+        Act: LazyAction<i64, Op>,
+    {
+        let mut rng = StdRng::seed_from_u64(seed);
+        for len in [0, 1, 2, 5, 8, 13, 32] {
+            let mut brute = (0..len)
+                .map(|_| rng.gen_range(-50..50))
+                .collect::<Vec<i64>>();
+            let mut tree = LazySegmentTree::<i64, Op, Act>::from_vec(brute.clone());
+
+            for _ in 0..300 {
+                let l = rng.gen_range(0..=len);
+                let r = rng.gen_range(l..=len);
+                match rng.gen_range(0..4) {
+                    0 => {
+                        let update = random_update(&mut rng);
+                        brute[l..r]
+                            .iter_mut()
+                            .for_each(|v| *v = apply_one(&update, *v));
+                        tree.update(l..r, update);
+                    }
+                    1 if len > 0 => {
+                        let (i, v) = (rng.gen_range(0..len), rng.gen_range(-50..50));
+                        brute[i] = v;
+                        tree.set(i, v);
+                    }
+                    2 if len > 0 => {
+                        let i = rng.gen_range(0..len);
+                        assert_eq!(tree.get(i), brute[i]);
+                    }
+                    _ => {
+                        let expected = brute[l..r]
+                            .iter()
+                            .fold(Op::identity(), |acc, v| Op::combine(&acc, v));
+                        assert_eq!(tree.query(l..r), expected, "len {len}, range {l}..{r}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn range_add_matches_brute_force() {
+        let add = |update: &i64, v: i64| v + update;
+        check::<Min, RangeAdd>(1, |rng| rng.gen_range(-10..10), add);
+        check::<Max, RangeAdd>(2, |rng| rng.gen_range(-10..10), add);
+        check::<Sum, RangeAdd>(3, |rng| rng.gen_range(-10..10), add);
+    }
+
+    #[test]
+    fn range_assign_matches_brute_force() {
+        let assign = |update: &Option<i64>, v: i64| update.unwrap_or(v);
+        let random = |rng: &mut StdRng| Some(rng.gen_range(-50..50));
+        check::<Min, RangeAssign>(4, random, assign);
+        check::<Max, RangeAssign>(5, random, assign);
+        check::<Sum, RangeAssign>(6, random, assign);
+    }
+
+    #[test]
+    fn interval_bookings() {
+        // max number of overlapping bookings [start, end) over the day's 24 hours
+        let bookings = [(9, 12), (10, 11), (11, 14), (10, 12), (13, 17)];
+        let mut hours = LazySegmentTree::<i32, Max, RangeAdd>::from_vec(vec![0; 24]);
+        for (start, end) in bookings {
+            hours.update(start..end, 1);
+        }
+        assert_eq!(hours.query(..), 3); // 10:00 - 11:00
+        assert_eq!(hours.query(14..), 1);
+        assert_eq!(hours.query(..9), 0);
+    }
+
+    #[test]
+    fn range_add_sums_up_to_the_limit() {
+        let mut single = LazySegmentTree::<i32, Sum, RangeAdd>::from_vec(vec![0]);
+        single.update(0..1, 1 << 30);
+        assert_eq!(single.query(..), 1 << 30);
+
+        // every prefix length, with an update as large as the sum allows
+        for len in 1..=64 {
+            let delta = i32::MAX / len as i32;
+            let mut tree = LazySegmentTree::<i32, Sum, RangeAdd>::new(len);
+            tree.update(.., delta);
+            assert_eq!(tree.query(..), delta * len as i32);
+        }
+    }
+}
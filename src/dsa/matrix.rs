@@ -0,0 +1,488 @@
+// == Matrix ==
+/* A rows x cols matrix stored row-major in one Vec, instead of a Vec<Vec<T>> where every row is
+ * its own allocation and nothing says the rows have the same length (the old kth_smallest read
+ * `matrix.last().unwrap().len()` and hoped). `from_rows` checks that once, and from then on the
+ * shape is known.
+ *
+ * Rows are contiguous slices, columns are strided iterators over the same storage.
+ *
+ * For matrices sorted along both rows and columns (every row and every column non-decreasing):
+ * - staircase search: start at the top right corner, every comparison drops a row (the value is
+ *   larger than everything left in it) or a column (smaller than everything below), O(rows + cols)
+ * - k-th smallest: binary search on the value, counting the values <= x with the same staircase
+ *   walk, O((rows + cols) log(max - min))
+ *
+ * Multiplication and `pow` (by squaring, O(n³ log e)) are what linear recurrences need: the next
+ * terms of a_n = c_1 a_(n-1) + ... + c_d a_(n-d) are the previous ones times a d x d matrix, so
+ * the n-th term is a power of it, see `linear_recurrence`.
+ */
+
+use std::{
+    cmp::Ordering,
+    fmt,
+    ops::{Add, Index, IndexMut, Mul},
+};
+
+use anyhow::bail;
+
+use super::{
+    monoid::{One, Zero},
+    search::{partition_point_int, Integer},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Matrix<T> {
+    rows: usize,
+    cols: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Matrix<T> {
+    /// `cell(row, col)` for every cell, in row-major order
+    pub fn from_fn(rows: usize, cols: usize, mut cell: impl FnMut(usize, usize) -> T) -> Self {
+        let cells = (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (row, col)))
+            .map(|(row, col)| cell(row, col))
+            .collect();
+        Matrix { rows, cols, cells }
+    }
+
+    /// Fails when the rows don't all have the same length. No rows is a 0 x 0 matrix
+    pub fn from_rows(rows: Vec<Vec<T>>) -> anyhow::Result<Self> {
+        let cols = rows.first().map_or(0, Vec::len);
+        if let Some((i, row)) = rows.iter().enumerate().find(|(_, row)| row.len() != cols) {
+            bail!(
+                "row {i} has {} values, row 0 has {cols}: rows must have the same length",
+                row.len()
+            );
+        }
+        Ok(Matrix {
+            rows: rows.len(),
+            cols,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if row >= self.rows || col >= self.cols {
+            return None;
+        }
+        Some(&self.cells[row * self.cols + col])
+    }
+
+    /// Panics if `row` is out of bounds
+    pub fn row(&self, row: usize) -> &[T] {
+        assert!(
+            row < self.rows,
+            "row {row} of a {}x{} matrix",
+            self.rows,
+            self.cols
+        );
+        &self.cells[row * self.cols..(row + 1) * self.cols]
+    }
+
+    /// Panics if `row` is out of bounds
+    pub fn row_mut(&mut self, row: usize) -> &mut [T] {
+        assert!(
+            row < self.rows,
+            "row {row} of a {}x{} matrix",
+            self.rows,
+            self.cols
+        );
+        &mut self.cells[row * self.cols..(row + 1) * self.cols]
+    }
+
+    /// Top to bottom. Panics if `col` is out of bounds
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> + Clone {
+        assert!(
+            col < self.cols,
+            "column {col} of a {}x{} matrix",
+            self.rows,
+            self.cols
+        );
+        self.cells.iter().skip(col).step_by(self.cols)
+    }
+
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.rows).map(|row| self.row(row))
+    }
+
+    pub fn into_rows(self) -> Vec<Vec<T>> {
+        let mut cells = self.cells.into_iter();
+        (0..self.rows)
+            .map(|_| cells.by_ref().take(self.cols).collect())
+            .collect()
+    }
+}
+
+impl<T: Clone> Matrix<T> {
+    pub fn filled(rows: usize, cols: usize, value: T) -> Self {
+        Matrix {
+            rows,
+            cols,
+            cells: vec![value; rows * cols],
+        }
+    }
+
+    pub fn transpose(&self) -> Self {
+        Matrix::from_fn(self.cols, self.rows, |row, col| self[(col, row)].clone())
+    }
+}
+
+// == Sorted matrices ==
+
+impl<T: Ord> Matrix<T> {
+    /// Position of a cell equal to `value`, in a matrix whose rows and columns are sorted
+    pub fn staircase_search(&self, value: &T) -> Option<(usize, usize)> {
+        let (mut row, mut col) = (0, self.cols.checked_sub(1)?);
+        while row < self.rows {
+            match self[(row, col)].cmp(value) {
+                Ordering::Equal => return Some((row, col)),
+                // smaller than everything left in the row
+                Ordering::Greater if col == 0 => return None,
+                Ordering::Greater => col -= 1,
+                // larger than everything in the column so far
+                Ordering::Less => row += 1,
+            }
+        }
+        None
+    }
+
+    /// How many cells are <= `value`, in a matrix whose rows and columns are sorted
+    pub fn count_at_most(&self, value: &T) -> usize {
+        // from the bottom left, `col` is how many cells of `row` are <= value, and it can only
+        // grow going up
+        let mut count = 0;
+        let mut col = 0;
+        for row in (0..self.rows).rev() {
+            while col < self.cols && self[(row, col)] <= *value {
+                col += 1;
+            }
+            count += col;
+        }
+        count
+    }
+}
+
+impl<T: Integer> Matrix<T> {
+    /// k-th smallest value (1-based, duplicates counted) of a matrix whose rows and columns are
+    /// sorted, None when k is 0 or larger than the number of cells
+    pub fn kth_smallest(&self, k: usize) -> Option<T> {
+        if k == 0 || k > self.cells.len() {
+            return None;
+        }
+        let (low, high) = (self.cells[0], self.cells[self.cells.len() - 1]);
+        // the first x with count_at_most(x) >= k, high when no x before it is
+        Some(partition_point_int(low..high, |x| {
+            self.count_at_most(&x) < k
+        }))
+    }
+}
+
+// == Arithmetic ==
+
+impl<T: Zero + One + Clone> Matrix<T> {
+    pub fn identity(n: usize) -> Self {
+        Matrix::from_fn(
+            n,
+            n,
+            |row, col| if row == col { T::one() } else { T::zero() },
+        )
+    }
+}
+
+impl<T: Zero + Add<Output = T> + Mul<Output = T> + Clone> Matrix<T> {
+    /// self x other, panics unless self.cols() == other.rows()
+    pub fn multiply(&self, other: &Self) -> Self {
+        assert_eq!(
+            self.cols, other.rows,
+            "can't multiply a {}x{} matrix by a {}x{} one",
+            self.rows, self.cols, other.rows, other.cols
+        );
+        Matrix::from_fn(self.rows, other.cols, |row, col| {
+            self.row(row)
+                .iter()
+                .zip(other.column(col))
+                .fold(T::zero(), |sum, (a, b)| sum + a.clone() * b.clone())
+        })
+    }
+}
+
+impl<T: Zero + One + Add<Output = T> + Mul<Output = T> + Clone> Matrix<T> {
+    /// self^exp by squaring, O(log exp) multiplications. Panics unless the matrix is square
+    pub fn pow(&self, mut exp: u64) -> Self {
+        assert!(
+            self.is_square(),
+            "pow of a {}x{} matrix",
+            self.rows,
+            self.cols
+        );
+        let mut result = Matrix::identity(self.rows);
+        let mut base = self.clone();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.multiply(&base);
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.multiply(&base);
+            }
+        }
+        result
+    }
+}
+
+/// n-th term (0-based) of a_n = coefficients[0] a_(n-1) + coefficients[1] a_(n-2) + ..., starting
+/// from `initial` = [a_0, a_1, ..] (as many as coefficients), in O(d³ log n) for d coefficients
+///
+/// NOTE: the terms usually grow exponentially, for large n use a type that wraps around (or
+/// reduces modulo something) on overflow
+pub fn linear_recurrence<T>(coefficients: &[T], initial: &[T], n: u64) -> T
+where
+    T: Zero + One + Add<Output = T> + Mul<Output = T> + Clone,
+{
+    let d = coefficients.len();
+    assert!(d > 0, "empty recurrence");
+    assert_eq!(initial.len(), d, "needs one initial term per coefficient");
+    if n < d as u64 {
+        return initial[n as usize].clone();
+    }
+    // maps [a_(i+d-1), .., a_i] to [a_(i+d), .., a_(i+1)]: the coefficients, then a shift
+    let step = Matrix::from_fn(d, d, |row, col| match row {
+        0 => coefficients[col].clone(),
+        _ if col + 1 == row => T::one(),
+        _ => T::zero(),
+    });
+    let last = Matrix::from_fn(d, 1, |row, _| initial[d - 1 - row].clone());
+    step.pow(n - (d as u64 - 1)).multiply(&last)[(0, 0)].clone()
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        assert!(
+            col < self.cols,
+            "column {col} of a {}x{} matrix",
+            self.rows,
+            self.cols
+        );
+        &self.row(row)[col]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        assert!(
+            col < self.cols,
+            "column {col} of a {}x{} matrix",
+            self.rows,
+            self.cols
+        );
+        &mut self.row_mut(row)[col]
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Matrix<T> {
+    /// One row per line, values separated by spaces
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.iter_rows() {
+            for (i, value) in row.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " ")?;
+                }
+                write!(f, "{value}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsa::stress::{check, Config};
+
+    // sorted along rows and columns: every cell is at least the one above and the one left of it,
+    // by a step from `steps` (cycled, so any number of them fits any shape)
+    fn sorted_matrix(rows: usize, cols: usize, steps: &[i32]) -> Matrix<i32> {
+        let mut matrix = Matrix::filled(rows, cols, 0);
+        for row in 0..rows {
+            for col in 0..cols {
+                let above = if row > 0 { matrix[(row - 1, col)] } else { -30 };
+                let left = if col > 0 { matrix[(row, col - 1)] } else { -30 };
+                let step = steps.get((row * cols + col) % steps.len().max(1));
+                matrix[(row, col)] = above.max(left) + step.map_or(0, |s| s.rem_euclid(4));
+            }
+        }
+        matrix
+    }
+
+    #[test]
+    fn sorted_queries_match_scans() {
+        // ((rows, cols), steps) -> (is each value there, how many are <= it, the kth smallest)
+        check(
+            "staircase_search, count_at_most and kth_smallest",
+            Config::default(),
+            |g| {
+                (
+                    (g.int(0..=6), g.int(0..=6)),
+                    g.vec(0..=36, |g| g.int(0..=3)),
+                )
+            },
+            |((rows, cols), steps)| {
+                let matrix = sorted_matrix(*rows, *cols, steps);
+                let mut values = matrix.iter_rows().flatten().copied().collect::<Vec<_>>();
+                values.sort();
+                let found = (-32..10).map(|value| values.contains(&value));
+                let at_most = (-32..10).map(|value| values.iter().filter(|&&v| v <= value).count());
+                let kth = (0..=values.len() + 1)
+                    .map(|k| k.checked_sub(1).and_then(|i| values.get(i)).copied());
+                (
+                    found.collect::<Vec<_>>(),
+                    at_most.collect::<Vec<_>>(),
+                    kth.collect::<Vec<_>>(),
+                )
+            },
+            |((rows, cols), steps)| {
+                let matrix = sorted_matrix(*rows, *cols, steps);
+                let found = (-32..10).map(|value| {
+                    matrix
+                        .staircase_search(&value)
+                        .is_some_and(|cell| matrix[cell] == value)
+                });
+                let at_most = (-32..10).map(|value| matrix.count_at_most(&value));
+                let kth = (0..=rows * cols + 1).map(|k| matrix.kth_smallest(k));
+                (
+                    found.collect::<Vec<_>>(),
+                    at_most.collect::<Vec<_>>(),
+                    kth.collect::<Vec<_>>(),
+                )
+            },
+        );
+    }
+
+    #[test]
+    fn shapes_and_views() {
+        let matrix = Matrix::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+        assert_eq!((matrix.rows(), matrix.cols()), (2, 3));
+        assert_eq!(matrix.row(1), [4, 5, 6]);
+        assert_eq!(matrix.column(2).collect::<Vec<_>>(), [&3, &6]);
+        assert_eq!(matrix.get(2, 0), None);
+        assert_eq!(matrix.to_string(), "1 2 3\n4 5 6\n");
+
+        let transposed = matrix.transpose();
+        assert_eq!(transposed.into_rows(), [[1, 4], [2, 5], [3, 6]]);
+        assert_eq!(Matrix::<i32>::from_rows(vec![]).unwrap().rows(), 0);
+
+        let ragged = Matrix::from_rows(vec![vec![1, 2], vec![3]]).unwrap_err();
+        assert!(ragged.to_string().contains("row 1 has 1 values"));
+    }
+
+    // rows x cols cells taken from `values` (cycled, 0 if there are none)
+    fn cells(values: &[i64], rows: usize, cols: usize) -> Vec<Vec<i64>> {
+        (0..rows)
+            .map(|row| {
+                (0..cols)
+                    .map(|col| values.get((row * cols + col) % values.len().max(1)))
+                    .map(|value| value.copied().unwrap_or(0))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn to_matrix(cells: &[Vec<i64>], rows: usize, cols: usize) -> Matrix<i64> {
+        Matrix::from_fn(rows, cols, |row, col| cells[row][col])
+    }
+
+    fn naive_multiply(a: &[Vec<i64>], b: &[Vec<i64>], cols: usize) -> Vec<Vec<i64>> {
+        a.iter()
+            .map(|row| {
+                (0..cols)
+                    .map(|col| row.iter().zip(b).map(|(x, b_row)| x * b_row[col]).sum())
+                    .collect()
+            })
+            .collect()
+    }
+
+    // ((n, m, p), (a, b, square)): a is n x m, b is m x p and square is n x n
+    type Products = ((usize, usize, usize), (Vec<i64>, Vec<i64>, Vec<i64>));
+
+    #[test]
+    fn multiplication_and_powers() {
+        // -> (a times b, (ab)ᵀ computed as bᵀaᵀ, square to the powers 0 to 5)
+        check(
+            "multiply and pow",
+            Config::default(),
+            |g| {
+                let dims = (g.int(0..=4), g.int(0..=4), g.int(0..=4));
+                let a = g.vec(0..=16, |g| g.int(-5..=4));
+                let b = g.vec(0..=16, |g| g.int(-5..=4));
+                (dims, (a, b, g.vec(0..=16, |g| g.int(-2..=2))))
+            },
+            |((n, m, p), (a, b, square)): &Products| {
+                let (a, b) = (cells(a, *n, *m), cells(b, *m, *p));
+                let square = cells(square, *n, *n);
+                let mut power = (0..*n)
+                    .map(|row| (0..*n).map(|col| i64::from(row == col)).collect())
+                    .collect::<Vec<Vec<i64>>>();
+                let mut powers = vec![];
+                for _ in 0..6 {
+                    let next = naive_multiply(&power, &square, *n);
+                    powers.push(power);
+                    power = next;
+                }
+                let product = naive_multiply(&a, &b, *p);
+                let transposed = (0..*p)
+                    .map(|col| product.iter().map(|row| row[col]).collect())
+                    .collect::<Vec<Vec<i64>>>();
+                (product, transposed, powers)
+            },
+            |((n, m, p), (a, b, square)): &Products| {
+                let a = to_matrix(&cells(a, *n, *m), *n, *m);
+                let b = to_matrix(&cells(b, *m, *p), *m, *p);
+                let square = to_matrix(&cells(square, *n, *n), *n, *n);
+                let product = a.multiply(&b);
+                let transposed = b.transpose().multiply(&a.transpose());
+                let powers = (0..6).map(|exp| square.pow(exp).into_rows());
+                (
+                    product.into_rows(),
+                    transposed.into_rows(),
+                    powers.collect::<Vec<_>>(),
+                )
+            },
+        );
+    }
+
+    #[test]
+    fn recurrences() {
+        let fib = |n| linear_recurrence(&[1u64, 1], &[0, 1], n);
+        assert_eq!(
+            (0..10).map(fib).collect::<Vec<_>>(),
+            [0, 1, 1, 2, 3, 5, 8, 13, 21, 34]
+        );
+        assert_eq!(fib(90), 2880067194370816120);
+
+        // a_n = 2 a_(n-1) - a_(n-3), checked against iterating it
+        let mut terms = vec![1i64, 0, 3];
+        for n in 3..40 {
+            terms.push(2 * terms[n - 1] - terms[n - 3]);
+        }
+        for (n, &term) in terms.iter().enumerate() {
+            assert_eq!(linear_recurrence(&[2, 0, -1], &[1, 0, 3], n as u64), term);
+        }
+    }
+}
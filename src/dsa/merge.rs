@@ -0,0 +1,197 @@
+// == K-way merge ==
+/* Merges any number of sorted iterators into one sorted iterator, lazily: the heap holds the
+ * current head of every source (at most k values), popping the smallest refills from the same
+ * source. n values from k sources cost O(n log k), and taking only the first few is cheap.
+ *
+ * BinaryHeap is a max heap, so entries are wrapped in `Reverse` (rather than negating values,
+ * which only works for signed numbers and overflows on MIN).
+ *
+ * On a matrix with sorted rows, the k-th smallest is just the k-th value of the merge. For huge
+ * matrices (or huge k) `kth_smallest_by_value` binary searches the answer in value space instead:
+ * O(rows * log(cols) * log(range)) time, no extra memory.
+ */
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+pub struct KWayMerge<I: Iterator> {
+    sources: Vec<I>,
+    heap: BinaryHeap<Reverse<(I::Item, usize)>>,
+}
+
+impl<I> KWayMerge<I>
+where
+    I: Iterator,
+    I::Item: Ord,
+{
+    /// Every source must already be sorted (ascending), it's not checked
+    pub fn new<S>(sources: impl IntoIterator<Item = S>) -> Self
+    where
+        S: IntoIterator<IntoIter = I>,
+    {
+        let mut sources = sources
+            .into_iter()
+            .map(IntoIterator::into_iter)
+            .collect::<Vec<_>>();
+        let heap = sources
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(source, iter)| Some(Reverse((iter.next()?, source))))
+            .collect();
+        KWayMerge { sources, heap }
+    }
+}
+
+impl<I> Iterator for KWayMerge<I>
+where
+    I: Iterator,
+    I::Item: Ord,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let Reverse((value, source)) = self.heap.pop()?;
+        if let Some(next) = self.sources[source].next() {
+            self.heap.push(Reverse((next, source)));
+        }
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.sources.iter().fold(
+            (self.heap.len(), Some(self.heap.len())),
+            |(lo, hi), iter| {
+                let (source_lo, source_hi) = iter.size_hint();
+                (
+                    lo.saturating_add(source_lo),
+                    hi.zip(source_hi).and_then(|(a, b)| a.checked_add(b)),
+                )
+            },
+        )
+    }
+}
+
+/// Shorthand for `KWayMerge::new`, eg. `merge(matrix.iter())` over the rows of a matrix
+pub fn merge<S>(sources: impl IntoIterator<Item = S>) -> KWayMerge<S::IntoIter>
+where
+    S: IntoIterator,
+    S::Item: Ord,
+{
+    KWayMerge::new(sources)
+}
+
+/// k-th smallest (1-based k) of all the sorted sources, None if k is 0 or there are fewer values
+pub fn kth_smallest<S>(sources: impl IntoIterator<Item = S>, k: usize) -> Option<S::Item>
+where
+    S: IntoIterator,
+    S::Item: Ord,
+{
+    merge(sources).nth(k.checked_sub(1)?)
+}
+
+/// The k smallest values of all the sorted sources, in order (fewer if there aren't k values)
+///
+/// NOTE: for the k largest, merge the sources reversed with their values wrapped in `Reverse`
+pub fn top_k<S>(sources: impl IntoIterator<Item = S>, k: usize) -> Vec<S::Item>
+where
+    S: IntoIterator,
+    S::Item: Ord,
+{
+    merge(sources).take(k).collect()
+}
+
+/// k-th smallest (1-based k) of sorted rows, by binary searching the answer's value
+///
+/// count(x) = how many values are <= x is a sum of a binary search per row, and the answer is the
+/// smallest x with count(x) >= k (which is always one of the values).
+pub fn kth_smallest_by_value<T, R>(rows: &[R], k: usize) -> Option<T>
+where
+    T: Copy + Ord + Into<i64> + TryFrom<i64>,
+    R: AsRef<[T]>,
+{
+    let total = rows.iter().map(|row| row.as_ref().len()).sum::<usize>();
+    if k == 0 || k > total {
+        return None;
+    }
+    let count_at_most = |x: i64| {
+        rows.iter()
+            .map(|row| row.as_ref().partition_point(|&v| v.into() <= x))
+            .sum::<usize>()
+    };
+
+    let values = rows
+        .iter()
+        .flat_map(|row| row.as_ref().first().into_iter().chain(row.as_ref().last()));
+    let (mut lo, mut hi) = (
+        values.clone().min().copied()?.into(),
+        values.max().copied()?.into(),
+    );
+    while lo < hi {
+        let mid = (lo & hi) + ((lo ^ hi) >> 1); // floor((lo + hi) / 2) without overflowing
+        if count_at_most(mid) >= k {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    T::try_from(lo).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn sorted_rows(rng: &mut StdRng, rows: usize) -> Vec<Vec<i32>> {
+        (0..rows)
+            .map(|_| {
+                let len = rng.gen_range(0..10);
+                let mut row = (0..len).map(|_| rng.gen_range(-50..50)).collect::<Vec<_>>();
+                row.sort();
+                row
+            })
+            .collect()
+    }
+
+    #[test]
+    fn merges_like_sorting_everything() {
+        let mut rng = StdRng::seed_from_u64(35);
+        for rows in 0..12 {
+            let matrix = sorted_rows(&mut rng, rows);
+            let mut expected = matrix.concat();
+            expected.sort();
+
+            let merged = merge(matrix.iter()).copied().collect::<Vec<_>>();
+            assert_eq!(merged, expected);
+            assert_eq!(
+                merge(matrix.iter()).size_hint(),
+                (expected.len(), Some(expected.len()))
+            );
+
+            for k in 0..=expected.len() + 1 {
+                let nth = k.checked_sub(1).and_then(|i| expected.get(i)).copied();
+                assert_eq!(kth_smallest(matrix.iter(), k).copied(), nth);
+                assert_eq!(kth_smallest_by_value(&matrix, k), nth);
+                assert_eq!(top_k(matrix.iter(), k).len(), k.min(expected.len()));
+            }
+        }
+    }
+
+    #[test]
+    fn is_lazy() {
+        // infinite sources are fine as long as only a prefix is taken
+        let multiples = (1..4).map(|step| (0..).step_by(step));
+        assert_eq!(top_k(multiples, 8), [0, 0, 0, 1, 2, 2, 3, 3]);
+    }
+
+    #[test]
+    fn merges_any_ord_and_empty_input() {
+        let sources = [vec![(1, 'a'), (3, 'a')], vec![(1, 'b'), (2, 'b')]];
+        assert_eq!(
+            merge(sources).map(|(_, source)| source).collect::<String>(),
+            "abba"
+        );
+        // and empty input is just empty, not a panic
+        assert_eq!(kth_smallest(Vec::<Vec<i32>>::new(), 1), None);
+        assert_eq!(kth_smallest_by_value::<i32, Vec<i32>>(&[], 1), None);
+    }
+}
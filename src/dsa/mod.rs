@@ -1,2 +1,25 @@
-pub mod kth_smallest_element_in_a_sorted_matrix;
-pub mod max_area;
+pub mod arena;
+pub mod bench;
+pub mod disjoint_set;
+pub mod dp;
+pub mod fenwick_tree;
+pub mod graph;
+pub mod indexed_heap;
+pub mod interval_tree;
+pub mod lazy_segment_tree;
+pub mod matrix;
+pub mod merge;
+pub mod monoid;
+pub mod monotonic;
+pub mod ordered_map;
+pub mod persistent_list;
+pub mod persistent_segment_tree;
+pub mod persistent_vector;
+pub mod problems;
+pub mod range_query;
+pub mod search;
+pub mod segment_tree;
+pub mod sparse_table;
+pub mod stress;
+pub mod strings;
+pub mod tree;
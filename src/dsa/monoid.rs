@@ -0,0 +1,211 @@
+// == Monoids ==
+/* A monoid is an associative `combine` with an `identity` element:
+ *     combine(a, combine(b, c)) == combine(combine(a, b), c)
+ *     combine(identity, a) == a == combine(a, identity)
+ * That's all a range structure (segment tree, ...) needs to answer "combine everything in [l, r)",
+ * so they are generic over `Op: Monoid<T>` and the operation is picked by a zero-sized marker type:
+ *
+ *     SegmentTree::<i32, Min>::from_vec(values)
+ *
+ * NOTE: combine doesn't have to be commutative (eg. string concatenation, function composition),
+ * range structures must keep the left-to-right order of the elements.
+ *
+ * For a custom operation, implement Monoid<T> for your own marker struct.
+ */
+
+use std::ops::Add;
+
+pub trait Monoid<T> {
+    fn identity() -> T;
+    fn combine(a: &T, b: &T) -> T;
+}
+
+// == Numeric helpers for the built-in monoids ==
+
+pub trait Bounded {
+    fn min_value() -> Self;
+    fn max_value() -> Self;
+}
+
+pub trait Zero {
+    fn zero() -> Self;
+}
+
+pub trait One {
+    fn one() -> Self;
+}
+
+pub trait Gcd {
+    /// Greatest common divisor, always non-negative, gcd(0, 0) == 0
+    ///
+    /// For signed types the gcd of MIN with 0 or MIN is 2^(bits - 1), one more than MAX, and that
+    /// panics (every other pair, MIN included, works)
+    fn gcd(a: Self, b: Self) -> Self;
+}
+
+macro_rules! impl_integer_helpers {
+    ($($t:ty),*) => {
+        $(
+            impl Bounded for $t {
+                fn min_value() -> Self { <$t>::MIN }
+                fn max_value() -> Self { <$t>::MAX }
+            }
+
+            impl Zero for $t {
+                fn zero() -> Self { 0 }
+            }
+
+            impl One for $t {
+                fn one() -> Self { 1 }
+            }
+        )*
+    };
+}
+
+impl_integer_helpers!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+macro_rules! impl_unsigned_gcd {
+    ($($t:ty),*) => {
+        $(
+            impl Gcd for $t {
+                fn gcd(a: Self, b: Self) -> Self {
+                    let (mut a, mut b) = (a, b);
+                    while b != 0 {
+                        (a, b) = (b, a % b);
+                    }
+                    a
+                }
+            }
+        )*
+    };
+}
+
+impl_unsigned_gcd!(u8, u16, u32, u64, u128, usize);
+
+// NOTE: signed values go through their absolute values as the unsigned type, `-a` overflows for
+// MIN (and so does MIN % -1)
+macro_rules! impl_signed_gcd {
+    ($($t:ty => $u:ty),*) => {
+        $(
+            impl Gcd for $t {
+                fn gcd(a: Self, b: Self) -> Self {
+                    let gcd = <$u>::gcd(a.unsigned_abs(), b.unsigned_abs());
+                    <$t>::try_from(gcd).unwrap_or_else(|_| {
+                        panic!("gcd({a}, {b}) = {gcd} doesn't fit in {}", stringify!($t))
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_signed_gcd!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
+
+macro_rules! impl_float_helpers {
+    ($($t:ty),*) => {
+        $(
+            impl Bounded for $t {
+                fn min_value() -> Self { <$t>::NEG_INFINITY }
+                fn max_value() -> Self { <$t>::INFINITY }
+            }
+
+            impl Zero for $t {
+                fn zero() -> Self { 0.0 }
+            }
+
+            impl One for $t {
+                fn one() -> Self { 1.0 }
+            }
+        )*
+    };
+}
+
+impl_float_helpers!(f32, f64);
+
+// == Built-in monoids ==
+
+pub struct Min;
+pub struct Max;
+pub struct Sum;
+pub struct GcdOf;
+
+// PartialOrd (not Ord) so floats work too, identity is the largest value (+inf for floats)
+impl<T: Bounded + PartialOrd + Clone> Monoid<T> for Min {
+    fn identity() -> T {
+        T::max_value()
+    }
+
+    fn combine(a: &T, b: &T) -> T {
+        if b < a {
+            b.clone()
+        } else {
+            a.clone()
+        }
+    }
+}
+
+impl<T: Bounded + PartialOrd + Clone> Monoid<T> for Max {
+    fn identity() -> T {
+        T::min_value()
+    }
+
+    fn combine(a: &T, b: &T) -> T {
+        if b > a {
+            b.clone()
+        } else {
+            a.clone()
+        }
+    }
+}
+
+impl<T: Zero + Add<Output = T> + Clone> Monoid<T> for Sum {
+    fn identity() -> T {
+        T::zero()
+    }
+
+    fn combine(a: &T, b: &T) -> T {
+        a.clone() + b.clone()
+    }
+}
+
+impl<T: Zero + Gcd + Clone> Monoid<T> for GcdOf {
+    fn identity() -> T {
+        T::zero()
+    }
+
+    fn combine(a: &T, b: &T) -> T {
+        T::gcd(a.clone(), b.clone())
+    }
+}
+
+// == Idempotent monoids ==
+
+/// combine(a, a) == a, so overlapping ranges can be combined without counting anything twice
+/// (what SparseTable relies on for its O(1) queries)
+pub trait Idempotent<T>: Monoid<T> {}
+
+impl<T: Bounded + PartialOrd + Clone> Idempotent<T> for Min {}
+impl<T: Bounded + PartialOrd + Clone> Idempotent<T> for Max {}
+impl<T: Zero + Gcd + Clone> Idempotent<T> for GcdOf {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gcd_handles_signs_and_min() {
+        assert_eq!(i32::gcd(12, -18), 6);
+        assert_eq!(i32::gcd(-12, 0), 12);
+        assert_eq!(i32::gcd(0, 0), 0);
+        assert_eq!(i32::gcd(i32::MIN, 6), 2);
+        assert_eq!(i32::gcd(i32::MIN, -1), 1);
+        assert_eq!(i8::gcd(i8::MIN, i8::MAX), 1);
+        assert_eq!(u64::gcd(u64::MAX, 5), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't fit in i32")]
+    fn gcd_of_min_and_zero_doesnt_fit() {
+        i32::gcd(i32::MIN, 0);
+    }
+}
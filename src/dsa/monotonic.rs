@@ -0,0 +1,373 @@
+// == Monotonic stack and deque ==
+/* A stack (or deque) that stays sorted from bottom to top by dropping, on every push, the items
+ * the new value makes useless. Every item is pushed and popped at most once, so a whole pass
+ * over n values is O(n).
+ *
+ * Items remember their index in the pushed sequence (0, 1, 2, ..), which is what the helpers
+ * below hand back, and what the deque uses to expire items that slid out of a window.
+ *
+ * The order is picked at construction, named after how the stack reads from bottom to top:
+ *
+ *     MonotonicStack::increasing()      // pushing x pops everything >= x
+ *     MonotonicStack::non_decreasing()  // pushing x pops everything > x
+ *     MonotonicStack::decreasing()      // pushing x pops everything <= x
+ *     MonotonicStack::non_increasing()  // pushing x pops everything < x
+ *
+ * The values a push pops are exactly those whose next smaller (or greater, ...) value is the one
+ * pushed, and what's left right below it is its previous smaller (or greater, ...) value.
+ */
+
+use std::{cmp::Ordering, collections::VecDeque};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Increasing,
+    NonDecreasing,
+    Decreasing,
+    NonIncreasing,
+}
+
+impl Kind {
+    // whether pushing `new` pops `top`
+    fn pops<T: Ord>(self, top: &T, new: &T) -> bool {
+        let order = top.cmp(new);
+        match self {
+            Kind::Increasing => order != Ordering::Less,
+            Kind::NonDecreasing => order == Ordering::Greater,
+            Kind::Decreasing => order != Ordering::Greater,
+            Kind::NonIncreasing => order == Ordering::Less,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MonotonicStack<T> {
+    kind: Kind,
+    /// (index in the pushed sequence, value), bottom first
+    items: Vec<(usize, T)>,
+    pushed: usize,
+}
+
+impl<T: Ord> MonotonicStack<T> {
+    pub fn increasing() -> Self {
+        Self::with_kind(Kind::Increasing)
+    }
+
+    pub fn non_decreasing() -> Self {
+        Self::with_kind(Kind::NonDecreasing)
+    }
+
+    pub fn decreasing() -> Self {
+        Self::with_kind(Kind::Decreasing)
+    }
+
+    pub fn non_increasing() -> Self {
+        Self::with_kind(Kind::NonIncreasing)
+    }
+
+    fn with_kind(kind: Kind) -> Self {
+        MonotonicStack {
+            kind,
+            items: vec![],
+            pushed: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn peek(&self) -> Option<(usize, &T)> {
+        self.items.last().map(|(index, value)| (*index, value))
+    }
+
+    /// The item right below the top: for the value pushed last, the closest earlier one it
+    /// didn't pop
+    pub fn previous(&self) -> Option<(usize, &T)> {
+        let below = self.items.len().checked_sub(2)?;
+        let (index, value) = &self.items[below];
+        Some((*index, value))
+    }
+
+    /// Pushes `value` (its index is the number of values pushed before), returning the items it
+    /// popped, most recent first
+    pub fn push(&mut self, value: T) -> Vec<(usize, T)> {
+        let mut popped = vec![];
+        while let Some((_, top)) = self.items.last() {
+            if !self.kind.pops(top, &value) {
+                break;
+            }
+            popped.extend(self.items.pop());
+        }
+        self.items.push((self.pushed, value));
+        self.pushed += 1;
+        popped
+    }
+
+    /// Bottom to top
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.items.iter().map(|(index, value)| (*index, value))
+    }
+}
+
+/// Sliding window minimum (or maximum): values go in at the back, the front is the smallest (or
+/// largest) of the values still in the window
+#[derive(Debug, Clone)]
+pub struct MonotonicDeque<T> {
+    kind: Kind,
+    items: VecDeque<(usize, T)>,
+    pushed: usize,
+}
+
+impl<T: Ord> MonotonicDeque<T> {
+    /// front() is the smallest value
+    pub fn min() -> Self {
+        Self::with_kind(Kind::Increasing)
+    }
+
+    /// front() is the largest value
+    pub fn max() -> Self {
+        Self::with_kind(Kind::Decreasing)
+    }
+
+    fn with_kind(kind: Kind) -> Self {
+        MonotonicDeque {
+            kind,
+            items: VecDeque::new(),
+            pushed: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns the index of `value` in the pushed sequence
+    pub fn push_back(&mut self, value: T) -> usize {
+        while let Some((_, back)) = self.items.back() {
+            if !self.kind.pops(back, &value) {
+                break;
+            }
+            self.items.pop_back();
+        }
+        self.items.push_back((self.pushed, value));
+        self.pushed += 1;
+        self.pushed - 1
+    }
+
+    /// Drops the values pushed before index `start` (the window now starts there)
+    pub fn expire_before(&mut self, start: usize) {
+        while self.items.front().is_some_and(|(index, _)| *index < start) {
+            self.items.pop_front();
+        }
+    }
+
+    /// The smallest (or largest) value still in the window, with its index
+    pub fn front(&self) -> Option<(usize, &T)> {
+        self.items.front().map(|(index, value)| (*index, value))
+    }
+}
+
+// == Helpers ==
+
+// for every value, the index of the value that popped it off `stack`
+fn next_popping<'a, T: Ord>(
+    values: &'a [T],
+    mut stack: MonotonicStack<&'a T>,
+) -> Vec<Option<usize>> {
+    let mut next = vec![None; values.len()];
+    for (i, value) in values.iter().enumerate() {
+        for (popped, _) in stack.push(value) {
+            next[popped] = Some(i);
+        }
+    }
+    next
+}
+
+// for every value, the index of the value right below it on `stack` once pushed
+fn previous_kept<'a, T: Ord>(
+    values: &'a [T],
+    mut stack: MonotonicStack<&'a T>,
+) -> Vec<Option<usize>> {
+    values
+        .iter()
+        .map(|value| {
+            stack.push(value);
+            stack.previous().map(|(index, _)| index)
+        })
+        .collect()
+}
+
+/// For every value, the index of the first later value that is strictly greater
+pub fn next_greater<T: Ord>(values: &[T]) -> Vec<Option<usize>> {
+    next_popping(values, MonotonicStack::non_increasing())
+}
+
+/// For every value, the index of the first later value that is strictly smaller
+pub fn next_smaller<T: Ord>(values: &[T]) -> Vec<Option<usize>> {
+    next_popping(values, MonotonicStack::non_decreasing())
+}
+
+/// For every value, the index of the last earlier value that is strictly greater
+pub fn previous_greater<T: Ord>(values: &[T]) -> Vec<Option<usize>> {
+    previous_kept(values, MonotonicStack::decreasing())
+}
+
+/// For every value, the index of the last earlier value that is strictly smaller
+pub fn previous_smaller<T: Ord>(values: &[T]) -> Vec<Option<usize>> {
+    previous_kept(values, MonotonicStack::increasing())
+}
+
+fn sliding_window<T: Ord + Clone>(values: &[T], k: usize, mut deque: MonotonicDeque<T>) -> Vec<T> {
+    assert!(k > 0, "empty window");
+    let mut extremes = vec![];
+    for (i, value) in values.iter().enumerate() {
+        deque.push_back(value.clone());
+        if i + 1 >= k {
+            deque.expire_before(i + 1 - k);
+            extremes.extend(deque.front().map(|(_, extreme)| extreme.clone()));
+        }
+    }
+    extremes
+}
+
+/// Minimum of every window of `k` consecutive values (values.len() - k + 1 of them, none if the
+/// slice is shorter than k). Panics if k is 0
+pub fn sliding_window_min<T: Ord + Clone>(values: &[T], k: usize) -> Vec<T> {
+    sliding_window(values, k, MonotonicDeque::min())
+}
+
+/// Maximum of every window of `k` consecutive values, see `sliding_window_min`
+pub fn sliding_window_max<T: Ord + Clone>(values: &[T], k: usize) -> Vec<T> {
+    sliding_window(values, k, MonotonicDeque::max())
+}
+
+/// Largest rectangle under a histogram of unit width bars: every bar, as the lowest one, extends
+/// up to (not including) the nearest lower bars on both sides
+pub fn largest_rectangle(heights: &[u64]) -> u64 {
+    let previous = previous_smaller(heights);
+    let next = next_smaller(heights);
+    heights
+        .iter()
+        .enumerate()
+        .map(|(i, &height)| {
+            let left = previous[i].map_or(0, |p| p + 1);
+            let right = next[i].unwrap_or(heights.len());
+            height * (right - left) as u64
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsa::stress::{check, Config, Gen};
+
+    fn values(g: &mut Gen) -> Vec<u64> {
+        g.vec(0..=30, |g| g.int(0..=7))
+    }
+
+    fn naive_scan(
+        values: &[u64],
+        keep: impl Fn(u64, u64) -> bool,
+        later: bool,
+    ) -> Vec<Option<usize>> {
+        (0..values.len())
+            .map(|i| {
+                let keep = |&j: &usize| keep(values[j], values[i]);
+                if later {
+                    (i + 1..values.len()).find(keep)
+                } else {
+                    (0..i).rev().find(keep)
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn nearest_greater_and_smaller_match_naive_scans() {
+        check(
+            "next/previous greater/smaller",
+            Config::default(),
+            values,
+            |values| {
+                [
+                    naive_scan(values, |w, v| w > v, true),
+                    naive_scan(values, |w, v| w < v, true),
+                    naive_scan(values, |w, v| w > v, false),
+                    naive_scan(values, |w, v| w < v, false),
+                ]
+            },
+            |values| {
+                [
+                    next_greater(values),
+                    next_smaller(values),
+                    previous_greater(values),
+                    previous_smaller(values),
+                ]
+            },
+        );
+    }
+
+    #[test]
+    fn sliding_windows_match_naive_scans() {
+        // (values, k - 1), so shrinking never reaches the invalid k = 0
+        check(
+            "sliding_window_min/max",
+            Config::default(),
+            |g| (values(g), g.int(0..=4usize)),
+            |(values, extra)| {
+                let windows = values.windows(extra + 1);
+                let mins = windows.clone().map(|w| *w.iter().min().unwrap());
+                let maxs = windows.map(|w| *w.iter().max().unwrap());
+                (mins.collect::<Vec<_>>(), maxs.collect::<Vec<_>>())
+            },
+            |(values, extra)| {
+                (
+                    sliding_window_min(values, extra + 1),
+                    sliding_window_max(values, extra + 1),
+                )
+            },
+        );
+    }
+
+    #[test]
+    fn largest_rectangle_matches_every_range() {
+        check(
+            "largest_rectangle",
+            Config::default(),
+            values,
+            |values| {
+                let len = values.len();
+                (0..len)
+                    .flat_map(|i| (i + 1..=len).map(move |j| (i, j)))
+                    .map(|(i, j)| values[i..j].iter().min().unwrap() * (j - i) as u64)
+                    .max()
+                    .unwrap_or(0)
+            },
+            |values| largest_rectangle(values),
+        );
+    }
+
+    #[test]
+    fn stack_pops_and_remembers_indices() {
+        let mut stack = MonotonicStack::increasing();
+        assert!(stack.push(3).is_empty());
+        assert!(stack.push(5).is_empty());
+        assert_eq!(stack.previous(), Some((0, &3)));
+        // 4 pops 5, equal values pop each other in a strictly increasing stack
+        assert_eq!(stack.push(4), [(1, 5)]);
+        assert_eq!(stack.push(4), [(2, 4)]);
+        assert_eq!(stack.iter().collect::<Vec<_>>(), [(0, &3), (3, &4)]);
+        assert_eq!(largest_rectangle(&[2, 1, 5, 6, 2, 3]), 10);
+    }
+}
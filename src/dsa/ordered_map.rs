@@ -0,0 +1,484 @@
+// == Ordered map with rank and select ==
+/* A sorted map like BTreeMap that also answers "how many keys are smaller than k?" (rank) and
+ * "which key is the i-th smallest?" (select) in O(log n), by keeping the size of every subtree.
+ *
+ * It's a treap, like the interval tree: a binary search tree on the keys that is also a heap on
+ * random priorities, balanced in expectation. Insertions and removals are a split by key and a
+ * couple of merges, and the same split/merge is exposed as `split_off`/`append` (both O(log n),
+ * where BTreeMap's append is linear).
+ *
+ * Ranks are 0-based, like indices: `select(rank(k))` is k when k is in the map, and the k-th
+ * smallest key (1-based, as in the kth_smallest problems) is `select(k - 1)`.
+ *
+ *     let mut scores = OrderedMap::new();
+ *     scores.insert(70, "carol");
+ *     scores.insert(90, "alice");
+ *     scores.insert(80, "bob");
+ *     scores.rank(&80);   // 1, one score is lower
+ *     scores.select(2);   // Some((&90, &"alice"))
+ */
+
+use std::{
+    cmp::Ordering,
+    ops::{Bound, RangeBounds},
+};
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+#[derive(Debug, Clone)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    priority: u64,
+    /// number of nodes in this subtree
+    size: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OrderedMap<K, V> {
+    root: Link<K, V>,
+    /// xorshift state for the priorities, deterministic so runs are reproducible
+    seed: u64,
+}
+
+fn size<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+impl<K, V> Node<K, V> {
+    fn update(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+}
+
+/// (nodes going left, the others), `goes_left` must be true for a prefix of the in-order keys
+fn split<K, V>(link: Link<K, V>, goes_left: &impl Fn(&K) -> bool) -> (Link<K, V>, Link<K, V>) {
+    let Some(mut node) = link else {
+        return (None, None);
+    };
+    if goes_left(&node.key) {
+        let (middle, right) = split(node.right.take(), goes_left);
+        node.right = middle;
+        node.update();
+        (Some(node), right)
+    } else {
+        let (left, middle) = split(node.left.take(), goes_left);
+        node.left = middle;
+        node.update();
+        (left, Some(node))
+    }
+}
+
+/// Every key of `left` must be smaller than every key of `right`
+fn merge<K, V>(left: Link<K, V>, right: Link<K, V>) -> Link<K, V> {
+    match (left, right) {
+        (None, link) | (link, None) => link,
+        (Some(mut l), Some(mut r)) => {
+            if l.priority > r.priority {
+                l.right = merge(l.right.take(), Some(r));
+                l.update();
+                Some(l)
+            } else {
+                r.left = merge(Some(l), r.left.take());
+                r.update();
+                Some(r)
+            }
+        }
+    }
+}
+
+// whether `key` is past the start of `bounds`
+fn after_start<K: Ord>(bounds: &impl RangeBounds<K>, key: &K) -> bool {
+    match bounds.start_bound() {
+        Bound::Included(start) => key >= start,
+        Bound::Excluded(start) => key > start,
+        Bound::Unbounded => true,
+    }
+}
+
+// whether `key` is before the end of `bounds`
+fn before_end<K: Ord>(bounds: &impl RangeBounds<K>, key: &K) -> bool {
+    match bounds.end_bound() {
+        Bound::Included(end) => key <= end,
+        Bound::Excluded(end) => key < end,
+        Bound::Unbounded => true,
+    }
+}
+
+impl<K: Ord, V> OrderedMap<K, V> {
+    pub fn new() -> Self {
+        OrderedMap {
+            root: None,
+            seed: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    fn next_priority(&mut self) -> u64 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        self.seed
+    }
+
+    fn find(&self, key: &K) -> Option<&Node<K, V>> {
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            current = match key.cmp(&node.key) {
+                Ordering::Less => node.left.as_deref(),
+                Ordering::Greater => node.right.as_deref(),
+                Ordering::Equal => return Some(node),
+            };
+        }
+        None
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.find(key).map(|node| &node.value)
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut current = self.root.as_deref_mut();
+        while let Some(node) = current {
+            current = match key.cmp(&node.key) {
+                Ordering::Less => node.left.as_deref_mut(),
+                Ordering::Greater => node.right.as_deref_mut(),
+                Ordering::Equal => return Some(&mut node.value),
+            };
+        }
+        None
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// Returns the old value if `key` was already there (the key itself isn't replaced)
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(old) = self.get_mut(&key) {
+            return Some(std::mem::replace(old, value));
+        }
+        let node = Box::new(Node {
+            priority: self.next_priority(),
+            size: 1,
+            left: None,
+            right: None,
+            key,
+            value,
+        });
+        let (left, right) = split(self.root.take(), &|k| *k < node.key);
+        self.root = merge(merge(left, Some(node)), right);
+        None
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (left, rest) = split(self.root.take(), &|k| k < key);
+        // at most one node has the key, it's the whole of `equal`
+        let (equal, right) = split(rest, &|k| k == key);
+        self.root = merge(left, right);
+        equal.map(|node| node.value)
+    }
+
+    /// Number of keys smaller than `key` (whether `key` is in the map or not)
+    pub fn rank(&self, key: &K) -> usize {
+        let mut rank = 0;
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            current = match key.cmp(&node.key) {
+                Ordering::Less => node.left.as_deref(),
+                Ordering::Greater => {
+                    rank += size(&node.left) + 1;
+                    node.right.as_deref()
+                }
+                Ordering::Equal => return rank + size(&node.left),
+            };
+        }
+        rank
+    }
+
+    /// The entry with `index` smaller keys (0-based), None when index >= len
+    pub fn select(&self, mut index: usize) -> Option<(&K, &V)> {
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            let left = size(&node.left);
+            current = match index.cmp(&left) {
+                Ordering::Less => node.left.as_deref(),
+                Ordering::Equal => return Some((&node.key, &node.value)),
+                Ordering::Greater => {
+                    index -= left + 1;
+                    node.right.as_deref()
+                }
+            };
+        }
+        None
+    }
+
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.select(0)
+    }
+
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.select(self.len().checked_sub(1)?)
+    }
+
+    /// Moves the entries with keys >= `key` into a new map, like BTreeMap::split_off
+    pub fn split_off(&mut self, key: &K) -> Self {
+        let (left, right) = split(self.root.take(), &|k| k < key);
+        self.root = left;
+        OrderedMap {
+            root: right,
+            seed: self.next_priority(),
+        }
+    }
+
+    /// Moves every entry of `other` into this map. All of its keys must be greater than ours
+    /// (panics otherwise), which is what makes it O(log n): the two treaps are just merged
+    pub fn append(&mut self, mut other: Self) {
+        if let (Some((last, _)), Some((first, _))) = (self.last(), other.first()) {
+            assert!(last < first, "appended keys must all be greater");
+        }
+        self.root = merge(self.root.take(), other.root.take());
+    }
+
+    /// Entries with a key in `bounds`, in order. The walk down to the first one is O(log n), then
+    /// every step is O(1) amortized
+    pub fn range<R: RangeBounds<K>>(&self, bounds: R) -> impl Iterator<Item = (&K, &V)> {
+        // the stack holds the nodes (past the start) whose left subtree has been handled
+        // but not themselves and their right subtree
+        let mut stack = vec![];
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            if after_start(&bounds, &node.key) {
+                stack.push(node);
+                current = node.left.as_deref();
+            } else {
+                current = node.right.as_deref();
+            }
+        }
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            if !before_end(&bounds, &node.key) {
+                stack.clear();
+                return None;
+            }
+            let mut current = node.right.as_deref();
+            while let Some(next) = current {
+                stack.push(next);
+                current = next.left.as_deref();
+            }
+            Some((&node.key, &node.value))
+        })
+    }
+
+    /// Every entry, in key order
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.range(..)
+    }
+}
+
+impl<K: Ord, V> Default for OrderedMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for OrderedMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(entries: I) -> Self {
+        let mut map = OrderedMap::new();
+        for (key, value) in entries {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsa::problems::kth_smallest_element_in_a_sorted_matrix::Solution;
+    use crate::dsa::stress::{check, Config, Gen};
+    use std::collections::BTreeMap;
+
+    fn entries<'a>(found: impl Iterator<Item = (&'a i32, &'a i32)>) -> Vec<(i32, i32)> {
+        found.map(|(k, v)| (*k, *v)).collect()
+    }
+
+    // what each operation returned, compared between the map and the model
+    #[derive(Debug, PartialEq)]
+    enum Seen {
+        Value(Option<i32>),
+        Rank(usize),
+        Entries(Vec<(i32, i32)>),
+        Len(usize),
+    }
+
+    // (operation, key, extra): extra is the select index or the other end of a range
+    type Op = (usize, i32, usize);
+
+    fn operations(g: &mut Gen) -> Vec<Op> {
+        g.vec(0..=300, |g| (g.int(0..=5), g.int(0..=49), g.int(0..=49)))
+    }
+
+    // model: a BTreeMap, with rank/select done by counting through it
+    fn run_model(ops: &[Op]) -> Vec<Seen> {
+        let mut model = BTreeMap::new();
+        let mut seen = vec![];
+        for (id, &(op, key, extra)) in ops.iter().enumerate() {
+            let id = id as i32;
+            match op {
+                0 | 1 => seen.push(Seen::Value(model.insert(key, id))),
+                2 => seen.push(Seen::Value(model.remove(&key))),
+                3 => {
+                    seen.push(Seen::Value(model.get(&key).copied()));
+                    seen.push(Seen::Rank(model.range(..key).count()));
+                    let index = extra % (model.len() + 1);
+                    seen.push(Seen::Entries(entries(model.iter().nth(index).into_iter())));
+                }
+                4 => {
+                    let (start, end) = (key.min(extra as i32), key.max(extra as i32));
+                    seen.push(Seen::Entries(entries(model.range(start..end))));
+                    let bounds = (Bound::Excluded(start), Bound::Included(end));
+                    seen.push(Seen::Entries(entries(model.range(bounds))));
+                    seen.push(Seen::Entries(entries(model.range(start..))));
+                }
+                _ => {
+                    let mut right = model.split_off(&key);
+                    seen.push(Seen::Entries(entries(model.iter())));
+                    seen.push(Seen::Entries(entries(right.iter())));
+                    if let Some(value) = right.get_mut(&key) {
+                        *value += 1;
+                    }
+                    model.append(&mut right);
+                }
+            }
+            seen.push(Seen::Len(model.len()));
+        }
+        seen.push(Seen::Entries(entries(model.iter())));
+        seen.push(Seen::Entries(entries(model.first_key_value().into_iter())));
+        seen.push(Seen::Entries(entries(model.last_key_value().into_iter())));
+        seen
+    }
+
+    fn run_map(ops: &[Op]) -> Vec<Seen> {
+        let mut map = OrderedMap::new();
+        let mut seen = vec![];
+        for (id, &(op, key, extra)) in ops.iter().enumerate() {
+            let id = id as i32;
+            match op {
+                0 | 1 => seen.push(Seen::Value(map.insert(key, id))),
+                2 => seen.push(Seen::Value(map.remove(&key))),
+                3 => {
+                    seen.push(Seen::Value(map.get(&key).copied()));
+                    seen.push(Seen::Rank(map.rank(&key)));
+                    let index = extra % (map.len() + 1);
+                    seen.push(Seen::Entries(entries(map.select(index).into_iter())));
+                }
+                4 => {
+                    let (start, end) = (key.min(extra as i32), key.max(extra as i32));
+                    seen.push(Seen::Entries(entries(map.range(start..end))));
+                    let bounds = (Bound::Excluded(start), Bound::Included(end));
+                    seen.push(Seen::Entries(entries(map.range(bounds))));
+                    seen.push(Seen::Entries(entries(map.range(start..))));
+                }
+                _ => {
+                    let mut right = map.split_off(&key);
+                    seen.push(Seen::Entries(entries(map.iter())));
+                    seen.push(Seen::Entries(entries(right.iter())));
+                    if let Some(value) = right.get_mut(&key) {
+                        *value += 1;
+                    }
+                    map.append(right);
+                }
+            }
+            seen.push(Seen::Len(map.len()));
+        }
+        seen.push(Seen::Entries(entries(map.iter())));
+        seen.push(Seen::Entries(entries(map.first().into_iter())));
+        seen.push(Seen::Entries(entries(map.last().into_iter())));
+        seen
+    }
+
+    #[test]
+    fn matches_btree_map_model() {
+        check(
+            "OrderedMap",
+            Config::default(),
+            operations,
+            |ops| run_model(ops),
+            |ops| run_map(ops),
+        );
+    }
+
+    // the kth_smallest problem on a 4x4 matrix that changes, one (row, col, value, k) per step:
+    // values are keyed by (value, row, col) so duplicates stay apart
+    #[test]
+    fn kth_smallest_on_dynamic_matrix() {
+        check(
+            "kth smallest of a changing matrix",
+            Config::default(),
+            |g| {
+                g.vec(0..=40, |g| {
+                    (
+                        (g.int(0..=3), g.int(0..=3)),
+                        (g.int(-20..=19), g.int(0..=15)),
+                    )
+                })
+            },
+            |steps| {
+                let mut matrix = vec![vec![0; 4]; 4];
+                steps
+                    .iter()
+                    .map(|&((r, c), (value, k))| {
+                        matrix[r % 4][c % 4] = value;
+                        // the problem wants sorted rows, sort copies of them
+                        let sorted = matrix.iter().map(|row| {
+                            let mut row = row.clone();
+                            row.sort();
+                            row
+                        });
+                        Solution::kth_smallest(sorted.collect(), (k % 16 + 1) as i32)
+                    })
+                    .collect::<Vec<_>>()
+            },
+            |steps| {
+                let mut matrix = [[0; 4]; 4];
+                let mut values = OrderedMap::new();
+                for (r, row) in matrix.iter().enumerate() {
+                    for (c, &value) in row.iter().enumerate() {
+                        values.insert((value, r, c), ());
+                    }
+                }
+                steps
+                    .iter()
+                    .map(|&((r, c), (value, k))| {
+                        let (r, c) = (r % 4, c % 4);
+                        values.remove(&(matrix[r][c], r, c));
+                        matrix[r][c] = value;
+                        values.insert((value, r, c), ());
+                        let ((kth, _, _), _) = values.select(k % 16).unwrap();
+                        *kth
+                    })
+                    .collect::<Vec<_>>()
+            },
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "appended keys must all be greater")]
+    fn append_rejects_overlapping_keys() {
+        let mut low = [(1, 'a'), (5, 'e')]
+            .into_iter()
+            .collect::<OrderedMap<_, _>>();
+        let high = [(3, 'c')].into_iter().collect();
+        low.append(high);
+    }
+}
@@ -0,0 +1,212 @@
+// == Persistent list ==
+/* The `List::Cons(i32, Rc<List>)` of docs::smart_pointers, generic and with the operations a
+ * functional language gives its lists. Nothing is ever modified: `cons` makes a new node in
+ * front of the old list, which it shares, so both stay valid and clone is O(1) (one Rc):
+ *
+ *     let a = PList::new().cons(4);
+ *     let b = a.cons(3);     // 3 -> 4 -> Nil
+ *     let c = a.cons(1);     // 1 -> 4 -> Nil, the 4 is the same node as in b
+ *
+ * Operations at the front (cons, head, tail) are O(1), anything else walks the list.
+ *
+ * NOTE: the derived Drop of a linked list is recursive (dropping a node drops its `next`, which
+ * drops its `next`, ...), so a long enough list overflows the stack when it goes away. Drop here
+ * is a loop instead, and it stops at the first node some other list still shares.
+ */
+
+use std::{fmt, rc::Rc};
+
+type Link<T> = Option<Rc<Node<T>>>;
+
+struct Node<T> {
+    value: T,
+    next: Link<T>,
+}
+
+pub struct PList<T> {
+    head: Link<T>,
+    len: usize,
+}
+
+impl<T> PList<T> {
+    /// The empty list, Nil
+    pub fn new() -> Self {
+        PList { head: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    /// `value` in front of this list, which is shared and not changed
+    pub fn cons(&self, value: T) -> Self {
+        PList {
+            head: Some(Rc::new(Node {
+                value,
+                next: self.head.clone(),
+            })),
+            len: self.len + 1,
+        }
+    }
+
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.value)
+    }
+
+    /// Everything but the head (shared), None for the empty list
+    pub fn tail(&self) -> Option<Self> {
+        let node = self.head.as_ref()?;
+        Some(PList {
+            head: node.next.clone(),
+            len: self.len - 1,
+        })
+    }
+
+    /// Whether both lists are the very same nodes, not just equal values
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        std::iter::successors(self.head.as_deref(), |node| node.next.as_deref())
+            .map(|node| &node.value)
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> PList<U> {
+        let mapped = self.iter().map(f).collect::<Vec<_>>();
+        mapped
+            .into_iter()
+            .rev()
+            .fold(PList::new(), |list, value| list.cons(value))
+    }
+}
+
+impl<T: Clone> PList<T> {
+    /// Same values, last first (a new list, nothing can be shared)
+    pub fn reverse(&self) -> Self {
+        self.iter()
+            .fold(PList::new(), |list, value| list.cons(value.clone()))
+    }
+}
+
+impl<T> Drop for PList<T> {
+    fn drop(&mut self) {
+        let mut link = self.head.take();
+        // nodes nobody else points to are unlinked one by one, so dropping each is shallow
+        while let Some(node) = link {
+            match Rc::try_unwrap(node) {
+                Ok(mut node) => link = node.next.take(),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+impl<T> Clone for PList<T> {
+    /// O(1), the clone shares every node
+    fn clone(&self) -> Self {
+        PList {
+            head: self.head.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Default for PList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<T> for PList<T> {
+    /// In iteration order: the first value is the head
+    fn from_iter<I: IntoIterator<Item = T>>(values: I) -> Self {
+        let values = values.into_iter().collect::<Vec<_>>();
+        values
+            .into_iter()
+            .rev()
+            .fold(PList::new(), |list, value| list.cons(value))
+    }
+}
+
+impl<T: PartialEq> PartialEq for PList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for PList<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for PList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for PList<T> {
+    /// Like the smart_pointers List: `3 -> 4 -> Nil`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for value in self.iter() {
+            write!(f, "{value} -> ")?;
+        }
+        write!(f, "Nil")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shares_tails() {
+        let a = PList::new().cons(4);
+        let b = a.cons(3);
+        let c = a.cons(1);
+        assert_eq!(b.to_string(), "3 -> 4 -> Nil");
+        assert_eq!(c.to_string(), "1 -> 4 -> Nil");
+        assert!(b.tail().unwrap().ptr_eq(&a) && c.tail().unwrap().ptr_eq(&a));
+        assert_eq!(b.head(), Some(&3));
+        assert_eq!(PList::<i32>::new().tail(), None);
+
+        let list = (1..=5).collect::<PList<_>>();
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+        assert_eq!(list.reverse(), (1..=5).rev().collect());
+        assert_eq!(
+            list.map(|x| x * 10).to_string(),
+            "10 -> 20 -> 30 -> 40 -> 50 -> Nil"
+        );
+        assert_eq!((list.len(), list.reverse().len()), (5, 5));
+        assert_eq!(format!("{list:?}"), "[1, 2, 3, 4, 5]");
+    }
+
+    #[test]
+    fn drops_long_lists_without_recursing() {
+        // recursive drops would need a stack frame per node, far more than a test thread has
+        let long = (0..1_000_000).collect::<PList<u32>>();
+        let shared = long.tail().unwrap().tail().unwrap();
+        drop(long);
+        // the nodes still shared survived the first drop
+        assert_eq!(shared.head(), Some(&2));
+        assert_eq!(shared.len(), 999_998);
+    }
+
+    #[test]
+    fn drop_frees_every_value() {
+        let counter = Rc::new(());
+        let list = (0..100).map(|_| Rc::clone(&counter)).collect::<PList<_>>();
+        let other = list.tail().unwrap().cons(Rc::clone(&counter));
+        assert_eq!(Rc::strong_count(&counter), 102);
+        drop(list);
+        assert_eq!(Rc::strong_count(&counter), 101);
+        drop(other);
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+}
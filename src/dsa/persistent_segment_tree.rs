@@ -0,0 +1,315 @@
+// == Persistent segment tree ==
+/* A segment tree where updates never modify a node: setting index i copies only the O(log n)
+ * nodes on the path from the root to leaf i, and the new path points to the untouched subtrees
+ * of the old version. Old roots stay valid, so every version is still queryable.
+ *
+ * The subtrees are shared through Rc, just like the tails of `Rc<List>` in
+ * docs::smart_pointers (b and c both point to a, nobody owns it alone):
+ *
+ *     v0:  root0 ─┬─ A ─┬─ a0          v1 = set(v0, 3, x):  root1 ─┬─ A   (shared)
+ *                 │     └─ a1                                      └─ B' ─┬─ b2    (shared)
+ *                 └─ B ─┬─ b2                                             └─ x
+ *                       └─ b3
+ *
+ * Versions are handles (indices into `roots`), every update takes the version to start from,
+ * so histories can branch.
+ *
+ * SubarrayKth builds on it for "k-th smallest in values[l..r]": version i counts how many times
+ * each (compressed) value appears in values[..i], and the counts of values[l..r] are the
+ * difference between versions r and l, walked down both trees at once.
+ */
+
+use std::{marker::PhantomData, ops::RangeBounds, rc::Rc};
+
+use super::{
+    monoid::{Monoid, Sum},
+    segment_tree::to_half_open,
+};
+
+type Link<T> = Rc<Node<T>>;
+
+struct Node<T> {
+    value: T,
+    /// None for leaves
+    children: Option<(Link<T>, Link<T>)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Version(usize);
+
+pub struct PersistentSegmentTree<T, Op> {
+    len: usize,
+    roots: Vec<Rc<Node<T>>>,
+    op: PhantomData<Op>,
+}
+
+fn build<T: Clone, Op: Monoid<T>>(values: &[T]) -> Rc<Node<T>> {
+    if values.len() <= 1 {
+        return Rc::new(Node {
+            value: values.first().cloned().unwrap_or_else(Op::identity),
+            children: None,
+        });
+    }
+    let (left, right) = values.split_at(values.len() / 2);
+    join::<T, Op>(build::<T, Op>(left), build::<T, Op>(right))
+}
+
+fn join<T, Op: Monoid<T>>(left: Rc<Node<T>>, right: Rc<Node<T>>) -> Rc<Node<T>> {
+    Rc::new(Node {
+        value: Op::combine(&left.value, &right.value),
+        children: Some((left, right)),
+    })
+}
+
+impl<T: Clone, Op: Monoid<T>> PersistentSegmentTree<T, Op> {
+    /// A tree with a single version (`Version` 0) holding `values`
+    pub fn from_vec(values: Vec<T>) -> Self {
+        PersistentSegmentTree {
+            len: values.len(),
+            roots: vec![build::<T, Op>(&values)],
+            op: PhantomData,
+        }
+    }
+
+    /// `len` identity values
+    pub fn new(len: usize) -> Self {
+        Self::from_vec(vec![Op::identity(); len])
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn initial(&self) -> Version {
+        Version(0)
+    }
+
+    /// The most recently created version
+    pub fn latest(&self) -> Version {
+        Version(self.roots.len() - 1)
+    }
+
+    pub fn version_count(&self) -> usize {
+        self.roots.len()
+    }
+
+    fn root(&self, version: Version) -> &Rc<Node<T>> {
+        self.roots.get(version.0).expect("version of another tree")
+    }
+
+    /// A new version equal to `version` except for values[index] = value
+    pub fn set(&mut self, version: Version, index: usize, value: T) -> Version {
+        fn set<T, Op: Monoid<T>>(
+            node: &Rc<Node<T>>,
+            (lo, hi): (usize, usize),
+            index: usize,
+            value: T,
+        ) -> Rc<Node<T>> {
+            match &node.children {
+                None => Rc::new(Node {
+                    value,
+                    children: None,
+                }),
+                Some((left, right)) => {
+                    let mid = lo + (hi - lo) / 2;
+                    if index < mid {
+                        join::<T, Op>(
+                            set::<T, Op>(left, (lo, mid), index, value),
+                            Rc::clone(right),
+                        )
+                    } else {
+                        join::<T, Op>(
+                            Rc::clone(left),
+                            set::<T, Op>(right, (mid, hi), index, value),
+                        )
+                    }
+                }
+            }
+        }
+
+        assert!(
+            index < self.len,
+            "index {index} out of bounds for length {}",
+            self.len
+        );
+        let root = set::<T, Op>(self.root(version), (0, self.len), index, value);
+        self.roots.push(root);
+        self.latest()
+    }
+
+    /// A new version with values[index] = f(values[index])
+    pub fn update(&mut self, version: Version, index: usize, f: impl FnOnce(&T) -> T) -> Version {
+        let value = f(&self.get(version, index));
+        self.set(version, index, value)
+    }
+
+    pub fn get(&self, version: Version, index: usize) -> T {
+        assert!(
+            index < self.len,
+            "index {index} out of bounds for length {}",
+            self.len
+        );
+        self.query(version, index..=index)
+    }
+
+    /// Combination of the values in `range` as they were in `version`
+    pub fn query(&self, version: Version, range: impl RangeBounds<usize>) -> T {
+        fn query<T: Clone, Op: Monoid<T>>(
+            node: &Node<T>,
+            (lo, hi): (usize, usize),
+            (l, r): (usize, usize),
+        ) -> T {
+            if r <= lo || hi <= l {
+                return Op::identity();
+            }
+            match &node.children {
+                Some((left, right)) if !(l <= lo && hi <= r) => {
+                    let mid = lo + (hi - lo) / 2;
+                    Op::combine(
+                        &query::<T, Op>(left, (lo, mid), (l, r)),
+                        &query::<T, Op>(right, (mid, hi), (l, r)),
+                    )
+                }
+                _ => node.value.clone(),
+            }
+        }
+
+        let (start, end) = to_half_open(range, self.len);
+        query::<T, Op>(self.root(version), (0, self.len), (start, end))
+    }
+}
+
+/// k-th smallest value of any subarray in O(log n), after an O(n log n) build
+pub struct SubarrayKth<T> {
+    /// distinct values, sorted, counts are indexed by position in here
+    sorted: Vec<T>,
+    counts: PersistentSegmentTree<usize, Sum>,
+    /// prefixes[i] is the version counting values[..i]
+    prefixes: Vec<Version>,
+}
+
+impl<T: Ord + Clone> SubarrayKth<T> {
+    pub fn new(values: &[T]) -> Self {
+        let mut sorted = values.to_vec();
+        sorted.sort();
+        sorted.dedup();
+
+        let mut counts = PersistentSegmentTree::<usize, Sum>::new(sorted.len());
+        let mut prefixes = vec![counts.initial()];
+        for value in values {
+            let rank = sorted.binary_search(value).unwrap();
+            let previous = *prefixes.last().unwrap();
+            prefixes.push(counts.update(previous, rank, |count| count + 1));
+        }
+        SubarrayKth {
+            sorted,
+            counts,
+            prefixes,
+        }
+    }
+
+    /// k-th smallest (1-based k) of values[range], None if k is 0 or past the end of the range
+    pub fn kth_smallest(&self, range: impl RangeBounds<usize>, k: usize) -> Option<&T> {
+        let (start, end) = to_half_open(range, self.prefixes.len() - 1);
+        if k == 0 || k > end - start {
+            return None;
+        }
+        let mut newer = self.counts.root(self.prefixes[end]);
+        let mut older = self.counts.root(self.prefixes[start]);
+        let (mut lo, mut hi, mut k) = (0, self.sorted.len(), k);
+
+        // count(newer) - count(older) is the count of each value inside the range
+        while let (Some((newer_left, newer_right)), Some((older_left, older_right))) =
+            (&newer.children, &older.children)
+        {
+            let mid = lo + (hi - lo) / 2;
+            let on_the_left = newer_left.value - older_left.value;
+            if k <= on_the_left {
+                (newer, older, hi) = (newer_left, older_left, mid);
+            } else {
+                k -= on_the_left;
+                (newer, older, lo) = (newer_right, older_right, mid);
+            }
+        }
+        self.sorted.get(lo)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn old_versions_stay_queryable() {
+        let mut rng = StdRng::seed_from_u64(34);
+        let len = 37;
+        let mut snapshots = vec![(0..len)
+            .map(|_| rng.gen_range(-100..100))
+            .collect::<Vec<i64>>()];
+        let mut tree = PersistentSegmentTree::<i64, Sum>::from_vec(snapshots[0].clone());
+
+        for _ in 0..200 {
+            // branch from a random old version, not just the latest one
+            let base = rng.gen_range(0..tree.version_count());
+            let (i, v) = (rng.gen_range(0..len), rng.gen_range(-100..100));
+            let version = tree.set(Version(base), i, v);
+            let mut snapshot = snapshots[base].clone();
+            snapshot[i] = v;
+            snapshots.push(snapshot);
+            assert_eq!(version.0, snapshots.len() - 1);
+        }
+
+        for (version, snapshot) in snapshots.iter().enumerate() {
+            let l = rng.gen_range(0..=len);
+            let r = rng.gen_range(l..=len);
+            let version = Version(version);
+            assert_eq!(tree.query(version, ..), snapshot.iter().sum::<i64>());
+            assert_eq!(
+                tree.query(version, l..r),
+                snapshot[l..r].iter().sum::<i64>()
+            );
+            assert_eq!(tree.get(version, l.min(len - 1)), snapshot[l.min(len - 1)]);
+        }
+    }
+
+    #[test]
+    fn updates_share_the_untouched_subtrees() {
+        let mut tree = PersistentSegmentTree::<i32, Sum>::from_vec(vec![1; 8]);
+        let v1 = tree.set(tree.initial(), 7, 10);
+        let (Some((old_left, _)), Some((new_left, _))) =
+            (&tree.root(tree.initial()).children, &tree.root(v1).children)
+        else {
+            panic!("8 values have an internal root");
+        };
+        assert!(Rc::ptr_eq(old_left, new_left));
+        assert_eq!(
+            (tree.query(tree.initial(), ..), tree.query(v1, ..)),
+            (8, 17)
+        );
+    }
+
+    #[test]
+    fn kth_smallest_in_subarrays() {
+        let mut rng = StdRng::seed_from_u64(35);
+        let values = (0..60)
+            .map(|_| rng.gen_range(-20..20))
+            .collect::<Vec<i32>>();
+        let kth = SubarrayKth::new(&values);
+        for l in 0..=values.len() {
+            for r in l..=values.len() {
+                let mut sorted = values[l..r].to_vec();
+                sorted.sort();
+                for k in 1..=sorted.len() {
+                    assert_eq!(kth.kth_smallest(l..r, k), Some(&sorted[k - 1]));
+                }
+                assert_eq!(kth.kth_smallest(l..r, 0), None);
+                assert_eq!(kth.kth_smallest(l..r, r - l + 1), None);
+            }
+        }
+    }
+}
@@ -0,0 +1,413 @@
+// == Persistent vector ==
+/* An immutable vector where push / pop / set return a new version sharing almost everything with
+ * the old one, like the persistent segment tree (and Clojure's vectors): clone is O(1), old
+ * versions stay valid, and an update copies O(log n) small nodes instead of the whole Vec.
+ *
+ * It's a bit-partitioned trie: a tree with 32 children per node where the path to index i is
+ * spelled by the bits of i, 5 at a time from the top (so ~log32 n levels, 4 for a million
+ * values). The leaves hold 32 values each.
+ *
+ *     index 1000 = 0b00000_11111_01000:  root -> child 31 -> value 8 of that leaf
+ *
+ * The last (up to 32) values are kept out of the tree in a `tail`, so a push only touches the
+ * tail until it's full, and then moves it into the tree in one go: push is O(1) amortized, and
+ * the tree only ever grows by whole leaves.
+ *
+ * T: Clone because updating a shared leaf means copying its values.
+ */
+
+use std::{fmt, rc::Rc};
+
+const BITS: u32 = 5;
+const WIDTH: usize = 1 << BITS;
+const MASK: usize = WIDTH - 1;
+
+enum Node<T> {
+    Branch(Vec<Rc<Node<T>>>),
+    /// always WIDTH values, the partial one at the end is the tail
+    Leaf(Vec<T>),
+}
+
+impl<T> Node<T> {
+    fn children(&self) -> &[Rc<Node<T>>] {
+        match self {
+            Node::Branch(children) => children,
+            Node::Leaf(_) => unreachable!("a leaf above level 0"),
+        }
+    }
+}
+
+pub struct PVector<T> {
+    len: usize,
+    /// bits of the index the root's children are picked by, BITS for a root whose children are
+    /// leaves
+    shift: u32,
+    root: Rc<Node<T>>,
+    tail: Rc<Vec<T>>,
+}
+
+// the path from `level` down to a single leaf
+fn new_path<T>(level: u32, leaf: Rc<Node<T>>) -> Rc<Node<T>> {
+    if level == 0 {
+        leaf
+    } else {
+        Rc::new(Node::Branch(vec![new_path(level - BITS, leaf)]))
+    }
+}
+
+impl<T> PVector<T> {
+    pub fn new() -> Self {
+        PVector {
+            len: 0,
+            shift: BITS,
+            root: Rc::new(Node::Branch(vec![])),
+            tail: Rc::new(vec![]),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Index of the first value in the tail, everything before it is in the tree
+    fn tail_offset(&self) -> usize {
+        if self.len < WIDTH {
+            0
+        } else {
+            (self.len - 1) >> BITS << BITS
+        }
+    }
+
+    /// The values of the leaf (or tail) holding index i
+    fn leaf(&self, i: usize) -> &[T] {
+        if i >= self.tail_offset() {
+            return &self.tail;
+        }
+        let mut node = &self.root;
+        let mut level = self.shift;
+        while level > 0 {
+            node = &node.children()[i >> level & MASK];
+            level -= BITS;
+        }
+        match &**node {
+            Node::Leaf(values) => values,
+            Node::Branch(_) => unreachable!("a branch at level 0"),
+        }
+    }
+
+    pub fn get(&self, i: usize) -> Option<&T> {
+        (i < self.len).then(|| &self.leaf(i)[i & MASK])
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.tail.last()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        // a leaf at a time, not a walk from the root per value
+        (0..self.len)
+            .step_by(WIDTH)
+            .flat_map(|start| self.leaf(start))
+    }
+}
+
+impl<T: Clone> PVector<T> {
+    /// A new version with `value` at the end
+    pub fn push(&self, value: T) -> Self {
+        if self.len - self.tail_offset() < WIDTH {
+            let mut tail = Vec::clone(&self.tail);
+            tail.push(value);
+            return PVector {
+                len: self.len + 1,
+                shift: self.shift,
+                root: Rc::clone(&self.root),
+                tail: Rc::new(tail),
+            };
+        }
+        // the tail is full, it becomes a leaf of the tree
+        let leaf = Rc::new(Node::Leaf(Vec::clone(&self.tail)));
+        let (root, shift) = if self.len >> BITS > 1 << self.shift {
+            // the tree is full too, it becomes the first child of a new root
+            let root = vec![Rc::clone(&self.root), new_path(self.shift, leaf)];
+            (Rc::new(Node::Branch(root)), self.shift + BITS)
+        } else {
+            (self.push_leaf(self.shift, &self.root, leaf), self.shift)
+        };
+        PVector {
+            len: self.len + 1,
+            shift,
+            root,
+            tail: Rc::new(vec![value]),
+        }
+    }
+
+    // copy of `node` (at `level`) with `leaf` as the new last leaf under it
+    fn push_leaf(&self, level: u32, node: &Node<T>, leaf: Rc<Node<T>>) -> Rc<Node<T>> {
+        let mut children = node.children().to_vec();
+        let i = (self.len - 1) >> level & MASK;
+        let child = if level == BITS {
+            leaf
+        } else if let Some(child) = children.get(i) {
+            self.push_leaf(level - BITS, child, leaf)
+        } else {
+            new_path(level - BITS, leaf)
+        };
+        if i < children.len() {
+            children[i] = child;
+        } else {
+            children.push(child);
+        }
+        Rc::new(Node::Branch(children))
+    }
+
+    /// A new version without the last value, None when empty
+    pub fn pop(&self) -> Option<Self> {
+        match self.len {
+            0 => return None,
+            1 => return Some(PVector::new()),
+            _ => {}
+        }
+        if self.len - self.tail_offset() > 1 {
+            let mut tail = Vec::clone(&self.tail);
+            tail.pop();
+            return Some(PVector {
+                len: self.len - 1,
+                shift: self.shift,
+                root: Rc::clone(&self.root),
+                tail: Rc::new(tail),
+            });
+        }
+        // the tail is emptied, the last leaf of the tree becomes the tail
+        let tail = self.leaf(self.len - 2).to_vec();
+        let mut root = self
+            .pop_leaf(self.shift, &self.root)
+            .unwrap_or_else(|| Rc::new(Node::Branch(vec![])));
+        let mut shift = self.shift;
+        // a root with a single child is a level too many
+        if shift > BITS && root.children().len() == 1 {
+            root = Rc::clone(&root.children()[0]);
+            shift -= BITS;
+        }
+        Some(PVector {
+            len: self.len - 1,
+            shift,
+            root,
+            tail: Rc::new(tail),
+        })
+    }
+
+    // copy of `node` (at `level`) without its last leaf, None if nothing is left under it
+    fn pop_leaf(&self, level: u32, node: &Node<T>) -> Option<Rc<Node<T>>> {
+        let i = (self.len - 2) >> level & MASK;
+        let mut children = node.children()[..=i].to_vec();
+        if level > BITS {
+            match self.pop_leaf(level - BITS, &children[i]) {
+                Some(child) => children[i] = child,
+                None => {
+                    children.pop();
+                }
+            }
+        } else {
+            children.pop();
+        }
+        (!children.is_empty()).then(|| Rc::new(Node::Branch(children)))
+    }
+
+    /// A new version with `value` at index i. Panics if i is out of bounds
+    pub fn set(&self, i: usize, value: T) -> Self {
+        assert!(
+            i < self.len,
+            "index {i} out of bounds for length {}",
+            self.len
+        );
+        let mut updated = self.clone();
+        if i >= self.tail_offset() {
+            let mut tail = Vec::clone(&self.tail);
+            tail[i & MASK] = value;
+            updated.tail = Rc::new(tail);
+        } else {
+            updated.root = Self::set_in(self.shift, &self.root, i, value);
+        }
+        updated
+    }
+
+    fn set_in(level: u32, node: &Node<T>, i: usize, value: T) -> Rc<Node<T>> {
+        Rc::new(match node {
+            Node::Leaf(values) => {
+                let mut values = values.clone();
+                values[i & MASK] = value;
+                Node::Leaf(values)
+            }
+            Node::Branch(children) => {
+                let mut children = children.clone();
+                let child = &children[i >> level & MASK];
+                children[i >> level & MASK] = Self::set_in(level - BITS, child, i, value);
+                Node::Branch(children)
+            }
+        })
+    }
+}
+
+impl<T> Clone for PVector<T> {
+    /// O(1), the clone shares everything
+    fn clone(&self) -> Self {
+        PVector {
+            len: self.len,
+            shift: self.shift,
+            root: Rc::clone(&self.root),
+            tail: Rc::clone(&self.tail),
+        }
+    }
+}
+
+impl<T> Default for PVector<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> FromIterator<T> for PVector<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(values: I) -> Self {
+        values
+            .into_iter()
+            .fold(PVector::new(), |vector, value| vector.push(value))
+    }
+}
+
+impl<T: PartialEq> PartialEq for PVector<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for PVector<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for PVector<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsa::stress::{check, Config, Gen};
+
+    // (operation, version to branch off, index): most operations (branch 0) continue from the main
+    // version and move it, the others branch off an older version
+    type Op = (usize, usize, usize);
+
+    // per operation: (the value read at the index, len, last), then the final main version
+    type Seen = (Vec<(Option<u32>, usize, Option<u32>)>, Vec<u32>);
+
+    // long enough to often go past 32 + 32² values, so the root grows a level
+    fn operations(g: &mut Gen) -> Vec<Op> {
+        g.vec(1500..=2500, |g| {
+            let branch = if g.int(0..=9) == 0 {
+                g.int(1..=3000)
+            } else {
+                0
+            };
+            (g.int(0..=9), branch, g.int(0..=3000))
+        })
+    }
+
+    // model: a Vec per version
+    fn run_model(ops: &[Op]) -> Seen {
+        let mut models: Vec<Vec<u32>> = vec![vec![]];
+        let (mut main, mut log) = (0, vec![]);
+        for (value, &(op, branch, index)) in ops.iter().enumerate() {
+            let from = if branch == 0 {
+                main
+            } else {
+                branch % models.len()
+            };
+            let mut model = models[from].clone();
+            match op {
+                0 => {
+                    model.pop();
+                }
+                1 | 2 if !model.is_empty() => {
+                    let i = index % model.len();
+                    model[i] = value as u32;
+                }
+                _ => model.push(value as u32),
+            }
+            let i = index % (model.len() + 1);
+            log.push((model.get(i).copied(), model.len(), model.last().copied()));
+            models.push(model);
+            if branch == 0 {
+                main = models.len() - 1;
+            }
+        }
+        (log, models.swap_remove(main))
+    }
+
+    fn run_vector(ops: &[Op]) -> Seen {
+        let mut versions = vec![PVector::new()];
+        let (mut main, mut log) = (0, vec![]);
+        for (value, &(op, branch, index)) in ops.iter().enumerate() {
+            let from = if branch == 0 {
+                main
+            } else {
+                branch % versions.len()
+            };
+            let vector = &versions[from];
+            let updated = match op {
+                0 => vector.pop().unwrap_or_default(),
+                1 | 2 if !vector.is_empty() => vector.set(index % vector.len(), value as u32),
+                _ => vector.push(value as u32),
+            };
+            let i = index % (updated.len() + 1);
+            log.push((
+                updated.get(i).copied(),
+                updated.len(),
+                updated.last().copied(),
+            ));
+            versions.push(updated);
+            if branch == 0 {
+                main = versions.len() - 1;
+            }
+        }
+        (log, versions[main].iter().copied().collect())
+    }
+
+    #[test]
+    fn versions_match_vec_model() {
+        check(
+            "PVector",
+            // thousands of operations per case, fewer cases than usual
+            Config {
+                cases: 64,
+                ..Config::default()
+            },
+            operations,
+            |ops| run_model(ops),
+            |ops| run_vector(ops),
+        );
+    }
+
+    #[test]
+    fn grows_and_shrinks_through_levels() {
+        // past 32 + 32² values the root gets a level, popping back down takes it off again
+        let n = WIDTH * WIDTH * 2 + 7;
+        let mut vector = (0..n).collect::<PVector<_>>();
+        assert_eq!(vector.shift, 2 * BITS);
+        assert!((0..n).all(|i| vector.get(i) == Some(&i)));
+        let before = vector.clone();
+        while vector.len() > WIDTH {
+            vector = vector.pop().unwrap();
+        }
+        assert_eq!(vector.shift, BITS);
+        assert_eq!(vector, (0..WIDTH).collect());
+        // and the first version didn't move
+        assert_eq!(before.len(), n);
+        assert_eq!(before.set(1000, 0).get(1000), Some(&0));
+        assert_eq!(before.get(1000), Some(&1000));
+        assert_eq!(PVector::<u8>::new().pop(), None);
+    }
+}
@@ -0,0 +1,54 @@
+// == Kth smallest element in a sorted matrix ==
+/* Input: k on the first line, then the matrix one row per line (rows and columns sorted).
+ * Output: the k-th smallest value (1-based, duplicates counted), -1 when there isn't one.
+ */
+
+use super::{parse_one, parse_rows, Problem};
+use crate::dsa::{
+    matrix::Matrix,
+    merge::{kth_smallest, kth_smallest_by_value},
+};
+
+pub struct KthSmallest;
+
+impl Problem for KthSmallest {
+    const NAME: &'static str = "kth_smallest_element_in_a_sorted_matrix";
+    type Input = (Vec<Vec<i32>>, i32);
+    type Output = i32;
+
+    fn parse_input(text: &str) -> anyhow::Result<(Vec<Vec<i32>>, i32)> {
+        let (k, matrix) = text.trim_start().split_once('\n').unwrap_or((text, ""));
+        Ok((parse_rows(matrix)?, parse_one(k)?))
+    }
+
+    fn parse_output(text: &str) -> anyhow::Result<i32> {
+        parse_one(text)
+    }
+}
+
+pub struct Solution;
+
+impl Solution {
+    // NOTE: used to be a BinaryHeap of (-value, (x, y)) walking right/down with a visited
+    // matrix, it's now the generic dsa::merge::KWayMerge over the (sorted) rows
+    pub fn kth_smallest(matrix: Vec<Vec<i32>>, k: i32) -> i32 {
+        kth_smallest(matrix.iter(), k as usize)
+            .copied()
+            .unwrap_or(-1)
+    }
+
+    // same answer without a heap, binary searching the value (rows and columns are sorted,
+    // but sorted rows are enough)
+    pub fn kth_smallest_by_value(matrix: Vec<Vec<i32>>, k: i32) -> i32 {
+        kth_smallest_by_value(&matrix, k as usize).unwrap_or(-1)
+    }
+
+    // same again, using that the columns are sorted too: the values <= x are counted with one
+    // staircase walk instead of a binary search per row (-1 as well for ragged rows)
+    pub fn kth_smallest_staircase(matrix: Vec<Vec<i32>>, k: i32) -> i32 {
+        Matrix::from_rows(matrix)
+            .ok()
+            .and_then(|matrix| matrix.kth_smallest(k as usize))
+            .unwrap_or(-1)
+    }
+}
@@ -0,0 +1,127 @@
+// == Container with most water ==
+/* Input: the heights, whitespace separated. Output: the largest (j - i) * min(h[i], h[j]).
+ */
+
+use std::cmp::max;
+
+use super::{parse_list, parse_one, Problem};
+use crate::dsa::search::partition_point_int;
+
+pub struct MaxArea;
+
+impl Problem for MaxArea {
+    const NAME: &'static str = "max_area";
+    type Input = Vec<i32>;
+    type Output = i32;
+
+    fn parse_input(text: &str) -> anyhow::Result<Vec<i32>> {
+        parse_list(text)
+    }
+
+    fn parse_output(text: &str) -> anyhow::Result<i32> {
+        parse_one(text)
+    }
+}
+
+pub struct Solution;
+
+impl Solution {
+    // NOTE: used to be the segment tree plus reverse trick (index h of a Min segment tree held
+    // the smallest index with height h, and the whole thing ran again on the reversed heights),
+    // O(n log max_height). Two pointers are O(n) and this is now the reference.
+    //
+    // Start with the widest container. Of the two walls, the lower one can't do better with any
+    // wall in between (narrower and still capped by itself), so it's dropped; repeat.
+    pub fn max_area(height: Vec<i32>) -> i32 {
+        let mut result = 0;
+        let (mut left, mut right) = (0, height.len().saturating_sub(1));
+        while left < right {
+            let wall = height[left].min(height[right]);
+            result = max(result, (right - left) as i32 * wall);
+            if height[left] < height[right] {
+                left += 1;
+            } else {
+                right -= 1;
+            }
+        }
+        result
+    }
+}
+
+pub struct Solution2;
+impl Solution2 {
+    fn max_area_helper(height: Vec<i32>) -> i32 {
+        let mut prefix_max: Vec<i32> = vec![];
+        height.iter().for_each(|h| {
+            if prefix_max.is_empty() {
+                prefix_max.push(*h);
+            } else {
+                prefix_max.push(max(*prefix_max.last().unwrap(), *h));
+            }
+        });
+
+        height
+            .into_iter()
+            .enumerate()
+            .skip(1)
+            .fold(0, |acc, (index, h)| {
+                // NOTE: was a hand written `while low <= high` over i32s, the farthest wall to
+                // the left at least as high as h is the first one where the prefix max reaches h
+                let low = partition_point_int(0..index, |g| prefix_max[g] < h);
+                if low != index {
+                    max(acc, (index - low) as i32 * h)
+                } else {
+                    acc
+                }
+            })
+    }
+
+    pub fn max_area(height: Vec<i32>) -> i32 {
+        max(
+            Solution2::max_area_helper(height.clone()),
+            Solution2::max_area_helper(height.clone().into_iter().rev().collect::<Vec<i32>>()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsa::stress::{check, Config, Gen};
+
+    fn brute_force(height: &[i32]) -> i32 {
+        let mut best = 0;
+        for i in 0..height.len() {
+            for j in i + 1..height.len() {
+                best = best.max((j - i) as i32 * height[i].min(height[j]));
+            }
+        }
+        best
+    }
+
+    fn heights(g: &mut Gen) -> Vec<i32> {
+        g.vec(0..=12, |g| g.int(0..=10))
+    }
+
+    #[test]
+    fn two_pointers_match_brute_force() {
+        check(
+            "Solution::max_area",
+            Config::default(),
+            heights,
+            |height| brute_force(height),
+            |height| Solution::max_area(height.clone()),
+        );
+    }
+
+    #[test]
+    fn binary_search_matches_brute_force() {
+        check(
+            "Solution2::max_area",
+            Config::default(),
+            heights,
+            |height| brute_force(height),
+            |height| Solution2::max_area(height.clone()),
+        );
+    }
+}
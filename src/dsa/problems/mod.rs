@@ -0,0 +1,284 @@
+// == Problem harness ==
+/* A problem is a way to read its input and expected output from text; a solution is a function
+ * from the parsed input to the output. Solutions are registered (several per problem are fine)
+ * and checked against case files:
+ *
+ *     cases/<problem name>/<case>.in
+ *     cases/<problem name>/<case>.out
+ *
+ * so adding a solution is writing a function, a line in `registry()` and some case files. The
+ * `problems` binary runs everything (timing each case and diffing mismatches), the
+ * `registered_solutions_pass_their_cases` test below does the same under `cargo test`.
+ *
+ * Only `solve` is timed, parsing and comparing the output aren't.
+ */
+
+pub mod kth_smallest_element_in_a_sorted_matrix;
+pub mod max_area;
+
+use std::{
+    fmt::Debug,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+
+pub trait Problem {
+    /// Also the name of its directory of cases
+    const NAME: &'static str;
+    type Input;
+    type Output: PartialEq + Debug;
+
+    fn parse_input(text: &str) -> anyhow::Result<Self::Input>;
+    fn parse_output(text: &str) -> anyhow::Result<Self::Output>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Case {
+    /// file name without the extension
+    pub name: String,
+    pub input: String,
+    pub expected: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Passed,
+    /// both outputs pretty printed, see `diff_lines`
+    Failed {
+        expected: String,
+        got: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub verdict: Verdict,
+    pub elapsed: Duration,
+}
+
+type Check = Box<dyn Fn(&Case) -> anyhow::Result<Outcome>>;
+
+/// One solution of one problem, with the types erased so that they can all go in one list
+pub struct Solution {
+    pub problem: &'static str,
+    pub name: &'static str,
+    check: Check,
+}
+
+impl Solution {
+    pub fn new<P: Problem>(
+        name: &'static str,
+        solve: impl Fn(P::Input) -> P::Output + 'static,
+    ) -> Self {
+        let check = move |case: &Case| {
+            let input = P::parse_input(&case.input).context("parsing the input")?;
+            let expected =
+                P::parse_output(&case.expected).context("parsing the expected output")?;
+            let start = Instant::now();
+            let got = solve(input);
+            let elapsed = start.elapsed();
+            let verdict = if got == expected {
+                Verdict::Passed
+            } else {
+                Verdict::Failed {
+                    expected: format!("{expected:#?}"),
+                    got: format!("{got:#?}"),
+                }
+            };
+            Ok(Outcome { verdict, elapsed })
+        };
+        Solution {
+            problem: P::NAME,
+            name,
+            check: Box::new(check),
+        }
+    }
+
+    pub fn check(&self, case: &Case) -> anyhow::Result<Outcome> {
+        (self.check)(case).with_context(|| format!("{}/{}", self.problem, case.name))
+    }
+}
+
+/// Every solution, in the order the runner goes through them
+pub fn registry() -> Vec<Solution> {
+    use kth_smallest_element_in_a_sorted_matrix as kth;
+    vec![
+        Solution::new::<max_area::MaxArea>("two_pointers", max_area::Solution::max_area),
+        Solution::new::<max_area::MaxArea>("binary_search", max_area::Solution2::max_area),
+        Solution::new::<kth::KthSmallest>("k_way_merge", |(matrix, k)| {
+            kth::Solution::kth_smallest(matrix, k)
+        }),
+        Solution::new::<kth::KthSmallest>("by_value", |(matrix, k)| {
+            kth::Solution::kth_smallest_by_value(matrix, k)
+        }),
+        Solution::new::<kth::KthSmallest>("staircase", |(matrix, k)| {
+            kth::Solution::kth_smallest_staircase(matrix, k)
+        }),
+    ]
+}
+
+/// `cases/` at the root of the crate
+pub fn default_cases_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("cases")
+}
+
+/// Every `<name>.in` in `dir` with its `<name>.out`, sorted by name
+pub fn load_cases(dir: &Path) -> anyhow::Result<Vec<Case>> {
+    let entries = fs::read_dir(dir).with_context(|| format!("reading {}", dir.display()))?;
+    let mut cases = vec![];
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_none_or(|extension| extension != "in") {
+            continue;
+        }
+        let output = path.with_extension("out");
+        let read = |path: &Path| {
+            fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))
+        };
+        cases.push(Case {
+            name: path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            input: read(&path)?,
+            expected: read(&output)?,
+        });
+    }
+    cases.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(cases)
+}
+
+/// Line by line, `-` for the expected lines and `+` for what came out instead
+pub fn diff_lines(expected: &str, got: &str) -> String {
+    let (mut expected, mut got) = (expected.lines(), got.lines());
+    let mut diff = String::new();
+    loop {
+        match (expected.next(), got.next()) {
+            (None, None) => break,
+            (Some(e), Some(g)) if e == g => diff += &format!("  {e}\n"),
+            (e, g) => {
+                if let Some(e) = e {
+                    diff += &format!("- {e}\n");
+                }
+                if let Some(g) = g {
+                    diff += &format!("+ {g}\n");
+                }
+            }
+        }
+    }
+    diff
+}
+
+// == Parsing helpers for the problems ==
+
+/// Whitespace separated values
+pub fn parse_list<T: FromStr>(text: &str) -> anyhow::Result<Vec<T>>
+where
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    text.split_whitespace()
+        .map(|word| {
+            word.parse()
+                .with_context(|| format!("invalid value `{word}`"))
+        })
+        .collect()
+}
+
+/// A single value, surrounding whitespace ignored
+pub fn parse_one<T: FromStr>(text: &str) -> anyhow::Result<T>
+where
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    let text = text.trim();
+    text.parse()
+        .with_context(|| format!("invalid value `{text}`"))
+}
+
+/// One list per non-blank line
+pub fn parse_rows<T: FromStr>(text: &str) -> anyhow::Result<Vec<Vec<T>>>
+where
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(parse_list)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registered_solutions_pass_their_cases() {
+        let dir = default_cases_dir();
+        for solution in registry() {
+            let cases = load_cases(&dir.join(solution.problem)).unwrap();
+            assert!(!cases.is_empty(), "no cases for {}", solution.problem);
+            for case in cases {
+                let outcome = solution.check(&case).unwrap();
+                if let Verdict::Failed { expected, got } = outcome.verdict {
+                    panic!(
+                        "{}/{} failed {}:\n{}",
+                        solution.problem,
+                        solution.name,
+                        case.name,
+                        diff_lines(&expected, &got)
+                    );
+                }
+            }
+        }
+    }
+
+    struct Sum;
+
+    impl Problem for Sum {
+        const NAME: &'static str = "sum";
+        type Input = Vec<i64>;
+        type Output = i64;
+
+        fn parse_input(text: &str) -> anyhow::Result<Vec<i64>> {
+            parse_list(text)
+        }
+
+        fn parse_output(text: &str) -> anyhow::Result<i64> {
+            parse_one(text)
+        }
+    }
+
+    #[test]
+    fn reports_mismatches_and_bad_cases() {
+        let case = |input: &str, expected: &str| Case {
+            name: "case".to_string(),
+            input: input.to_string(),
+            expected: expected.to_string(),
+        };
+        let right = Solution::new::<Sum>("right", |numbers| numbers.iter().sum());
+        let wrong = Solution::new::<Sum>("wrong", |numbers| numbers.iter().product());
+
+        let outcome = right.check(&case("1 2 3\n", "6\n")).unwrap();
+        assert_eq!(outcome.verdict, Verdict::Passed);
+        let outcome = wrong.check(&case("2 3 4", "9")).unwrap();
+        assert_eq!(
+            outcome.verdict,
+            Verdict::Failed {
+                expected: "9".to_string(),
+                got: "24".to_string()
+            }
+        );
+        let error = right.check(&case("1 two 3", "6")).unwrap_err();
+        assert!(format!("{error:#}").contains("invalid value `two`"));
+    }
+
+    #[test]
+    fn diffs_line_by_line() {
+        assert_eq!(
+            diff_lines("a\nb\nc", "a\nx\nc\nd"),
+            "  a\n- b\n+ x\n  c\n+ d\n"
+        );
+    }
+}
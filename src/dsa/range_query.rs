@@ -0,0 +1,105 @@
+// == Range queries, whatever the structure ==
+/* SegmentTree, FenwickTree and SparseTable all answer "combine everything in [l, r)", with
+ * different trade-offs:
+ *
+ *                   build        query      point update   operations
+ *     SegmentTree   O(n)         O(log n)   O(log n)       any monoid
+ *     FenwickTree   O(n)         O(log n)   O(log n)       sums (needs subtraction)
+ *     SparseTable   O(n log n)   O(1)       -              idempotent (min, max, gcd)
+ *
+ * RangeQuery is the common read-only interface, so callers can be written once and handed any of
+ * them. It takes a plain Range (not `impl RangeBounds`) to stay object safe, eg. to benchmark a
+ * `Vec<Box<dyn RangeQuery<T>>>` against the same queries.
+ */
+
+use std::ops::{Add, Range, Sub};
+
+use super::{
+    fenwick_tree::FenwickTree,
+    monoid::{Idempotent, Monoid, Zero},
+    segment_tree::SegmentTree,
+    sparse_table::SparseTable,
+};
+
+pub trait RangeQuery<T> {
+    fn len(&self) -> usize;
+
+    /// Combination of all the values in `range`, panics if it's out of bounds
+    fn query(&self, range: Range<usize>) -> T;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Clone, Op: Monoid<T>> RangeQuery<T> for SegmentTree<T, Op> {
+    fn len(&self) -> usize {
+        SegmentTree::len(self)
+    }
+
+    fn query(&self, range: Range<usize>) -> T {
+        SegmentTree::query(self, range)
+    }
+}
+
+impl<T: Clone, Op: Idempotent<T>> RangeQuery<T> for SparseTable<T, Op> {
+    fn len(&self) -> usize {
+        SparseTable::len(self)
+    }
+
+    fn query(&self, range: Range<usize>) -> T {
+        SparseTable::query(self, range)
+    }
+}
+
+impl<T: Zero + Add<Output = T> + Sub<Output = T> + Clone> RangeQuery<T> for FenwickTree<T> {
+    fn len(&self) -> usize {
+        FenwickTree::len(self)
+    }
+
+    fn query(&self, range: Range<usize>) -> T {
+        self.range_sum(range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsa::monoid::{Min, Sum};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn answers(structure: &dyn RangeQuery<i64>, queries: &[Range<usize>]) -> Vec<i64> {
+        queries.iter().map(|q| structure.query(q.clone())).collect()
+    }
+
+    #[test]
+    fn implementations_are_interchangeable() {
+        let mut rng = StdRng::seed_from_u64(33);
+        let values = (0..100)
+            .map(|_| rng.gen_range(-1000..1000))
+            .collect::<Vec<i64>>();
+        let queries = (0..500)
+            .map(|_| {
+                let l = rng.gen_range(0..=values.len());
+                l..rng.gen_range(l..=values.len())
+            })
+            .collect::<Vec<_>>();
+
+        let minimums: Vec<Box<dyn RangeQuery<i64>>> = vec![
+            Box::new(SegmentTree::<i64, Min>::from_slice(&values)),
+            Box::new(SparseTable::<i64, Min>::from_slice(&values)),
+        ];
+        let sums: Vec<Box<dyn RangeQuery<i64>>> = vec![
+            Box::new(SegmentTree::<i64, Sum>::from_slice(&values)),
+            Box::new(FenwickTree::from_vec(values.clone())),
+        ];
+
+        for structures in [minimums, sums] {
+            assert!(structures.iter().all(|s| s.len() == values.len()));
+            let expected = answers(structures[0].as_ref(), &queries);
+            for structure in &structures[1..] {
+                assert_eq!(answers(structure.as_ref(), &queries), expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,485 @@
+// == Binary search and selection ==
+/* Searching: everything is phrased like `slice::partition_point`, a predicate that is true on a
+ * prefix of the domain and false after it, and the answer is the first value where it's false.
+ * "first index with a[i] >= x", "smallest speed that finishes in time", ... are all that shape,
+ * and there's no off-by-one to get wrong in a hand written `while low <= high`.
+ *
+ * - integers: any range of any primitive integer, the midpoint is computed without overflow
+ * - floats: bisects until the two ends are adjacent floats, so no epsilon to choose
+ * - slices: lower/upper bound, and exponential search, which finds the answer at position i in
+ *   O(log i) (doubling until it's passed, then binary search), good when it's near the front
+ *
+ * Selection: `nth_element` puts the n-th smallest value at index n, everything smaller or equal
+ * before it, everything greater or equal after it, in place (like C++'s nth_element, or
+ * `select_nth_unstable`), O(n) on average instead of sorting the whole slice.
+ *
+ * - quickselect: median of three pivots, O(n) on average, O(n²) on adversarial inputs
+ * - median of medians: a pivot guaranteed to be in the middle 30-70% (the median of the medians
+ *   of groups of 5), O(n) worst case, but a much larger constant
+ * - introselect (`nth_element`): quickselect, switching to median of medians once it has taken
+ *   more steps than a balanced run would (2 log n), so O(n) worst case and fast in practice
+ *
+ * Partitioning is three-way (smaller / equal / greater), so many equal values don't degrade it.
+ * Every function has a `_by` version taking a comparator.
+ */
+
+use std::{
+    cmp::Ordering,
+    ops::{Range, RangeInclusive},
+};
+
+/// Primitive integers, for `partition_point_int`
+pub trait Integer: Copy + Ord {
+    /// floor((a + b) / 2), without overflowing
+    fn midpoint(a: Self, b: Self) -> Self;
+    fn successor(self) -> Self;
+}
+
+macro_rules! integer {
+    ($($t:ty),*) => {$(
+        impl Integer for $t {
+            fn midpoint(a: $t, b: $t) -> $t {
+                // every primitive up to 64 bits fits in an i128, and so does their sum
+                (a as i128 + b as i128).div_euclid(2) as $t
+            }
+
+            fn successor(self) -> $t {
+                self + 1
+            }
+        }
+    )*};
+}
+
+integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+/// First value in `range` where `pred` is false (`range.end` if there's none), `pred` must be
+/// true on a prefix of the range and false on the rest
+pub fn partition_point_int<T: Integer>(range: Range<T>, mut pred: impl FnMut(T) -> bool) -> T {
+    let (mut low, mut high) = (range.start, range.end);
+    while low < high {
+        let middle = T::midpoint(low, high);
+        if pred(middle) {
+            low = middle.successor();
+        } else {
+            high = middle;
+        }
+    }
+    low
+}
+
+/// The boundary between where `pred` is true and where it's false in `range`, to the precision
+/// of f64: the two ends get bisected until they are adjacent floats, the false end is returned.
+/// Returns the start if `pred` is false there, the end if it's true everywhere
+pub fn partition_point_float(range: RangeInclusive<f64>, mut pred: impl FnMut(f64) -> bool) -> f64 {
+    let (mut low, mut high) = range.into_inner();
+    if !pred(low) {
+        return low;
+    }
+    if pred(high) {
+        return high;
+    }
+    // pred(low) && !pred(high) from here on
+    loop {
+        let middle = low + (high - low) / 2.0;
+        if middle <= low || middle >= high {
+            return high;
+        }
+        if pred(middle) {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+}
+
+/// First index whose value is not less than `value`
+pub fn lower_bound<T: Ord>(slice: &[T], value: &T) -> usize {
+    lower_bound_by(slice, value, T::cmp)
+}
+
+/// First index whose value is greater than `value`
+pub fn upper_bound<T: Ord>(slice: &[T], value: &T) -> usize {
+    upper_bound_by(slice, value, T::cmp)
+}
+
+pub fn lower_bound_by<T>(slice: &[T], value: &T, mut cmp: impl FnMut(&T, &T) -> Ordering) -> usize {
+    slice.partition_point(|item| cmp(item, value) == Ordering::Less)
+}
+
+pub fn upper_bound_by<T>(slice: &[T], value: &T, mut cmp: impl FnMut(&T, &T) -> Ordering) -> usize {
+    slice.partition_point(|item| cmp(item, value) != Ordering::Greater)
+}
+
+/// Like `slice::binary_search`, in O(log i) where i is where the answer is
+pub fn exponential_search<T: Ord>(slice: &[T], value: &T) -> Result<usize, usize> {
+    exponential_search_by(slice, |item| item.cmp(value))
+}
+
+/// Like `slice::binary_search_by`: `f` says how an item compares to the one looked for
+pub fn exponential_search_by<T>(
+    slice: &[T],
+    mut f: impl FnMut(&T) -> Ordering,
+) -> Result<usize, usize> {
+    // find a power of two past the answer, the answer is in the last doubling
+    let mut end = 1;
+    while end < slice.len() && f(&slice[end - 1]) == Ordering::Less {
+        end *= 2;
+    }
+    let start = end / 2;
+    let end = end.min(slice.len());
+    let index = start + slice[start..end].partition_point(|item| f(item) == Ordering::Less);
+    match slice.get(index).map(&mut f) {
+        Some(Ordering::Equal) => Ok(index),
+        _ => Err(index),
+    }
+}
+
+// == Selection ==
+
+// rearranges slice[low..high] into < pivot, == pivot, > pivot, returning the == range
+fn partition3<T>(
+    slice: &mut [T],
+    low: usize,
+    high: usize,
+    pivot: usize,
+    cmp: &mut impl FnMut(&T, &T) -> Ordering,
+) -> Range<usize> {
+    slice.swap(low, pivot);
+    // slice[lt..i] is the == block, and slice[lt] stays a copy of the pivot value
+    let (mut lt, mut i, mut gt) = (low, low + 1, high);
+    while i < gt {
+        match cmp(&slice[i], &slice[lt]) {
+            Ordering::Less => {
+                slice.swap(lt, i);
+                lt += 1;
+                i += 1;
+            }
+            Ordering::Greater => {
+                gt -= 1;
+                slice.swap(i, gt);
+            }
+            Ordering::Equal => i += 1,
+        }
+    }
+    lt..gt
+}
+
+fn median_of_three<T>(
+    slice: &[T],
+    low: usize,
+    high: usize,
+    cmp: &mut impl FnMut(&T, &T) -> Ordering,
+) -> usize {
+    let (a, b, c) = (low, low + (high - low) / 2, high - 1);
+    let mut less = |x: usize, y: usize| cmp(&slice[x], &slice[y]) == Ordering::Less;
+    if less(a, b) {
+        if less(b, c) {
+            b
+        } else if less(a, c) {
+            c
+        } else {
+            a
+        }
+    } else if less(a, c) {
+        a
+    } else if less(b, c) {
+        c
+    } else {
+        b
+    }
+}
+
+// index (in low..high) of a value with at least ~30% of slice[low..high] on each side of it.
+// Moves the medians of the groups of 5 to the front of the range
+fn median_of_medians<T>(
+    slice: &mut [T],
+    low: usize,
+    high: usize,
+    cmp: &mut impl FnMut(&T, &T) -> Ordering,
+) -> usize {
+    let mut medians = 0;
+    for start in (low..high).step_by(5) {
+        let group = &mut slice[start..(start + 5).min(high)];
+        // insertion sort, at most 10 comparisons
+        for i in 1..group.len() {
+            for j in (1..=i).rev() {
+                if cmp(&group[j], &group[j - 1]) != Ordering::Less {
+                    break;
+                }
+                group.swap(j, j - 1);
+            }
+        }
+        let median = start + group.len() / 2;
+        slice.swap(low + medians, median);
+        medians += 1;
+    }
+    let middle = low + medians / 2;
+    select(
+        slice,
+        low,
+        low + medians,
+        middle,
+        cmp,
+        Strategy::MedianOfMedians,
+    );
+    middle
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Strategy {
+    Quickselect,
+    MedianOfMedians,
+    Introselect,
+}
+
+// puts the value of rank `n` (an index in low..high) at index n, partitioned around it
+fn select<T>(
+    slice: &mut [T],
+    mut low: usize,
+    mut high: usize,
+    n: usize,
+    cmp: &mut impl FnMut(&T, &T) -> Ordering,
+    mut strategy: Strategy,
+) {
+    // introselect's budget: twice the steps a run halving the range every time would take
+    let mut budget = 2 * (usize::BITS - (high - low).leading_zeros());
+    while high - low > 1 {
+        if strategy == Strategy::Introselect && budget == 0 {
+            strategy = Strategy::MedianOfMedians;
+        }
+        budget = budget.saturating_sub(1);
+        let pivot = match strategy {
+            Strategy::MedianOfMedians => median_of_medians(slice, low, high, cmp),
+            Strategy::Quickselect | Strategy::Introselect => median_of_three(slice, low, high, cmp),
+        };
+        let equal = partition3(slice, low, high, pivot, cmp);
+        if n < equal.start {
+            high = equal.start;
+        } else if n >= equal.end {
+            low = equal.end;
+        } else {
+            return;
+        }
+    }
+}
+
+fn select_checked<T>(
+    slice: &mut [T],
+    n: usize,
+    mut cmp: impl FnMut(&T, &T) -> Ordering,
+    strategy: Strategy,
+) -> &mut T {
+    assert!(
+        n < slice.len(),
+        "rank {n} out of range for a slice of length {}",
+        slice.len()
+    );
+    let len = slice.len();
+    select(slice, 0, len, n, &mut cmp, strategy);
+    &mut slice[n]
+}
+
+/// Introselect: puts the n-th smallest value (0-based) at index n, with nothing greater before it
+/// and nothing smaller after it. O(n) worst case. Panics if n >= slice.len()
+pub fn nth_element<T: Ord>(slice: &mut [T], n: usize) -> &mut T {
+    nth_element_by(slice, n, T::cmp)
+}
+
+pub fn nth_element_by<T>(slice: &mut [T], n: usize, cmp: impl FnMut(&T, &T) -> Ordering) -> &mut T {
+    select_checked(slice, n, cmp, Strategy::Introselect)
+}
+
+/// `nth_element` with plain quickselect, O(n²) worst case
+pub fn quickselect<T: Ord>(slice: &mut [T], n: usize) -> &mut T {
+    quickselect_by(slice, n, T::cmp)
+}
+
+pub fn quickselect_by<T>(slice: &mut [T], n: usize, cmp: impl FnMut(&T, &T) -> Ordering) -> &mut T {
+    select_checked(slice, n, cmp, Strategy::Quickselect)
+}
+
+/// `nth_element` with median of medians pivots only, O(n) worst case but slower on average
+pub fn median_of_medians_select<T: Ord>(slice: &mut [T], n: usize) -> &mut T {
+    median_of_medians_select_by(slice, n, T::cmp)
+}
+
+pub fn median_of_medians_select_by<T>(
+    slice: &mut [T],
+    n: usize,
+    cmp: impl FnMut(&T, &T) -> Ordering,
+) -> &mut T {
+    select_checked(slice, n, cmp, Strategy::MedianOfMedians)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsa::stress::{check, Config};
+    use std::cmp::Reverse;
+
+    #[test]
+    fn binary_searches_match_linear_scans() {
+        // (values, x), the values get sorted by both sides
+        check(
+            "binary searches",
+            Config::default(),
+            |g| (g.vec(0..=40, |g| g.int(0..=19)), g.int(-1..=20)),
+            |(values, x)| {
+                let mut values = values.clone();
+                values.sort();
+                let len = values.len();
+                let lower = values.iter().position(|v| v >= x).unwrap_or(len);
+                let upper = values.iter().position(|v| v > x).unwrap_or(len);
+                let found = if values.contains(x) {
+                    Ok(*x)
+                } else {
+                    Err(lower)
+                };
+                (lower, upper, lower, found, len - upper, len - lower)
+            },
+            |(values, x)| {
+                let mut values = values.clone();
+                values.sort();
+                let found = exponential_search(&values, x).map(|i| values[i]);
+                let descending = values.iter().rev().copied().collect::<Vec<_>>();
+                let by = |a: &i32, b: &i32| b.cmp(a);
+                (
+                    lower_bound(&values, x),
+                    upper_bound(&values, x),
+                    partition_point_int(0..values.len(), |i| values[i] < *x),
+                    found,
+                    lower_bound_by(&descending, x, by),
+                    upper_bound_by(&descending, x, by),
+                )
+            },
+        );
+    }
+
+    #[test]
+    fn integer_and_float_domains() {
+        // no overflow at the ends of the type
+        assert_eq!(partition_point_int(i64::MIN..i64::MAX, |v| v < -3), -3);
+        assert_eq!(
+            partition_point_int(u64::MIN..u64::MAX, |v| v < u64::MAX - 1),
+            u64::MAX - 1
+        );
+        assert_eq!(partition_point_int(0u8..255, |_| true), 255);
+        assert_eq!(partition_point_int(5..5, |_: i32| true), 5);
+
+        let sqrt2 = partition_point_float(0.0..=2.0, |x| x * x < 2.0);
+        assert!((sqrt2 - 2f64.sqrt()).abs() <= f64::EPSILON * 2.0);
+        assert_eq!(partition_point_float(1.0..=2.0, |_| false), 1.0);
+        assert_eq!(partition_point_float(1.0..=2.0, |_| true), 2.0);
+    }
+
+    fn check_selected<T: Ord + Clone + std::fmt::Debug>(original: &[T], selected: &[T], n: usize) {
+        let mut sorted = original.to_vec();
+        sorted.sort();
+        assert_eq!(selected[n], sorted[n]);
+        assert!(selected[..n].iter().all(|v| *v <= selected[n]));
+        assert!(selected[n + 1..].iter().all(|v| *v >= selected[n]));
+        let mut permutation = selected.to_vec();
+        permutation.sort();
+        assert_eq!(permutation, sorted);
+    }
+
+    // the selected value, after checking the slice is a permutation of `original` partitioned
+    // around it
+    fn selection<T: Ord + Clone + std::fmt::Debug>(
+        name: &str,
+        original: &[T],
+        selected: &[T],
+        n: usize,
+    ) -> T {
+        let mut sorted = original.to_vec();
+        sorted.sort();
+        let mut permutation = selected.to_vec();
+        permutation.sort();
+        assert_eq!(permutation, sorted, "{name} lost or duplicated values");
+        assert!(
+            selected[..n].iter().all(|v| *v <= selected[n])
+                && selected[n + 1..].iter().all(|v| *v >= selected[n]),
+            "{name} didn't partition {selected:?} around index {n}"
+        );
+        selected[n].clone()
+    }
+
+    #[test]
+    fn selection_matches_sorting() {
+        // (values, n), n is taken modulo the length so every shrunk input stays valid
+        check(
+            "nth_element and friends",
+            Config::default(),
+            |g| {
+                let spread = g.int(1..=99);
+                (g.vec(0..=60, |g| g.int(0..=spread)), g.int(0..=59usize))
+            },
+            |(values, n)| {
+                let mut sorted = values.clone();
+                sorted.sort();
+                let n = n.checked_rem(values.len())?;
+                let mut expected = vec![sorted[n]; 3];
+                // the n-th smallest in reversed order is the n-th largest
+                expected.push(sorted[values.len() - 1 - n]);
+                Some(expected)
+            },
+            |(values, n)| {
+                let n = n.checked_rem(values.len())?;
+                let strategies: [(&str, fn(&mut [i32], usize) -> &mut i32); 3] = [
+                    ("nth_element", nth_element),
+                    ("quickselect", quickselect),
+                    ("median_of_medians_select", median_of_medians_select),
+                ];
+                let mut results = strategies
+                    .iter()
+                    .map(|(name, select)| {
+                        let mut selected = values.clone();
+                        let value = *select(&mut selected, n);
+                        let at_n = selection(name, values, &selected, n);
+                        assert_eq!(
+                            value, at_n,
+                            "{name} returned a value that isn't at index {n}"
+                        );
+                        value
+                    })
+                    .collect::<Vec<_>>();
+
+                let original = values.iter().map(|&v| Reverse(v)).collect::<Vec<_>>();
+                let mut reversed = original.clone();
+                nth_element_by(&mut reversed, n, |a, b| a.cmp(b));
+                let Reverse(value) = selection("nth_element_by", &original, &reversed, n);
+                results.push(value);
+                Some(results)
+            },
+        );
+    }
+
+    #[test]
+    fn adversarial_inputs_stay_linear() {
+        // sorted, reversed, all equal and organ pipe: the classic quickselect killers for
+        // median of three are fine here, the point is that nothing degrades or recurses deeply
+        let n = 20_000;
+        let inputs = [
+            (0..n).collect::<Vec<_>>(),
+            (0..n).rev().collect(),
+            vec![7; n],
+            (0..n / 2).chain((0..n / 2).rev()).collect(),
+        ];
+        for values in inputs {
+            for rank in [0, n / 2, n - 1] {
+                let mut selected = values.clone();
+                nth_element(&mut selected, rank);
+                check_selected(&values, &selected, rank);
+                let mut selected = values.clone();
+                median_of_medians_select(&mut selected, rank);
+                check_selected(&values, &selected, rank);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn rank_past_the_end_panics() {
+        nth_element(&mut [1, 2, 3], 3);
+    }
+}
@@ -0,0 +1,217 @@
+// == Segment tree (iterative, bottom-up) ==
+/* For n values, the tree is stored in a flat Vec of size 2n:
+ * - leaves live at [n, 2n), i.e. value i is at tree[n + i]
+ * - node i (for 1 <= i < n) is combine(tree[2i], tree[2i + 1])
+ * so there's no recursion, no 4n allocation and no power of two padding (n can be anything).
+ *
+ * Building from a slice is O(n) (every internal node is computed once, right to left),
+ * point updates and range queries are O(log n).
+ *
+ * A query walks up from both ends of [l, r) at once, collecting the left side into `left` and the
+ * right side into `right`, which keeps the elements in order for non-commutative monoids.
+ */
+
+use std::{
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+};
+
+use super::monoid::Monoid;
+
+pub struct SegmentTree<T, Op> {
+    len: usize,
+    tree: Vec<T>,
+    op: PhantomData<Op>,
+}
+
+/// Turns any `RangeBounds` into a half-open [start, end), panics like slice indexing when invalid
+pub(crate) fn to_half_open(range: impl RangeBounds<usize>, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(&s) => s,
+        Bound::Excluded(&s) => s + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&e) => e + 1,
+        Bound::Excluded(&e) => e,
+        Bound::Unbounded => len,
+    };
+    assert!(
+        start <= end && end <= len,
+        "range [{start}, {end}) out of bounds for length {len}"
+    );
+    (start, end)
+}
+
+impl<T: Clone, Op: Monoid<T>> SegmentTree<T, Op> {
+    /// `len` identity values
+    pub fn new(len: usize) -> Self {
+        SegmentTree {
+            len,
+            tree: vec![Op::identity(); 2 * len],
+            op: PhantomData,
+        }
+    }
+
+    pub fn from_vec(values: Vec<T>) -> Self {
+        let len = values.len();
+        let mut tree = Vec::with_capacity(2 * len);
+        tree.resize(len, Op::identity()); // internal nodes, filled in below
+        tree.extend(values);
+        for i in (1..len).rev() {
+            tree[i] = Op::combine(&tree[2 * i], &tree[2 * i + 1]);
+        }
+        SegmentTree {
+            len,
+            tree,
+            op: PhantomData,
+        }
+    }
+
+    pub fn from_slice(values: &[T]) -> Self {
+        Self::from_vec(values.to_vec())
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> &T {
+        assert!(
+            index < self.len,
+            "index {index} out of bounds for length {}",
+            self.len
+        );
+        &self.tree[self.len + index]
+    }
+
+    pub fn set(&mut self, index: usize, value: T) {
+        assert!(
+            index < self.len,
+            "index {index} out of bounds for length {}",
+            self.len
+        );
+        let mut i = self.len + index;
+        self.tree[i] = value;
+        while i > 1 {
+            i /= 2;
+            self.tree[i] = Op::combine(&self.tree[2 * i], &self.tree[2 * i + 1]);
+        }
+    }
+
+    /// Point update from the old value, eg. `tree.update(i, |old| Min::combine(old, &new))`
+    pub fn update(&mut self, index: usize, f: impl FnOnce(&T) -> T) {
+        let value = f(self.get(index));
+        self.set(index, value);
+    }
+
+    /// Combination of all the values in `range`, identity for an empty range
+    pub fn query(&self, range: impl RangeBounds<usize>) -> T {
+        let (start, end) = to_half_open(range, self.len);
+        let (mut left, mut right) = (Op::identity(), Op::identity());
+        let (mut l, mut r) = (start + self.len, end + self.len);
+        while l < r {
+            if l % 2 == 1 {
+                left = Op::combine(&left, &self.tree[l]);
+                l += 1;
+            }
+            if r % 2 == 1 {
+                r -= 1;
+                right = Op::combine(&self.tree[r], &right);
+            }
+            l /= 2;
+            r /= 2;
+        }
+        Op::combine(&left, &right)
+    }
+}
+
+impl<T: Clone, Op: Monoid<T>> FromIterator<T> for SegmentTree<T, Op> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsa::monoid::{GcdOf, Max, Min, Sum};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    // random point updates and range queries against folding the plain Vec
+    fn check_against_brute_force<Op: Monoid<i64>>(seed: u64, values: impl Fn(&mut StdRng) -> i64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        for len in [0, 1, 2, 3, 7, 16, 33] {
+            let mut brute = (0..len).map(|_| values(&mut rng)).collect::<Vec<_>>();
+            let mut tree = SegmentTree::<i64, Op>::from_slice(&brute);
+
+            for _ in 0..200 {
+                if len > 0 && rng.gen_bool(0.3) {
+                    let (i, v) = (rng.gen_range(0..len), values(&mut rng));
+                    brute[i] = v;
+                    tree.set(i, v);
+                }
+                let l = rng.gen_range(0..=len);
+                let r = rng.gen_range(l..=len);
+                let expected = brute[l..r]
+                    .iter()
+                    .fold(Op::identity(), |acc, v| Op::combine(&acc, v));
+                assert_eq!(tree.query(l..r), expected, "len {len}, range {l}..{r}");
+            }
+        }
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        check_against_brute_force::<Min>(1, |rng| rng.gen_range(-100..100));
+        check_against_brute_force::<Max>(2, |rng| rng.gen_range(-100..100));
+        check_against_brute_force::<Sum>(3, |rng| rng.gen_range(-100..100));
+        check_against_brute_force::<GcdOf>(4, |rng| rng.gen_range(-10..10) * 6);
+    }
+
+    #[test]
+    fn keeps_order_for_non_commutative_monoids() {
+        struct Concat;
+        impl Monoid<String> for Concat {
+            fn identity() -> String {
+                String::new()
+            }
+            fn combine(a: &String, b: &String) -> String {
+                format!("{a}{b}")
+            }
+        }
+
+        let letters = "abcdefghijk";
+        let mut tree = letters
+            .chars()
+            .map(String::from)
+            .collect::<SegmentTree<String, Concat>>();
+        for l in 0..=letters.len() {
+            for r in l..=letters.len() {
+                assert_eq!(tree.query(l..r), letters[l..r]);
+            }
+        }
+        tree.set(4, String::from("E"));
+        assert_eq!(tree.query(3..6), "dEf");
+        assert_eq!(tree.query(5..5), "");
+    }
+
+    #[test]
+    fn update_combines_with_the_old_value() {
+        let mut tree = SegmentTree::<i32, Min>::new(5);
+        assert_eq!(tree.query(..), i32::MAX);
+        tree.update(3, |old| Min::combine(old, &7));
+        tree.update(3, |old| Min::combine(old, &9));
+        assert_eq!((tree.query(..3), tree.query(3..)), (i32::MAX, 7));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn rejects_ranges_past_the_end() {
+        SegmentTree::<i32, Sum>::from_slice(&[1, 2, 3]).query(1..4);
+    }
+}
@@ -0,0 +1,103 @@
+// == Sparse table ==
+/* Static range queries in O(1) after an O(n log n) build, no updates.
+ * levels[k][i] = combine of the 2^k values starting at i, built from two halves of level k - 1.
+ *
+ * A query [l, r) picks the largest 2^k <= r - l and combines the two (overlapping) blocks
+ * [l, l + 2^k) and [r - 2^k, r). The overlap is only harmless when combine(a, a) == a,
+ * hence `Op: Idempotent` (min, max, gcd; not sum).
+ */
+
+use std::{marker::PhantomData, ops::RangeBounds};
+
+use super::{monoid::Idempotent, segment_tree::to_half_open};
+
+#[derive(Debug, Clone)]
+pub struct SparseTable<T, Op> {
+    levels: Vec<Vec<T>>,
+    op: PhantomData<Op>,
+}
+
+impl<T: Clone, Op: Idempotent<T>> SparseTable<T, Op> {
+    pub fn from_vec(values: Vec<T>) -> Self {
+        let len = values.len();
+        let mut levels = vec![values];
+        let mut width = 1;
+        while 2 * width <= len {
+            let previous = levels.last().unwrap();
+            let level = (0..=len - 2 * width)
+                .map(|i| Op::combine(&previous[i], &previous[i + width]))
+                .collect();
+            levels.push(level);
+            width *= 2;
+        }
+        SparseTable {
+            levels,
+            op: PhantomData,
+        }
+    }
+
+    pub fn from_slice(values: &[T]) -> Self {
+        Self::from_vec(values.to_vec())
+    }
+
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, index: usize) -> &T {
+        &self.levels[0][index]
+    }
+
+    /// Combination of all the values in `range`, identity for an empty range
+    pub fn query(&self, range: impl RangeBounds<usize>) -> T {
+        let (start, end) = to_half_open(range, self.len());
+        if start == end {
+            return Op::identity();
+        }
+        let k = (end - start).ilog2() as usize;
+        let level = &self.levels[k];
+        Op::combine(&level[start], &level[end - (1 << k)])
+    }
+}
+
+impl<T: Clone, Op: Idempotent<T>> FromIterator<T> for SparseTable<T, Op> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsa::monoid::{GcdOf, Max, Min};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn check_all_ranges<Op: Idempotent<i64>>(seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        for len in [0, 1, 2, 3, 7, 16, 33] {
+            let values = (0..len)
+                .map(|_| rng.gen_range(-20..20) * 6)
+                .collect::<Vec<i64>>();
+            let table = SparseTable::<i64, Op>::from_slice(&values);
+            for l in 0..=len {
+                for r in l..=len {
+                    let expected = values[l..r]
+                        .iter()
+                        .fold(Op::identity(), |acc, v| Op::combine(&acc, v));
+                    assert_eq!(table.query(l..r), expected, "len {len}, range {l}..{r}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        check_all_ranges::<Min>(1);
+        check_all_ranges::<Max>(2);
+        check_all_ranges::<GcdOf>(3);
+    }
+}
@@ -0,0 +1,209 @@
+// == Stress testing against a reference ==
+/* Runs a candidate solution and a (slow, obviously right) reference on random inputs until they
+ * disagree, then shrinks the input to a minimal counterexample and reports it with the seed that
+ * generates it:
+ *
+ *     check("max_area", Config::default(), |g| g.vec(0..10, |g| g.int(0..=5)), brute, fast);
+ *
+ * Every case gets its own seed (config.seed + case number), the one printed on failure makes the
+ * failing input the very first case, so SEED=<n> replays it at once.
+ *
+ * A panic (index out of bounds, unwrap on an empty input, ..) counts as a wrong answer, so it is
+ * shrunk and reported like any other; the default hook still prints its message while shrinking.
+ *
+ * Shrinking, like Config, comes from crate::testing (Vec, tuples and integers implement Shrink).
+ */
+
+use std::{
+    fmt::{self, Debug},
+    ops::RangeInclusive,
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+use rand::{distributions::uniform::SampleUniform, rngs::StdRng, Rng, SeedableRng};
+
+pub use crate::testing::Config;
+use crate::testing::{shrink_failure, Shrink};
+
+/// Random inputs for one case
+pub struct Gen {
+    rng: StdRng,
+}
+
+impl Gen {
+    pub fn new(seed: u64) -> Self {
+        Gen {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+
+    pub fn int<T: SampleUniform + PartialOrd>(&mut self, range: RangeInclusive<T>) -> T {
+        self.rng.gen_range(range)
+    }
+
+    /// A length in `len`, then every item from `item`
+    pub fn vec<T>(
+        &mut self,
+        len: RangeInclusive<usize>,
+        mut item: impl FnMut(&mut Gen) -> T,
+    ) -> Vec<T> {
+        let len = self.rng.gen_range(len);
+        (0..len).map(|_| item(self)).collect()
+    }
+}
+
+/// What a solution did on an input: its answer, or the message it panicked with
+pub type Answer<O> = Result<O, String>;
+
+fn answer<I, O>(solve: impl Fn(&I) -> O, input: &I) -> Answer<O> {
+    catch_unwind(AssertUnwindSafe(|| solve(input))).map_err(|panic| {
+        panic
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "panicked".to_string())
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Failure<I, O> {
+    /// generates `original` as the first case
+    pub seed: u64,
+    /// the smallest input found that still fails
+    pub input: I,
+    pub expected: Answer<O>,
+    pub got: Answer<O>,
+    /// the input as generated
+    pub original: I,
+}
+
+impl<I: Debug, O: Debug> fmt::Display for Failure<I, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "replay with SEED={}", self.seed)?;
+        writeln!(f, "  minimal: {:?}", self.input)?;
+        writeln!(f, "  expected: {:?}", self.expected)?;
+        writeln!(f, "  got: {:?}", self.got)?;
+        write!(f, "  original: {:?}", self.original)
+    }
+}
+
+/// Compares `candidate` with `reference` on `config.cases` generated inputs, the first
+/// disagreement is shrunk and returned
+pub fn stress<I, O, G, R, C>(
+    config: Config,
+    mut generate: G,
+    reference: R,
+    candidate: C,
+) -> Result<(), Failure<I, O>>
+where
+    I: Shrink + Clone,
+    O: PartialEq,
+    G: FnMut(&mut Gen) -> I,
+    R: Fn(&I) -> O,
+    C: Fn(&I) -> O,
+{
+    let agree = |input: &I| answer(&reference, input) == answer(&candidate, input);
+    for case in 0..config.cases {
+        let seed = config.seed.wrapping_add(case as u64);
+        let input = generate(&mut Gen::new(seed));
+        if agree(&input) {
+            continue;
+        }
+        let smallest = shrink_failure(input.clone(), agree, config.max_shrinks);
+        return Err(Failure {
+            seed,
+            expected: answer(&reference, &smallest),
+            got: answer(&candidate, &smallest),
+            input: smallest,
+            original: input,
+        });
+    }
+    Ok(())
+}
+
+/// `stress`, panicking with the counterexample
+pub fn check<I, O, G, R, C>(name: &str, config: Config, generate: G, reference: R, candidate: C)
+where
+    I: Shrink + Clone + Debug,
+    O: PartialEq + Debug,
+    G: FnMut(&mut Gen) -> I,
+    R: Fn(&I) -> O,
+    C: Fn(&I) -> O,
+{
+    if let Err(failure) = stress(config, generate, reference, candidate) {
+        panic!("`{name}` disagrees with its reference, {failure}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn max_subarray_sum(numbers: &[i32]) -> i32 {
+        (0..=numbers.len())
+            .flat_map(|i| (i..=numbers.len()).map(move |j| (i, j)))
+            .map(|(i, j)| numbers[i..j].iter().sum())
+            .max()
+            .unwrap()
+    }
+
+    // Kadane, forgetting that the empty subarray is allowed (unless there's nothing else)
+    fn kadane_without_empty(numbers: &[i32]) -> i32 {
+        let Some(&first) = numbers.first() else {
+            return 0;
+        };
+        let mut best = first;
+        let mut current = 0;
+        for &n in numbers {
+            current = (current + n).max(n);
+            best = best.max(current);
+        }
+        best
+    }
+
+    #[test]
+    fn shrinks_to_a_minimal_counterexample() {
+        let config = Config {
+            seed: 41,
+            ..Config::default()
+        };
+        let generate = |g: &mut Gen| g.vec(1..=12, |g| g.int(-20..=20));
+        let reference = |numbers: &Vec<i32>| max_subarray_sum(numbers);
+        let candidate = |numbers: &Vec<i32>| kadane_without_empty(numbers);
+        let failure =
+            stress(config, generate, reference, candidate).expect_err("all negative inputs exist");
+        // one negative number is as small as it gets
+        assert_eq!(failure.input, [-1]);
+        assert_eq!((failure.expected, failure.got), (Ok(0), Ok(-1)));
+
+        // the seed replays the original input as the first case
+        let replay = Config {
+            seed: failure.seed,
+            cases: 1,
+            ..config
+        };
+        let again = stress(replay, generate, reference, candidate).unwrap_err();
+        assert_eq!(again.original, failure.original);
+    }
+
+    #[test]
+    fn panics_are_wrong_answers() {
+        let generate = |g: &mut Gen| g.vec(0..=3, |g| g.int(0..=9));
+        let largest = |numbers: &Vec<i32>| numbers.iter().max().copied().unwrap_or(0);
+        let unwrapped = |numbers: &Vec<i32>| *numbers.iter().max().unwrap();
+        let failure = stress(Config::default(), generate, largest, unwrapped).unwrap_err();
+        assert_eq!(failure.input, Vec::<i32>::new());
+        assert!(failure.got.is_err());
+        check(
+            "largest of non-empty inputs",
+            Config::default(),
+            |g| g.vec(1..=8, |g| g.int(0..=9)),
+            largest,
+            unwrapped,
+        );
+    }
+}
@@ -0,0 +1,202 @@
+// == Aho–Corasick ==
+/* Finds every occurrence of every pattern in O(text + patterns + matches), however many
+ * patterns there are.
+ *
+ * The patterns go into a trie (one node per prefix). Each node also gets a failure link: the node
+ * of its longest proper suffix that is also a prefix of some pattern. Scanning the text, when the
+ * current node has no child for the next char, we follow failure links (keeping as much of what
+ * was matched as possible) instead of starting over.
+ *
+ * A node's outputs are the patterns ending there plus the outputs of its failure node (a pattern
+ * that is a suffix of another one ends at the same time), merged once while building, breadth
+ * first so that the failure node is always done before.
+ *
+ * Empty patterns never match.
+ */
+
+use std::collections::{BTreeMap, VecDeque};
+
+const ROOT: usize = 0;
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    children: BTreeMap<char, usize>,
+    failure: usize,
+    /// indices of the patterns ending here
+    outputs: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    /// index of the pattern in the list given to `AhoCorasick::new`
+    pub pattern: usize,
+    /// byte offsets in the text, `&text[start..end]` is the pattern
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct AhoCorasick {
+    nodes: Vec<Node>,
+    /// in bytes, to turn the end of a match into its start
+    pattern_lens: Vec<usize>,
+}
+
+impl AhoCorasick {
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Self {
+        let mut nodes = vec![Node::default()];
+        for (index, pattern) in patterns.iter().enumerate() {
+            let pattern = pattern.as_ref();
+            if pattern.is_empty() {
+                continue;
+            }
+            let mut node = ROOT;
+            for c in pattern.chars() {
+                node = match nodes[node].children.get(&c) {
+                    Some(&child) => child,
+                    None => {
+                        nodes.push(Node::default());
+                        let child = nodes.len() - 1;
+                        nodes[node].children.insert(c, child);
+                        child
+                    }
+                };
+            }
+            nodes[node].outputs.push(index);
+        }
+
+        let mut automaton = AhoCorasick {
+            nodes,
+            pattern_lens: patterns.iter().map(|p| p.as_ref().len()).collect(),
+        };
+        automaton.link_failures();
+        automaton
+    }
+
+    // breadth first, so every shorter node (and so every failure target) is linked already.
+    // The root's children fail to the root, which is the default
+    fn link_failures(&mut self) {
+        let mut queue = self.nodes[ROOT]
+            .children
+            .values()
+            .copied()
+            .collect::<VecDeque<_>>();
+        while let Some(node) = queue.pop_front() {
+            let children = self.nodes[node]
+                .children
+                .iter()
+                .map(|(&c, &child)| (c, child))
+                .collect::<Vec<_>>();
+            for (c, child) in children {
+                // the longest suffix of (node + c) in the trie extends one of node's suffixes
+                let failure = self.step(self.nodes[node].failure, c);
+                self.nodes[child].failure = failure;
+                let inherited = self.nodes[failure].outputs.clone();
+                self.nodes[child].outputs.extend(inherited);
+                queue.push_back(child);
+            }
+        }
+    }
+
+    /// Next node after reading `c` in `node`
+    fn step(&self, mut node: usize, c: char) -> usize {
+        loop {
+            if let Some(&child) = self.nodes[node].children.get(&c) {
+                return child;
+            }
+            if node == ROOT {
+                return ROOT;
+            }
+            node = self.nodes[node].failure;
+        }
+    }
+
+    /// Every occurrence of every pattern (overlapping ones too), ordered by where they end, then
+    /// longest first
+    pub fn find_all(&self, text: &str) -> Vec<Match> {
+        let mut matches = vec![];
+        let mut node = ROOT;
+        for (offset, c) in text.char_indices() {
+            node = self.step(node, c);
+            let end = offset + c.len_utf8();
+            matches.extend(self.nodes[node].outputs.iter().map(|&pattern| Match {
+                pattern,
+                start: end - self.pattern_lens[pattern],
+                end,
+            }));
+        }
+        matches
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        let mut node = ROOT;
+        text.chars().any(|c| {
+            node = self.step(node, c);
+            !self.nodes[node].outputs.is_empty()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+    // every char boundary, every pattern
+    fn naive(patterns: &[String], text: &str) -> Vec<Match> {
+        let mut matches = vec![];
+        for start in text.char_indices().map(|(i, _)| i) {
+            for (pattern, p) in patterns.iter().enumerate() {
+                if !p.is_empty() && text[start..].starts_with(p.as_str()) {
+                    matches.push(Match {
+                        pattern,
+                        start,
+                        end: start + p.len(),
+                    });
+                }
+            }
+        }
+        matches
+    }
+
+    #[test]
+    fn matches_naive_search() {
+        let mut rng = StdRng::seed_from_u64(39);
+        let alphabet = ['a', 'b', 'ä', 'ß', '語'];
+        let random = |rng: &mut StdRng, max: usize| {
+            let len = rng.gen_range(0..=max);
+            (0..len)
+                .map(|_| *alphabet.choose(rng).unwrap())
+                .collect::<String>()
+        };
+        for _ in 0..300 {
+            let patterns = (0..rng.gen_range(0..8))
+                .map(|_| random(&mut rng, 4))
+                .collect::<Vec<_>>();
+            let text = random(&mut rng, 40);
+            let automaton = AhoCorasick::new(&patterns);
+
+            let key = |m: &Match| (m.start, m.end, m.pattern);
+            let mut found = automaton.find_all(&text);
+            let mut expected = naive(&patterns, &text);
+            found.sort_by_key(key);
+            expected.sort_by_key(key);
+            assert_eq!(found, expected, "{patterns:?} in {text:?}");
+            assert_eq!(automaton.is_match(&text), !expected.is_empty());
+        }
+    }
+
+    #[test]
+    fn overlapping_and_nested_patterns() {
+        let patterns = ["he", "she", "his", "hers"];
+        let automaton = AhoCorasick::new(&patterns);
+        let text = "ushers";
+        let found = automaton
+            .find_all(text)
+            .into_iter()
+            .map(|m| &text[m.start..m.end])
+            .collect::<Vec<_>>();
+        assert_eq!(found, ["she", "he", "hers"]);
+        assert!(!automaton.is_match("hi"));
+    }
+}
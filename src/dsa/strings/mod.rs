@@ -0,0 +1,14 @@
+// == String search ==
+/* Everything in here works on chars (unicode scalar values), not bytes, so a multi-byte
+ * character is one step in a trie / automaton / suffix, and nothing ever matches half of one.
+ * Positions handed back are still byte offsets into the original &str, so they can be used to
+ * slice it directly (`&text[m.start..m.end]`).
+ *
+ * - trie: a set of words, prefix iteration and longest-prefix matching
+ * - aho_corasick: every occurrence of many patterns in one pass over the text
+ * - suffix_array: sorted suffixes of one text plus their LCPs, for substring queries
+ */
+
+pub mod aho_corasick;
+pub mod suffix_array;
+pub mod trie;
@@ -0,0 +1,240 @@
+// == Suffix array and LCP ==
+/* All the suffixes of a text, sorted, as their starting positions; plus lcp[i], the length of the
+ * longest common prefix of the i-th and (i + 1)-th suffixes in that order.
+ *
+ * - build: prefix doubling, sort by (rank of the first k chars, rank of the next k chars) for
+ *   k = 1, 2, 4, ... until all ranks differ, O(n log² n)
+ * - lcp: Kasai, O(n). Going through the suffixes in text order, the lcp can only drop by one from
+ *   one suffix to the next (remove the first char of both), so it's never recomputed from 0
+ *
+ * Suffixes start at chars, not bytes (a suffix starting inside a multi-byte char isn't a string).
+ * Lengths (lcp, ...) are counted in chars, positions handed out are byte offsets into the text.
+ *
+ * Substring queries: the suffixes starting with a pattern are a contiguous block of the array,
+ * found with two binary searches, O(pattern * log n).
+ */
+
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone)]
+pub struct SuffixArray<'a> {
+    text: &'a str,
+    chars: Vec<char>,
+    /// byte offset of every char, plus text.len() at the end
+    offsets: Vec<usize>,
+    /// char indices of the suffixes, in sorted order
+    suffixes: Vec<usize>,
+    /// lcp[i] = common prefix (in chars) of suffixes i and i + 1
+    lcp: Vec<usize>,
+}
+
+impl<'a> SuffixArray<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let chars = text.chars().collect::<Vec<_>>();
+        let offsets = text
+            .char_indices()
+            .map(|(offset, _)| offset)
+            .chain([text.len()])
+            .collect();
+        let suffixes = sort_suffixes(&chars);
+        let lcp = kasai(&chars, &suffixes);
+        SuffixArray {
+            text,
+            chars,
+            offsets,
+            suffixes,
+            lcp,
+        }
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// Byte offsets of the suffixes, in sorted order
+    pub fn suffixes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.suffixes.iter().map(|&i| self.offsets[i])
+    }
+
+    /// Common prefix lengths (in chars) of neighbouring suffixes
+    pub fn lcp(&self) -> &[usize] {
+        &self.lcp
+    }
+
+    // how the suffix at char index `start` compares to strings starting with `pattern`
+    fn compare(&self, start: usize, pattern: &[char]) -> Ordering {
+        let suffix = &self.chars[start..];
+        let prefix = &suffix[..suffix.len().min(pattern.len())];
+        prefix.cmp(pattern)
+    }
+
+    // positions in `suffixes` of the block of suffixes starting with `pattern`
+    fn block(&self, pattern: &str) -> std::ops::Range<usize> {
+        let pattern = pattern.chars().collect::<Vec<_>>();
+        let lo = self
+            .suffixes
+            .partition_point(|&s| self.compare(s, &pattern) == Ordering::Less);
+        let hi = self
+            .suffixes
+            .partition_point(|&s| self.compare(s, &pattern) != Ordering::Greater);
+        lo..hi
+    }
+
+    pub fn contains(&self, pattern: &str) -> bool {
+        !self.block(pattern).is_empty()
+    }
+
+    pub fn count(&self, pattern: &str) -> usize {
+        self.block(pattern).len()
+    }
+
+    /// Byte offsets of every occurrence of `pattern`, overlapping ones included, in text order
+    pub fn occurrences(&self, pattern: &str) -> Vec<usize> {
+        let mut found = self.suffixes[self.block(pattern)]
+            .iter()
+            .map(|&i| self.offsets[i])
+            .collect::<Vec<_>>();
+        found.sort();
+        found
+    }
+
+    /// Longest substring occurring at least twice (maybe overlapping), "" if there's none
+    pub fn longest_repeated(&self) -> &'a str {
+        let Some((i, &len)) = self.lcp.iter().enumerate().max_by_key(|(_, &len)| len) else {
+            return "";
+        };
+        let start = self.suffixes[i];
+        &self.text[self.offsets[start]..self.offsets[start + len]]
+    }
+
+    /// Number of different non-empty substrings: every suffix adds its prefixes, except the ones
+    /// it shares with the previous suffix
+    pub fn distinct_substrings(&self) -> usize {
+        let n = self.chars.len();
+        n * (n + 1) / 2 - self.lcp.iter().sum::<usize>()
+    }
+}
+
+fn sort_suffixes(chars: &[char]) -> Vec<usize> {
+    let n = chars.len();
+    let mut suffixes = (0..n).collect::<Vec<_>>();
+    let mut ranks = chars.iter().map(|&c| c as usize).collect::<Vec<_>>();
+    let mut next_ranks = vec![0; n];
+    if n <= 1 {
+        return suffixes;
+    }
+    let mut k = 1;
+    loop {
+        // a suffix shorter than k + 1 chars has nothing after its first k, which sorts first
+        let key = |i: usize| (ranks[i], ranks.get(i + k).map(|&r| r + 1).unwrap_or(0));
+        suffixes.sort_by_key(|&i| key(i));
+        next_ranks[suffixes[0]] = 0;
+        for w in 1..n {
+            let different = key(suffixes[w - 1]) != key(suffixes[w]);
+            next_ranks[suffixes[w]] = next_ranks[suffixes[w - 1]] + different as usize;
+        }
+        std::mem::swap(&mut ranks, &mut next_ranks);
+        if ranks[suffixes[n - 1]] == n - 1 {
+            break;
+        }
+        k *= 2;
+    }
+    suffixes
+}
+
+fn kasai(chars: &[char], suffixes: &[usize]) -> Vec<usize> {
+    let n = chars.len();
+    let mut rank = vec![0; n];
+    for (position, &suffix) in suffixes.iter().enumerate() {
+        rank[suffix] = position;
+    }
+    let mut lcp = vec![0; n.saturating_sub(1)];
+    let mut common = 0;
+    for start in 0..n {
+        if rank[start] + 1 == n {
+            common = 0;
+            continue;
+        }
+        let next = suffixes[rank[start] + 1];
+        while start + common < n
+            && next + common < n
+            && chars[start + common] == chars[next + common]
+        {
+            common += 1;
+        }
+        lcp[rank[start]] = common;
+        common = common.saturating_sub(1);
+    }
+    lcp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+    use std::collections::HashSet;
+
+    fn random_text(rng: &mut StdRng, max: usize) -> String {
+        let alphabet = ['a', 'b', 'ñ', 'ü', '中'];
+        let len = rng.gen_range(0..=max);
+        (0..len).map(|_| *alphabet.choose(rng).unwrap()).collect()
+    }
+
+    #[test]
+    fn matches_naive_sorting() {
+        let mut rng = StdRng::seed_from_u64(39);
+        for _ in 0..300 {
+            let text = random_text(&mut rng, 30);
+            let array = SuffixArray::new(&text);
+
+            let mut naive = text.char_indices().map(|(i, _)| i).collect::<Vec<_>>();
+            naive.sort_by_key(|&i| &text[i..]);
+            assert_eq!(array.suffixes().collect::<Vec<_>>(), naive);
+
+            let naive_lcp = naive
+                .windows(2)
+                .map(|w| {
+                    let (a, b) = (text[w[0]..].chars(), text[w[1]..].chars());
+                    a.zip(b).take_while(|(x, y)| x == y).count()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(array.lcp(), naive_lcp);
+
+            let substrings = text
+                .char_indices()
+                .flat_map(|(i, _)| {
+                    let text = &text;
+                    text[i..]
+                        .char_indices()
+                        .skip(1)
+                        .map(move |(j, _)| &text[i..i + j])
+                        .chain([&text[i..]])
+                })
+                .collect::<HashSet<_>>();
+            assert_eq!(array.distinct_substrings(), substrings.len());
+
+            let pattern = random_text(&mut rng, 3);
+            let occurrences = text
+                .char_indices()
+                .map(|(i, _)| i)
+                .filter(|&i| text[i..].starts_with(&pattern))
+                .collect::<Vec<_>>();
+            if !pattern.is_empty() {
+                assert_eq!(array.occurrences(&pattern), occurrences);
+                assert_eq!(array.contains(&pattern), !occurrences.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn banana() {
+        let array = SuffixArray::new("bananaß");
+        assert_eq!(array.count("ana"), 2);
+        assert_eq!(array.occurrences("ana"), [1, 3]);
+        assert_eq!(array.occurrences("aß"), [5]);
+        assert_eq!(array.longest_repeated(), "ana");
+        assert!(!array.contains("nab"));
+        assert_eq!(SuffixArray::new("").longest_repeated(), "");
+        assert_eq!(SuffixArray::new("ééé").longest_repeated(), "éé");
+    }
+}
@@ -0,0 +1,201 @@
+// == Trie (prefix tree) ==
+/* One node per distinct prefix, children keyed by the next char. The children are a BTreeMap,
+ * not an array of 26 (or a HashMap), so any char works and walking them goes in char order,
+ * which makes `with_prefix` yield words sorted.
+ *
+ * Nodes own their children (no indices or Rc), so removing a word can simply drop the branch
+ * that no other word needs anymore.
+ */
+
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    children: BTreeMap<char, Node>,
+    /// a word ends here
+    terminal: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Trie {
+    root: Node,
+    len: usize,
+}
+
+impl Trie {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn node(&self, prefix: &str) -> Option<&Node> {
+        prefix
+            .chars()
+            .try_fold(&self.root, |node, c| node.children.get(&c))
+    }
+
+    /// false if it was already there
+    pub fn insert(&mut self, word: &str) -> bool {
+        let node = word.chars().fold(&mut self.root, |node, c| {
+            node.children.entry(c).or_default()
+        });
+        let new = !node.terminal;
+        node.terminal = true;
+        self.len += new as usize;
+        new
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.node(word).is_some_and(|node| node.terminal)
+    }
+
+    /// Some word starts with `prefix`
+    pub fn starts_with(&self, prefix: &str) -> bool {
+        self.node(prefix).is_some()
+    }
+
+    /// false if it wasn't there. Nodes only used by this word are dropped
+    pub fn remove(&mut self, word: &str) -> bool {
+        // returns (removed, whether `node` is now useless and can be dropped by its parent)
+        fn remove(node: &mut Node, mut chars: std::str::Chars) -> (bool, bool) {
+            let removed = match chars.next() {
+                None => std::mem::replace(&mut node.terminal, false),
+                Some(c) => {
+                    let Some(child) = node.children.get_mut(&c) else {
+                        return (false, false);
+                    };
+                    let (removed, prune) = remove(child, chars);
+                    if prune {
+                        node.children.remove(&c);
+                    }
+                    removed
+                }
+            };
+            (removed, !node.terminal && node.children.is_empty())
+        }
+
+        let (removed, _) = remove(&mut self.root, word.chars());
+        self.len -= removed as usize;
+        removed
+    }
+
+    /// Every word starting with `prefix`, in char order
+    pub fn with_prefix(&self, prefix: &str) -> impl Iterator<Item = String> + '_ {
+        // preorder DFS, children pushed in reverse so that the smallest char comes out first
+        let mut stack = self
+            .node(prefix)
+            .map(|node| (node, prefix.to_string()))
+            .into_iter()
+            .collect::<Vec<_>>();
+        std::iter::from_fn(move || {
+            while let Some((node, word)) = stack.pop() {
+                for (c, child) in node.children.iter().rev() {
+                    let mut longer = word.clone();
+                    longer.push(*c);
+                    stack.push((child, longer));
+                }
+                if node.terminal {
+                    return Some(word);
+                }
+            }
+            None
+        })
+    }
+
+    /// Every word, in char order
+    pub fn iter(&self) -> impl Iterator<Item = String> + '_ {
+        self.with_prefix("")
+    }
+
+    /// The longest word that `text` starts with, as a slice of `text`
+    pub fn longest_prefix_of<'a>(&self, text: &'a str) -> Option<&'a str> {
+        let mut node = &self.root;
+        let mut longest = node.terminal.then_some(0);
+        for (offset, c) in text.char_indices() {
+            match node.children.get(&c) {
+                Some(child) => node = child,
+                None => break,
+            }
+            if node.terminal {
+                longest = Some(offset + c.len_utf8());
+            }
+        }
+        longest.map(|end| &text[..end])
+    }
+}
+
+impl<'a> FromIterator<&'a str> for Trie {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(words: I) -> Self {
+        let mut trie = Trie::new();
+        for word in words {
+            trie.insert(word);
+        }
+        trie
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn matches_btree_set_model() {
+        let mut rng = StdRng::seed_from_u64(39);
+        // multi-byte chars on purpose, 'é' is 2 bytes, '日' 3 and '🦀' 4
+        let alphabet = ['a', 'b', 'é', '日', '🦀'];
+        let word = |rng: &mut StdRng| {
+            let len = rng.gen_range(0..5);
+            (0..len)
+                .map(|_| *alphabet.choose(rng).unwrap())
+                .collect::<String>()
+        };
+        let mut trie = Trie::new();
+        let mut model = BTreeSet::new();
+        for _ in 0..3000 {
+            let w = word(&mut rng);
+            match rng.gen_range(0..4) {
+                0 | 1 => assert_eq!(trie.insert(&w), model.insert(w)),
+                2 => assert_eq!(trie.remove(&w), model.remove(&w)),
+                _ => {
+                    assert_eq!(trie.contains(&w), model.contains(&w));
+                    let expected = model.iter().filter(|m| m.starts_with(&w));
+                    assert_eq!(trie.starts_with(&w), expected.clone().count() > 0);
+                    // BTreeSet sorts by bytes, which for UTF-8 is the same as by chars
+                    assert!(trie.with_prefix(&w).eq(expected.cloned()));
+
+                    let text = format!("{w}{}", word(&mut rng));
+                    let longest = model
+                        .iter()
+                        .filter(|m| text.starts_with(m.as_str()))
+                        .max_by_key(|m| m.len());
+                    assert_eq!(trie.longest_prefix_of(&text), longest.map(|m| m.as_str()));
+                }
+            }
+            assert_eq!(trie.len(), model.len());
+        }
+        assert!(trie.iter().eq(model.into_iter()));
+    }
+
+    #[test]
+    fn removing_prunes_unused_branches() {
+        let mut trie = ["car", "cart", "日本", "日本語"]
+            .into_iter()
+            .collect::<Trie>();
+        assert!(trie.remove("cart"));
+        assert!(!trie.starts_with("cart") && trie.contains("car"));
+        assert!(trie.remove("日本"));
+        assert!(trie.starts_with("日本") && !trie.contains("日本"));
+        assert_eq!(trie.longest_prefix_of("日本語です"), Some("日本語"));
+        assert_eq!(trie.longest_prefix_of("日本"), None);
+        assert_eq!(trie.iter().collect::<Vec<_>>(), ["car", "日本語"]);
+    }
+}
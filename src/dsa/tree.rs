@@ -0,0 +1,600 @@
+// == Rooted trees ==
+/* A rooted tree over nodes 0..n with a value each, stored as index vectors (a parent and a list
+ * of children per node) rather than the Rc<TreeNode> / Weak parent of docs::smart_pointers:
+ * no RefCell to borrow, no reference counts, and a node is just a usize that can index side
+ * tables (depths, subtree sizes, ...).
+ *
+ *     let mut tree = Tree::new("root");
+ *     let a = tree.add_child(tree.root(), "a");
+ *     tree.add_child(a, "b");
+ *     tree.ancestors(2).map(|node| tree[node]).collect::<Vec<_>>();  // ["b", "a", "root"]
+ *
+ * It can be built from (and written back to) a parent array, where parents[v] is the parent of
+ * v and None for the root, or from an undirected edge list and a root.
+ *
+ * Traversals are iterative (a deep path would overflow the call stack with recursion), children
+ * are visited in order. In-order is for binary trees, generalized as: the first child's subtree,
+ * the node, then the other children's subtrees.
+ *
+ * - `Lca`: lowest common ancestor by binary lifting, up[j][v] is the 2^j-th ancestor of v, so
+ *   any ancestor is O(log n) jumps away. Built once in O(n log n), a snapshot of the tree.
+ * - `reroot`: makes another node the root, flipping the parent links on the path up to it.
+ * - `distance_sums`: the rerooting technique, an answer for every node as the root in O(n)
+ *   instead of n traversals: compute it for the root, then move the root to a neighbour and
+ *   patch the answer for what changed (only the edge between them did).
+ */
+
+use std::{
+    collections::VecDeque,
+    ops::{Index, IndexMut},
+};
+
+use anyhow::{bail, ensure};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tree<T> {
+    root: usize,
+    values: Vec<T>,
+    parents: Vec<Option<usize>>,
+    children: Vec<Vec<usize>>,
+}
+
+impl<T> Tree<T> {
+    /// A single node, 0, which is the root
+    pub fn new(root_value: T) -> Self {
+        Tree {
+            root: 0,
+            values: vec![root_value],
+            parents: vec![None],
+            children: vec![vec![]],
+        }
+    }
+
+    /// parents[v] is the parent of node v, None for the root (there must be exactly one).
+    /// Children are in increasing order
+    pub fn from_parents(values: Vec<T>, parents: &[Option<usize>]) -> anyhow::Result<Self> {
+        let n = values.len();
+        ensure!(
+            parents.len() == n,
+            "{} parents for {n} values",
+            parents.len()
+        );
+        let mut roots = (0..n).filter(|&v| parents[v].is_none());
+        let (Some(root), None) = (roots.next(), roots.next()) else {
+            bail!("a tree needs exactly one node without a parent");
+        };
+        let mut children = vec![vec![]; n];
+        for (child, parent) in parents.iter().enumerate() {
+            if let Some(parent) = *parent {
+                ensure!(parent < n, "parent {parent} of {child} out of bounds");
+                children[parent].push(child);
+            }
+        }
+        let tree = Tree {
+            root,
+            values,
+            parents: parents.to_vec(),
+            children,
+        };
+        // n - 1 parent links and everything reachable from the root, so no cycles
+        ensure!(
+            tree.preorder().count() == n,
+            "the parent links have a cycle"
+        );
+        Ok(tree)
+    }
+
+    /// Undirected edges between nodes 0..values.len(), they must connect everything without a
+    /// cycle. Children are in the order of the edges
+    pub fn from_edges(
+        values: Vec<T>,
+        root: usize,
+        edges: impl IntoIterator<Item = (usize, usize)>,
+    ) -> anyhow::Result<Self> {
+        let n = values.len();
+        ensure!(root < n, "root {root} out of bounds for {n} nodes");
+        let mut adjacency = vec![vec![]; n];
+        let mut edge_count = 0;
+        for (a, b) in edges {
+            ensure!(a < n && b < n, "edge {a} - {b} out of bounds for {n} nodes");
+            adjacency[a].push(b);
+            adjacency[b].push(a);
+            edge_count += 1;
+        }
+        ensure!(
+            edge_count + 1 == n,
+            "{edge_count} edges for {n} nodes, a tree has n - 1"
+        );
+
+        let mut parents = vec![None; n];
+        let mut children = vec![vec![]; n];
+        let mut seen = vec![false; n];
+        seen[root] = true;
+        let mut queue = VecDeque::from([root]);
+        while let Some(node) = queue.pop_front() {
+            for &next in &adjacency[node] {
+                if !seen[next] {
+                    seen[next] = true;
+                    parents[next] = Some(node);
+                    children[node].push(next);
+                    queue.push_back(next);
+                }
+            }
+        }
+        ensure!(
+            seen.iter().all(|&s| s),
+            "the edges don't connect every node"
+        );
+        Ok(Tree {
+            root,
+            values,
+            parents,
+            children,
+        })
+    }
+
+    /// parents[v] is the parent of v, None for the root
+    pub fn to_parents(&self) -> Vec<Option<usize>> {
+        self.parents.clone()
+    }
+
+    /// (parent, child) for every edge, in preorder of the children
+    pub fn to_edges(&self) -> Vec<(usize, usize)> {
+        self.preorder()
+            .filter_map(|node| Some((self.parents[node]?, node)))
+            .collect()
+    }
+
+    /// Adds a leaf under `parent`, returning it
+    pub fn add_child(&mut self, parent: usize, value: T) -> usize {
+        assert!(parent < self.len(), "node {parent} out of bounds");
+        let child = self.len();
+        self.values.push(value);
+        self.parents.push(Some(parent));
+        self.children.push(vec![]);
+        self.children[parent].push(child);
+        child
+    }
+
+    pub fn root(&self) -> usize {
+        self.root
+    }
+
+    /// Number of nodes, never 0
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    pub fn parent(&self, node: usize) -> Option<usize> {
+        self.parents[node]
+    }
+
+    pub fn children(&self, node: usize) -> &[usize] {
+        &self.children[node]
+    }
+
+    pub fn is_leaf(&self, node: usize) -> bool {
+        self.children[node].is_empty()
+    }
+
+    /// `node`, its parent, ... up to the root
+    pub fn ancestors(&self, node: usize) -> impl Iterator<Item = usize> + '_ {
+        std::iter::successors(Some(node), |&node| self.parents[node])
+    }
+
+    /// Number of edges from the root, O(depth)
+    pub fn depth(&self, node: usize) -> usize {
+        self.ancestors(node).count() - 1
+    }
+
+    /// The depth of every node
+    pub fn depths(&self) -> Vec<usize> {
+        let mut depths = vec![0; self.len()];
+        for (node, depth) in self.level_order() {
+            depths[node] = depth;
+        }
+        depths
+    }
+
+    /// Edges on the longest path from the root down to a leaf
+    pub fn height(&self) -> usize {
+        self.level_order()
+            .map(|(_, depth)| depth)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The height of every node's subtree (0 for leaves)
+    pub fn heights(&self) -> Vec<usize> {
+        let mut heights = vec![0; self.len()];
+        for node in self.postorder() {
+            if let Some(parent) = self.parents[node] {
+                heights[parent] = heights[parent].max(heights[node] + 1);
+            }
+        }
+        heights
+    }
+
+    /// The number of nodes in every node's subtree, itself included
+    pub fn subtree_sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![1; self.len()];
+        for node in self.postorder() {
+            if let Some(parent) = self.parents[node] {
+                sizes[parent] += sizes[node];
+            }
+        }
+        sizes
+    }
+
+    // == Traversals ==
+
+    /// Every node before its children
+    pub fn preorder(&self) -> impl Iterator<Item = usize> + '_ {
+        let mut stack = vec![self.root];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            // reversed, so that the first child is on top of the stack
+            stack.extend(self.children[node].iter().rev());
+            Some(node)
+        })
+    }
+
+    /// Every node after its children
+    pub fn postorder(&self) -> impl Iterator<Item = usize> + '_ {
+        // (node, how many of its children have been visited)
+        let mut stack = vec![(self.root, 0)];
+        std::iter::from_fn(move || loop {
+            let (node, visited) = stack.last_mut()?;
+            let node = *node;
+            match self.children[node].get(*visited) {
+                Some(&child) => {
+                    *visited += 1;
+                    stack.push((child, 0));
+                }
+                None => {
+                    stack.pop();
+                    return Some(node);
+                }
+            }
+        })
+    }
+
+    /// The first child's subtree, the node, then the other children's subtrees (left subtree,
+    /// node, right subtree for a binary tree)
+    pub fn inorder(&self) -> impl Iterator<Item = usize> + '_ {
+        // (node, how many of its children have been visited)
+        let mut stack = vec![(self.root, 0)];
+        std::iter::from_fn(move || loop {
+            let (node, visited) = stack.last_mut()?;
+            let node = *node;
+            let children = &self.children[node];
+            // the node itself goes right after its first child (or first thing for a leaf)
+            let yield_now = *visited == children.len().min(1);
+            let next = children.get(*visited).copied();
+            *visited += 1;
+            match next {
+                Some(child) => stack.push((child, 0)),
+                None => {
+                    stack.pop();
+                }
+            }
+            if yield_now {
+                return Some(node);
+            }
+        })
+    }
+
+    /// (node, depth), level by level, like Graph::bfs
+    pub fn level_order(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let mut queue = VecDeque::from([(self.root, 0)]);
+        std::iter::from_fn(move || {
+            let (node, depth) = queue.pop_front()?;
+            queue.extend(self.children[node].iter().map(|&child| (child, depth + 1)));
+            Some((node, depth))
+        })
+    }
+
+    // == Rerooting ==
+
+    /// Makes `node` the root: every parent link on the path from it to the old root is
+    /// flipped, the old parents becoming the last child of their old child
+    pub fn reroot(&mut self, node: usize) {
+        assert!(node < self.len(), "node {node} out of bounds");
+        let path = self.ancestors(node).collect::<Vec<_>>();
+        for pair in path.windows(2) {
+            let (child, parent) = (pair[0], pair[1]);
+            self.children[parent].retain(|&c| c != child);
+            self.children[child].push(parent);
+            self.parents[parent] = Some(child);
+        }
+        self.parents[node] = None;
+        self.root = node;
+    }
+
+    /// For every node, the sum of the distances (in edges) from it to every other node, O(n)
+    ///
+    /// Moving the root from a node to its child c brings the sizes[c] nodes under c one edge
+    /// closer, and the n - sizes[c] others one edge further
+    pub fn distance_sums(&self) -> Vec<usize> {
+        let n = self.len();
+        let sizes = self.subtree_sizes();
+        let mut sums = vec![0; n];
+        sums[self.root] = self.depths().iter().sum();
+        for node in self.preorder() {
+            if let Some(parent) = self.parents[node] {
+                sums[node] = sums[parent] + n - 2 * sizes[node];
+            }
+        }
+        sums
+    }
+}
+
+impl<T> Index<usize> for Tree<T> {
+    type Output = T;
+
+    fn index(&self, node: usize) -> &T {
+        &self.values[node]
+    }
+}
+
+impl<T> IndexMut<usize> for Tree<T> {
+    fn index_mut(&mut self, node: usize) -> &mut T {
+        &mut self.values[node]
+    }
+}
+
+// == Lowest common ancestor ==
+
+/// Ancestor queries by binary lifting, for the tree as it was when built
+#[derive(Debug, Clone)]
+pub struct Lca {
+    /// up[j][v] is the 2^j-th ancestor of v, the root for anything past it
+    up: Vec<Vec<usize>>,
+    depths: Vec<usize>,
+}
+
+impl Lca {
+    pub fn new<T>(tree: &Tree<T>) -> Self {
+        let n = tree.len();
+        let depths = tree.depths();
+        let levels = usize::BITS - n.leading_zeros();
+        let mut up = vec![(0..n)
+            .map(|v| tree.parent(v).unwrap_or(tree.root()))
+            .collect::<Vec<_>>()];
+        for j in 1..levels.max(1) as usize {
+            let half = &up[j - 1];
+            up.push((0..n).map(|v| half[half[v]]).collect());
+        }
+        Lca { up, depths }
+    }
+
+    pub fn depth(&self, node: usize) -> usize {
+        self.depths[node]
+    }
+
+    /// The ancestor `k` edges above `node` (itself for k = 0), None past the root
+    pub fn kth_ancestor(&self, mut node: usize, k: usize) -> Option<usize> {
+        if k > self.depths[node] {
+            return None;
+        }
+        for (j, up) in self.up.iter().enumerate() {
+            if k >> j & 1 == 1 {
+                node = up[node];
+            }
+        }
+        Some(node)
+    }
+
+    /// The deepest node that is an ancestor of both (a node is its own ancestor), O(log n)
+    pub fn lca(&self, a: usize, b: usize) -> usize {
+        let (mut a, mut b) = if self.depths[a] >= self.depths[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        a = self
+            .kth_ancestor(a, self.depths[a] - self.depths[b])
+            .expect("within depth");
+        if a == b {
+            return a;
+        }
+        // the largest jumps that keep them apart, they end up right below the answer
+        for up in self.up.iter().rev() {
+            if up[a] != up[b] {
+                (a, b) = (up[a], up[b]);
+            }
+        }
+        self.up[0][a]
+    }
+
+    /// Number of edges on the path between `a` and `b`
+    pub fn distance(&self, a: usize, b: usize) -> usize {
+        self.depths[a] + self.depths[b] - 2 * self.depths[self.lca(a, b)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsa::stress::{check, Config, Gen};
+
+    // node v + 1 hangs below parents[v] % (v + 1): shrinking the parents towards 0 flattens the
+    // tree into a star and dropping them removes nodes, every input is a valid tree
+    fn tree_from(parents: &[usize]) -> Tree<usize> {
+        let mut tree = Tree::new(0);
+        for (i, &parent) in parents.iter().enumerate() {
+            tree.add_child(parent % (i + 1), i + 1);
+        }
+        tree
+    }
+
+    // (parents, new root), see tree_from
+    fn rerooted_tree(g: &mut Gen, max_len: usize) -> (Vec<usize>, usize) {
+        (
+            g.vec(0..=max_len, |g| g.int(0..=max_len)),
+            g.int(0..=max_len),
+        )
+    }
+
+    fn rerooted((parents, root): &(Vec<usize>, usize)) -> Tree<usize> {
+        let mut tree = tree_from(parents);
+        tree.reroot(root % tree.len());
+        tree
+    }
+
+    // the recursive versions the iterators replace
+    fn recursive_orders(tree: &Tree<usize>, node: usize, orders: &mut [Vec<usize>; 3]) {
+        orders[0].push(node);
+        let children = tree.children(node);
+        for (i, &child) in children.iter().enumerate() {
+            recursive_orders(tree, child, orders);
+            if i == 0 {
+                orders[1].push(node);
+            }
+        }
+        if children.is_empty() {
+            orders[1].push(node);
+        }
+        orders[2].push(node);
+    }
+
+    #[test]
+    fn traversals_match_recursion() {
+        // -> (preorder, inorder, postorder, level_order, depths)
+        check(
+            "tree traversals",
+            Config::default(),
+            |g| rerooted_tree(g, 60),
+            |input| {
+                let tree = rerooted(input);
+                let mut orders = [vec![], vec![], vec![]];
+                recursive_orders(&tree, tree.root(), &mut orders);
+                let [pre, in_, post] = orders;
+                // both go through children in order, so a level is in preorder
+                let depth = |v: usize| tree.ancestors(v).count() - 1;
+                let mut levels = pre.iter().map(|&v| (v, depth(v))).collect::<Vec<_>>();
+                levels.sort_by_key(|&(_, d)| d);
+                let depths = (0..tree.len()).map(depth).collect::<Vec<_>>();
+                (pre, in_, post, levels, depths)
+            },
+            |input| {
+                let tree = rerooted(input);
+                let depths = tree.depths();
+                for (v, &d) in depths.iter().enumerate() {
+                    assert_eq!(tree.depth(v), d, "depth({v}) disagrees with depths()");
+                }
+                (
+                    tree.preorder().collect::<Vec<_>>(),
+                    tree.inorder().collect::<Vec<_>>(),
+                    tree.postorder().collect::<Vec<_>>(),
+                    tree.level_order().collect::<Vec<_>>(),
+                    depths,
+                )
+            },
+        );
+    }
+
+    // ((parents, root), (a, b, k) queries) -> per query (lca(a, b), kth ancestor of a), then per
+    // node its subtree size, height and sum of distances, and the height of the tree
+    type AncestorQueries = ((Vec<usize>, usize), Vec<(usize, usize, usize)>);
+    type AncestorAnswers = (
+        Vec<(usize, Option<usize>)>,
+        Vec<(usize, usize, usize)>,
+        usize,
+    );
+
+    #[test]
+    fn ancestors_match_walking_up() {
+        check(
+            "Lca, subtree sizes, heights and distance sums",
+            Config::default(),
+            |g| {
+                let queries = g.vec(0..=20, |g| (g.int(0..=80), g.int(0..=80), g.int(0..=80)));
+                (rerooted_tree(g, 80), queries)
+            },
+            |(input, queries): &AncestorQueries| -> AncestorAnswers {
+                let tree = rerooted(input);
+                let n = tree.len();
+                let depth = |v: usize| tree.ancestors(v).count() - 1;
+                let lca = |a: usize, b: usize| {
+                    let above_a = tree.ancestors(a).collect::<Vec<_>>();
+                    tree.ancestors(b).find(|v| above_a.contains(v)).unwrap()
+                };
+                let answers = queries.iter().map(|&(a, b, k)| {
+                    let (a, b) = (a % n, b % n);
+                    (lca(a, b), tree.ancestors(a).nth(k % n))
+                });
+                let nodes = (0..n).map(|v| {
+                    let below = (0..n).filter(|&u| tree.ancestors(u).any(|w| w == v));
+                    let height = below.clone().map(|u| depth(u) - depth(v)).max().unwrap();
+                    let distances = (0..n).map(|u| depth(u) + depth(v) - 2 * depth(lca(u, v)));
+                    (below.count(), height, distances.sum())
+                });
+                let nodes = nodes.collect::<Vec<_>>();
+                let height = nodes[tree.root()].1;
+                (answers.collect(), nodes, height)
+            },
+            |(input, queries): &AncestorQueries| -> AncestorAnswers {
+                let tree = rerooted(input);
+                let n = tree.len();
+                let lca = Lca::new(&tree);
+                let answers = queries.iter().map(|&(a, b, k)| {
+                    let (a, b) = (a % n, b % n);
+                    (lca.lca(a, b), lca.kth_ancestor(a, k % n))
+                });
+                let (sizes, heights, sums) =
+                    (tree.subtree_sizes(), tree.heights(), tree.distance_sums());
+                let nodes = (0..n).map(|v| (sizes[v], heights[v], sums[v]));
+                (answers.collect(), nodes.collect(), tree.height())
+            },
+        );
+    }
+
+    #[test]
+    fn parent_arrays_and_edge_lists() {
+        check(
+            "from_parents(to_parents) and from_edges(to_edges)",
+            Config::default(),
+            |g| g.vec(0..=30, |g| g.int(0..=30)),
+            |parents| {
+                let tree = tree_from(parents);
+                let parents = tree.to_parents();
+                (tree, parents)
+            },
+            |parents| {
+                let tree = tree_from(parents);
+                let values = (0..tree.len()).collect::<Vec<_>>();
+                let from_parents = Tree::from_parents(values.clone(), &tree.to_parents()).unwrap();
+                let from_edges = Tree::from_edges(values, tree.root(), tree.to_edges()).unwrap();
+                (from_parents, from_edges.to_parents())
+            },
+        );
+
+        // the smart_pointers tree, one-based there
+        let edges = [(1, 2), (1, 5), (2, 3), (2, 4), (5, 6)].map(|(a, b)| (a - 1, b - 1));
+        let mut tree = Tree::from_edges((1..=6).collect(), 0, edges).unwrap();
+        assert_eq!(
+            tree.ancestors(5).map(|v| tree[v]).collect::<Vec<_>>(),
+            [6, 5, 1]
+        );
+        tree.reroot(5);
+        assert_eq!(
+            tree.ancestors(2).map(|v| tree[v]).collect::<Vec<_>>(),
+            [3, 2, 1, 5, 6]
+        );
+        assert_eq!(tree.children(4), [0]);
+
+        let error = |result: anyhow::Result<Tree<()>>| result.unwrap_err().to_string();
+        assert!(error(Tree::from_parents(vec![(); 2], &[None, None])).contains("exactly one"));
+        let cycle = Tree::from_parents(vec![(); 3], &[None, Some(2), Some(1)]);
+        assert!(error(cycle).contains("cycle"));
+        let disconnected = Tree::from_edges(vec![(); 4], 0, [(0, 1), (1, 0), (2, 3)]);
+        assert!(error(disconnected).contains("don't connect"));
+        let extra = Tree::from_edges(vec![(); 3], 0, [(0, 1), (1, 2), (2, 0)]);
+        assert!(error(extra).contains("n - 1"));
+    }
+}
@@ -0,0 +1,3 @@
+pub mod dsa;
+pub mod shapes;
+pub mod testing;
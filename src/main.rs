@@ -1,26 +1,18 @@
 mod collections;
 mod docs;
-mod dsa;
-mod shapes;
+// dsa and shapes live in the library (src/lib.rs) so that the binaries in src/bin can share them
 /*
-use shapes::{collisions::Collidable, shape::Shape};
+use rust_typescript::shapes::scene_reader::{for_each_collision, SceneReader};
 
 fn check_collisions() {
-    let valid_shapes = std::fs::read_to_string("shapes_input")
+    // streams the file instead of read_to_string + collect, only the last shape stays in memory
+    let valid_shapes = SceneReader::open("shapes_input")
         .expect("Error reading the file shapes_input")
-        .lines()
-        .filter_map(|line| line.parse::<Shape>().ok())
-        .collect::<Vec<_>>();
+        .filter_map(|shape| shape.ok());
 
-    valid_shapes
-        .iter()
-        .skip(1)
-        .zip(valid_shapes.iter().take(valid_shapes.len() - 1))
-        .for_each(|(shape_x, shape_y)| {
-            if shape_x.collide(shape_y) {
-                println!("{} collides with {}", shape_x, shape_y);
-            }
-        });
+    for_each_collision(valid_shapes, 1, |(_, shape_x), (_, shape_y)| {
+        println!("{} collides with {}", shape_x, shape_y);
+    });
 }
 */
 
@@ -2,9 +2,12 @@ use std::{f32::consts::PI, fmt::Display, str::FromStr};
 
 use super::{
     area::Area,
-    collisions::{Contains, Points},
+    collisions::{Collidable, Contains, Points},
+    common::{parse_coordinate, parse_size},
+    rect::Rect,
 };
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Circle {
     pub x: f32,
     pub y: f32,
@@ -23,13 +26,15 @@ impl FromStr for Circle {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let parts = s.split(" ").collect::<Vec<_>>();
         if parts.len() != 3 {
-            return Err(anyhow::anyhow!("Badly formed circle"));
+            return Err(anyhow::anyhow!(
+                "Badly formed circle, expected `x y radius`"
+            ));
         }
 
         return Ok(Circle {
-            x: parts[0].parse()?,
-            y: parts[1].parse()?,
-            radius: parts[2].parse()?,
+            x: parse_coordinate(parts[0], "x")?,
+            y: parse_coordinate(parts[1], "y")?,
+            radius: parse_size(parts[2], "radius")?,
         });
     }
 }
@@ -41,6 +46,20 @@ impl Contains for Circle {
     }
 }
 
+impl Collidable<Circle> for Circle {
+    fn collide(&self, other: &Circle) -> bool {
+        let (dx, dy) = (self.x - other.x, self.y - other.y);
+        let reach = self.radius + other.radius;
+        dx * dx + dy * dy <= reach * reach
+    }
+}
+
+impl Collidable<Rect> for Circle {
+    fn collide(&self, other: &Rect) -> bool {
+        other.collide(self) // symmetric by construction
+    }
+}
+
 impl Points for Circle {
     fn get_points_iter(&self) -> super::collisions::PointsIter {
         return vec![(self.x, self.y)].into();
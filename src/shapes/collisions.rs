@@ -38,15 +38,4 @@ pub trait Contains {
     fn contains_point(&self, point: (f32, f32)) -> bool;
 }
 
-// implementing generics
-impl<V, T> Collidable<T> for V
-where
-    V: Contains,
-    T: Points,
-{
-    fn collide(&self, other: &T) -> bool {
-        return other
-            .get_points_iter()
-            .any(|point| self.contains_point(point));
-    }
-}
+// NOTE: there used to be a blanket Contains + Points impl here, it only checked corners and wasn't symmetric
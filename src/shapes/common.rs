@@ -1 +1,21 @@
 pub struct Point(f32, f32);
+
+// shared by the Rect and Circle parsers, so every shape rejects the same bad input
+pub fn parse_coordinate(part: &str, name: &str) -> anyhow::Result<f32> {
+    let value = part
+        .parse::<f32>()
+        .map_err(|_| anyhow::anyhow!("`{part}` isn't a valid {name}"))?;
+    if !value.is_finite() {
+        return Err(anyhow::anyhow!("{name} must be finite, got `{part}`"));
+    }
+    Ok(value)
+}
+
+// sizes can't be negative either, otherwise area() could be negative
+pub fn parse_size(part: &str, name: &str) -> anyhow::Result<f32> {
+    let value = parse_coordinate(part, name)?;
+    if value < 0.0 {
+        return Err(anyhow::anyhow!("{name} can't be negative, got `{part}`"));
+    }
+    Ok(value)
+}
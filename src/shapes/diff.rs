@@ -0,0 +1,572 @@
+// == Scene diff and three-way merge ==
+/* Scene files have no ids, so shapes are matched between two versions of a scene:
+ * 1. identical shapes are paired up first (a shape that didn't change, wherever it moved in the file),
+ * 2. the rest are paired greedily by kind and proximity of their anchor points (closest first),
+ * 3. whatever is left unpaired was removed (before) or added (after).
+ *
+ * The three-way merge diffs base->ours and base->theirs with the same matching and combines the
+ * changes per base shape, like `git merge-file` does per line.
+ */
+
+use std::fmt::Display;
+
+use super::shape::Shape;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiffOptions {
+    /// Shapes whose anchors are further apart than this are never considered the same shape
+    pub max_distance: f32,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions { max_distance: 5.0 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added {
+        after_index: usize,
+        shape: Shape,
+    },
+    Removed {
+        before_index: usize,
+        shape: Shape,
+    },
+    Modified {
+        before_index: usize,
+        after_index: usize,
+        before: Shape,
+        after: Shape,
+        moved: bool,
+        resized: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SceneDiff {
+    pub changes: Vec<Change>,
+    pub unchanged: usize,
+}
+
+impl SceneDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    fn count(&self, f: impl Fn(&Change) -> bool) -> usize {
+        self.changes.iter().filter(|change| f(change)).count()
+    }
+}
+
+fn distance((x1, y1): (f32, f32), (x2, y2): (f32, f32)) -> f32 {
+    ((x1 - x2) * (x1 - x2) + (y1 - y2) * (y1 - y2)).sqrt()
+}
+
+fn dimensions(shape: &Shape) -> Vec<f32> {
+    match shape {
+        Shape::Rect(r) => vec![r.height, r.width],
+        Shape::Circle(c) => vec![c.radius],
+    }
+}
+
+fn size_difference(a: &Shape, b: &Shape) -> f32 {
+    dimensions(a)
+        .iter()
+        .zip(dimensions(b).iter())
+        .map(|(x, y)| (x - y).abs())
+        .sum()
+}
+
+// before_to_after[i] = index in `after` of the shape matched with before[i], if any
+fn match_shapes(before: &[Shape], after: &[Shape], options: &DiffOptions) -> Vec<Option<usize>> {
+    let mut before_to_after = vec![None; before.len()];
+    let mut after_matched = vec![false; after.len()];
+
+    // 1. identical shapes, in file order
+    for (i, b) in before.iter().enumerate() {
+        if let Some(j) = (0..after.len()).find(|&j| !after_matched[j] && after[j] == *b) {
+            before_to_after[i] = Some(j);
+            after_matched[j] = true;
+        }
+    }
+
+    // 2. same kind and close enough, closest pairs first (then the least resized)
+    let mut candidates = vec![];
+    for (i, b) in before.iter().enumerate() {
+        if before_to_after[i].is_some() {
+            continue;
+        }
+        for (j, a) in after.iter().enumerate() {
+            if after_matched[j] || a.kind() != b.kind() {
+                continue;
+            }
+            let d = distance(b.position(), a.position());
+            if d <= options.max_distance {
+                candidates.push((d, size_difference(b, a), i, j));
+            }
+        }
+    }
+    candidates.sort_by(|x, y| x.0.total_cmp(&y.0).then(x.1.total_cmp(&y.1)));
+    for (_, _, i, j) in candidates {
+        if before_to_after[i].is_none() && !after_matched[j] {
+            before_to_after[i] = Some(j);
+            after_matched[j] = true;
+        }
+    }
+
+    before_to_after
+}
+
+pub fn diff(before: &[Shape], after: &[Shape], options: &DiffOptions) -> SceneDiff {
+    let before_to_after = match_shapes(before, after, options);
+    let mut result = SceneDiff::default();
+    let mut after_matched = vec![false; after.len()];
+
+    for (before_index, matched) in before_to_after.into_iter().enumerate() {
+        let before_shape = &before[before_index];
+        match matched {
+            None => result.changes.push(Change::Removed {
+                before_index,
+                shape: before_shape.clone(),
+            }),
+            Some(after_index) => {
+                after_matched[after_index] = true;
+                let after_shape = &after[after_index];
+                if before_shape == after_shape {
+                    result.unchanged += 1;
+                    continue;
+                }
+                result.changes.push(Change::Modified {
+                    before_index,
+                    after_index,
+                    before: before_shape.clone(),
+                    after: after_shape.clone(),
+                    moved: before_shape.position() != after_shape.position(),
+                    resized: dimensions(before_shape) != dimensions(after_shape),
+                });
+            }
+        }
+    }
+
+    for (after_index, shape) in after.iter().enumerate() {
+        if !after_matched[after_index] {
+            result.changes.push(Change::Added {
+                after_index,
+                shape: shape.clone(),
+            });
+        }
+    }
+    result
+}
+
+impl Display for SceneDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for change in &self.changes {
+            match change {
+                Change::Added { after_index, shape } => {
+                    writeln!(f, "+ [after #{after_index}] {shape}")?
+                }
+                Change::Removed {
+                    before_index,
+                    shape,
+                } => writeln!(f, "- [before #{before_index}] {shape}")?,
+                Change::Modified {
+                    before_index,
+                    after_index,
+                    before,
+                    after,
+                    moved,
+                    resized,
+                } => {
+                    let what = match (moved, resized) {
+                        (true, true) => "moved, resized",
+                        (true, false) => "moved",
+                        _ => "resized",
+                    };
+                    writeln!(
+                        f,
+                        "~ [#{before_index} -> #{after_index}] {what}: {before} -> {after}"
+                    )?
+                }
+            }
+        }
+        write!(
+            f,
+            "{} unchanged, {} added, {} removed, {} modified",
+            self.unchanged,
+            self.count(|c| matches!(c, Change::Added { .. })),
+            self.count(|c| matches!(c, Change::Removed { .. })),
+            self.count(|c| matches!(c, Change::Modified { .. })),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Ours,
+    Theirs,
+}
+
+impl Display for Side {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Side::Ours => write!(f, "ours"),
+            Side::Theirs => write!(f, "theirs"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Conflict {
+    /// Both sides modified the same base shape, differently
+    BothModified {
+        base_index: usize,
+        base: Shape,
+        ours: Shape,
+        theirs: Shape,
+    },
+    /// One side modified a shape the other side removed
+    ModifiedAndRemoved {
+        base_index: usize,
+        base: Shape,
+        modified: Shape,
+        removed_by: Side,
+    },
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Conflict::BothModified {
+                base_index,
+                base,
+                ours,
+                theirs,
+            } => write!(
+                f,
+                "conflict at base #{base_index} `{base}`: ours `{ours}`, theirs `{theirs}`"
+            ),
+            Conflict::ModifiedAndRemoved {
+                base_index,
+                base,
+                modified,
+                removed_by,
+            } => write!(
+                f,
+                "conflict at base #{base_index} `{base}`: removed by {removed_by}, modified to `{modified}`"
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MergeResult {
+    /// Conflicting shapes are kept as they were in base
+    pub merged: Vec<Shape>,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl MergeResult {
+    pub fn has_conflicts(&self) -> bool {
+        !self.conflicts.is_empty()
+    }
+}
+
+enum Fate<'a> {
+    Kept,
+    Modified(&'a Shape),
+    Removed,
+}
+
+fn fates<'a>(base: &[Shape], side: &'a [Shape], options: &DiffOptions) -> Vec<Fate<'a>> {
+    match_shapes(base, side, options)
+        .into_iter()
+        .enumerate()
+        .map(|(i, matched)| match matched {
+            None => Fate::Removed,
+            Some(j) if side[j] == base[i] => Fate::Kept,
+            Some(j) => Fate::Modified(&side[j]),
+        })
+        .collect()
+}
+
+fn added<'a>(base: &[Shape], side: &'a [Shape], options: &DiffOptions) -> Vec<&'a Shape> {
+    let mut matched = vec![false; side.len()];
+    match_shapes(base, side, options)
+        .into_iter()
+        .flatten()
+        .for_each(|j| matched[j] = true);
+    side.iter()
+        .zip(matched)
+        .filter(|(_, matched)| !matched)
+        .map(|(shape, _)| shape)
+        .collect()
+}
+
+pub fn merge(
+    base: &[Shape],
+    ours: &[Shape],
+    theirs: &[Shape],
+    options: &DiffOptions,
+) -> MergeResult {
+    let mut result = MergeResult::default();
+
+    let our_fates = fates(base, ours, options);
+    let their_fates = fates(base, theirs, options);
+
+    for (base_index, (our_fate, their_fate)) in our_fates.iter().zip(their_fates.iter()).enumerate()
+    {
+        let base_shape = &base[base_index];
+        match (our_fate, their_fate) {
+            (Fate::Kept, Fate::Kept) => result.merged.push(base_shape.clone()),
+            (Fate::Modified(changed), Fate::Kept) | (Fate::Kept, Fate::Modified(changed)) => {
+                result.merged.push((*changed).clone())
+            }
+            (Fate::Modified(o), Fate::Modified(t)) if o == t => result.merged.push((*o).clone()),
+            (Fate::Modified(o), Fate::Modified(t)) => {
+                result.merged.push(base_shape.clone());
+                result.conflicts.push(Conflict::BothModified {
+                    base_index,
+                    base: base_shape.clone(),
+                    ours: (*o).clone(),
+                    theirs: (*t).clone(),
+                });
+            }
+            (Fate::Removed, Fate::Modified(changed)) | (Fate::Modified(changed), Fate::Removed) => {
+                let removed_by = if matches!(our_fate, Fate::Removed) {
+                    Side::Ours
+                } else {
+                    Side::Theirs
+                };
+                result.merged.push(base_shape.clone());
+                result.conflicts.push(Conflict::ModifiedAndRemoved {
+                    base_index,
+                    base: base_shape.clone(),
+                    modified: (*changed).clone(),
+                    removed_by,
+                });
+            }
+            (Fate::Removed, _) | (_, Fate::Removed) => {}
+        }
+    }
+
+    let our_additions = added(base, ours, options);
+    let their_additions = added(base, theirs, options);
+    our_additions
+        .iter()
+        .for_each(|s| result.merged.push((*s).clone()));
+    // the same shape added on both sides is only added once
+    their_additions
+        .into_iter()
+        .filter(|s| !our_additions.contains(s))
+        .for_each(|s| result.merged.push(s.clone()));
+
+    result
+}
+
+// == JSON output ==
+// hand rolled (no serde in this crate), the values are only numbers and fixed keywords
+
+fn json_number(value: f32) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        String::from("null")
+    }
+}
+
+pub fn shape_to_json(shape: &Shape) -> String {
+    match shape {
+        Shape::Rect(r) => format!(
+            r#"{{"kind":"rect","x":{},"y":{},"height":{},"width":{}}}"#,
+            json_number(r.x),
+            json_number(r.y),
+            json_number(r.height),
+            json_number(r.width)
+        ),
+        Shape::Circle(c) => format!(
+            r#"{{"kind":"circle","x":{},"y":{},"radius":{}}}"#,
+            json_number(c.x),
+            json_number(c.y),
+            json_number(c.radius)
+        ),
+    }
+}
+
+impl SceneDiff {
+    pub fn to_json(&self) -> String {
+        let changes = self
+            .changes
+            .iter()
+            .map(|change| match change {
+                Change::Added { after_index, shape } => format!(
+                    r#"{{"change":"added","after_index":{after_index},"shape":{}}}"#,
+                    shape_to_json(shape)
+                ),
+                Change::Removed {
+                    before_index,
+                    shape,
+                } => format!(
+                    r#"{{"change":"removed","before_index":{before_index},"shape":{}}}"#,
+                    shape_to_json(shape)
+                ),
+                Change::Modified {
+                    before_index,
+                    after_index,
+                    before,
+                    after,
+                    moved,
+                    resized,
+                } => format!(
+                    r#"{{"change":"modified","before_index":{before_index},"after_index":{after_index},"moved":{moved},"resized":{resized},"before":{},"after":{}}}"#,
+                    shape_to_json(before),
+                    shape_to_json(after)
+                ),
+            })
+            .collect::<Vec<_>>();
+        format!(
+            r#"{{"unchanged":{},"changes":[{}]}}"#,
+            self.unchanged,
+            changes.join(",")
+        )
+    }
+}
+
+impl MergeResult {
+    pub fn to_json(&self) -> String {
+        let merged = self.merged.iter().map(shape_to_json).collect::<Vec<_>>();
+        let conflicts = self
+            .conflicts
+            .iter()
+            .map(|conflict| match conflict {
+                Conflict::BothModified {
+                    base_index,
+                    base,
+                    ours,
+                    theirs,
+                } => format!(
+                    r#"{{"conflict":"both_modified","base_index":{base_index},"base":{},"ours":{},"theirs":{}}}"#,
+                    shape_to_json(base),
+                    shape_to_json(ours),
+                    shape_to_json(theirs)
+                ),
+                Conflict::ModifiedAndRemoved {
+                    base_index,
+                    base,
+                    modified,
+                    removed_by,
+                } => format!(
+                    r#"{{"conflict":"modified_and_removed","base_index":{base_index},"removed_by":"{removed_by}","base":{},"modified":{}}}"#,
+                    shape_to_json(base),
+                    shape_to_json(modified)
+                ),
+            })
+            .collect::<Vec<_>>();
+        format!(
+            r#"{{"merged":[{}],"conflicts":[{}]}}"#,
+            merged.join(","),
+            conflicts.join(",")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scene(lines: &str) -> Vec<Shape> {
+        lines.lines().map(|line| line.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn reports_added_removed_and_modified() {
+        let before = scene("rect 1 2 3 4\ncircle 0 0 2\ncircle 10 10 1");
+        let after = scene("circle 0 0 2\nrect 2 2 3 5\ncircle 50 50 1");
+
+        let d = diff(&before, &after, &DiffOptions::default());
+        assert_eq!(d.unchanged, 1); // reordering alone isn't a change
+        assert_eq!(
+            d.changes,
+            vec![
+                Change::Modified {
+                    before_index: 0,
+                    after_index: 1,
+                    before: before[0].clone(),
+                    after: after[1].clone(),
+                    moved: true,
+                    resized: true,
+                },
+                Change::Removed {
+                    before_index: 2,
+                    shape: before[2].clone(),
+                },
+                Change::Added {
+                    after_index: 2,
+                    shape: after[2].clone(),
+                },
+            ]
+        );
+        assert_eq!(
+            d.to_string().lines().last(),
+            Some("1 unchanged, 1 added, 1 removed, 1 modified")
+        );
+    }
+
+    #[test]
+    fn matches_closest_shape_of_the_same_kind() {
+        let before = scene("circle 0 0 1\ncircle 3 0 1");
+        let after = scene("rect 3 0 1 1\ncircle 3.5 0 1\ncircle 0.5 0 1");
+
+        let d = diff(&before, &after, &DiffOptions::default());
+        let moved = d
+            .changes
+            .iter()
+            .filter_map(|c| match c {
+                Change::Modified {
+                    before_index,
+                    after_index,
+                    moved: true,
+                    resized: false,
+                    ..
+                } => Some((*before_index, *after_index)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(moved, vec![(0, 2), (1, 1)]);
+    }
+
+    #[test]
+    fn merges_independent_changes_and_flags_conflicts() {
+        let base = scene("rect 0 0 1 1\ncircle 10 10 2\ncircle 20 20 2\nrect 30 30 1 1");
+        let ours = scene("rect 0 0 2 2\ncircle 10 10 3\ncircle 20 20 2\ncircle 5 5 5");
+        let theirs = scene("rect 0 0 1 1\ncircle 10 10 4\ncircle 21 20 2\ncircle 5 5 5");
+
+        let result = merge(&base, &ours, &theirs, &DiffOptions::default());
+        assert_eq!(
+            result.merged,
+            scene("rect 0 0 2 2\ncircle 10 10 2\ncircle 21 20 2\ncircle 5 5 5")
+        );
+        assert_eq!(
+            result.conflicts,
+            vec![Conflict::BothModified {
+                base_index: 1,
+                base: base[1].clone(),
+                ours: ours[1].clone(),
+                theirs: theirs[1].clone(),
+            }]
+        );
+    }
+
+    #[test]
+    fn modified_and_removed_is_a_conflict() {
+        let base = scene("circle 0 0 1");
+        let result = merge(&base, &[], &scene("circle 0 0 2"), &DiffOptions::default());
+        assert_eq!(result.merged, base);
+        assert_eq!(
+            result.to_json(),
+            r#"{"merged":[{"kind":"circle","x":0,"y":0,"radius":1}],"conflicts":[{"conflict":"modified_and_removed","base_index":0,"removed_by":"ours","base":{"kind":"circle","x":0,"y":0,"radius":1},"modified":{"kind":"circle","x":0,"y":0,"radius":2}}]}"#
+        );
+    }
+}
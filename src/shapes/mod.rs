@@ -2,5 +2,10 @@ pub mod area;
 pub mod circle;
 pub mod collisions;
 pub mod common;
+pub mod diff;
+pub mod raster;
 pub mod rect;
+pub mod repl;
+pub mod scene_reader;
 pub mod shape;
+pub mod testing;
@@ -0,0 +1,483 @@
+// == Rasterising scenes into tile maps (and back) ==
+/* A Grid maps scene coordinates to cells: cell (col, row) covers
+ * [origin.x + col * cell_size, origin.x + (col + 1) * cell_size) horizontally, and the same
+ * vertically for rows. Row 0 is the first row of the image, i.e. y grows downwards like in the
+ * PGM/PBM files (and in most tile based games).
+ *
+ * - rasterize: Shape -> Bitmap, a cell is covered if shapes contain its center (Coverage::Binary)
+ *   or by the fraction of samples contained in any shape (Coverage::AntiAliased)
+ * - vectorize: Bitmap -> Vec<Rect>, greedy meshing of the covered cells into as few rects as it
+ *   finds by growing each rect right first and then down
+ */
+
+use std::io::{Read, Write};
+
+use super::{collisions::Contains, rect::Rect, shape::Shape};
+
+/// Upper bound on `width * height` for `Grid::fit`, 64M cells is already a 64 MiB bitmap
+pub const MAX_CELLS: usize = 1 << 26;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Grid {
+    pub origin: (f32, f32),
+    pub cell_size: f32,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Grid {
+    /// Smallest grid with `cell_size` cells (aligned to multiples of `cell_size`) that covers
+    /// every shape of the scene
+    ///
+    /// Errors if `cell_size` isn't a positive finite number, or the grid would need more than
+    /// `MAX_CELLS` cells (shapes far apart, huge or not finite)
+    pub fn fit(shapes: &[Shape], cell_size: f32) -> anyhow::Result<Grid> {
+        if !(cell_size.is_finite() && cell_size > 0.0) {
+            return Err(anyhow::anyhow!(
+                "cell size must be positive and finite, got {cell_size}"
+            ));
+        }
+        let (min_x, min_y, max_x, max_y) = shapes.iter().map(Shape::bounds).fold(
+            (f32::MAX, f32::MAX, f32::MIN, f32::MIN),
+            |(a, b, c, d), (x1, y1, x2, y2)| (a.min(x1), b.min(y1), c.max(x2), d.max(y2)),
+        );
+        if shapes.is_empty() {
+            return Ok(Grid {
+                origin: (0.0, 0.0),
+                cell_size,
+                width: 0,
+                height: 0,
+            });
+        }
+        let origin = (
+            (min_x / cell_size).floor() * cell_size,
+            (min_y / cell_size).floor() * cell_size,
+        );
+        // in f64 before casting, `as usize` saturates infinities and turns NaN into 0
+        let width = ((max_x - origin.0) as f64 / cell_size as f64).ceil();
+        let height = ((max_y - origin.1) as f64 / cell_size as f64).ceil();
+        // written so that NaN fails it too
+        if !(width * height <= MAX_CELLS as f64 && width <= MAX_CELLS as f64) {
+            return Err(anyhow::anyhow!(
+                "a {width}x{height} grid is too large, cells can't be more than {MAX_CELLS}"
+            ));
+        }
+        Ok(Grid {
+            origin,
+            cell_size,
+            width: width as usize,
+            height: height as usize,
+        })
+    }
+
+    /// Scene coordinates of the point at fraction (fx, fy) of cell (col, row), (0.5, 0.5) is its center
+    fn point_in_cell(&self, col: usize, row: usize, (fx, fy): (f32, f32)) -> (f32, f32) {
+        (
+            self.origin.0 + (col as f32 + fx) * self.cell_size,
+            self.origin.1 + (row as f32 + fy) * self.cell_size,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Coverage {
+    Binary,
+    /// `samples` x `samples` points per cell
+    AntiAliased {
+        samples: usize,
+    },
+}
+
+/// Row-major 8-bit coverage map, 0 is empty and 255 fully covered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bitmap {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Bitmap {
+    /// Panics if `width * height` overflows usize
+    pub fn new(width: usize, height: usize) -> Self {
+        let size = width
+            .checked_mul(height)
+            .unwrap_or_else(|| panic!("a {width}x{height} bitmap doesn't fit in memory"));
+        Bitmap {
+            width,
+            height,
+            pixels: vec![0; size],
+        }
+    }
+
+    pub fn get(&self, col: usize, row: usize) -> u8 {
+        self.pixels[row * self.width + col]
+    }
+
+    pub fn set(&mut self, col: usize, row: usize, value: u8) {
+        self.pixels[row * self.width + col] = value;
+    }
+}
+
+pub fn rasterize(shapes: &[Shape], grid: &Grid, coverage: Coverage) -> Bitmap {
+    let offsets = match coverage {
+        Coverage::Binary => vec![(0.5, 0.5)],
+        Coverage::AntiAliased { samples } => {
+            let samples = samples.max(1);
+            (0..samples * samples)
+                .map(|i| {
+                    (
+                        ((i % samples) as f32 + 0.5) / samples as f32,
+                        ((i / samples) as f32 + 0.5) / samples as f32,
+                    )
+                })
+                .collect()
+        }
+    };
+
+    let mut bitmap = Bitmap::new(grid.width, grid.height);
+    for row in 0..grid.height {
+        for col in 0..grid.width {
+            let covered = offsets
+                .iter()
+                .filter(|&&offset| {
+                    let point = grid.point_in_cell(col, row, offset);
+                    shapes.iter().any(|shape| shape.contains_point(point))
+                })
+                .count();
+            let value = (covered * 255 + offsets.len() / 2) / offsets.len();
+            bitmap.set(col, row, value as u8);
+        }
+    }
+    bitmap
+}
+
+/// Greedy meshing of the cells with coverage >= `threshold` into rects, in scene coordinates
+pub fn vectorize(bitmap: &Bitmap, grid: &Grid, threshold: u8) -> Vec<Rect> {
+    let filled = |col: usize, row: usize| bitmap.get(col, row) >= threshold;
+    let mut meshed = vec![false; bitmap.width * bitmap.height];
+    let mut rects = vec![];
+
+    for row in 0..bitmap.height {
+        for col in 0..bitmap.width {
+            if meshed[row * bitmap.width + col] || !filled(col, row) {
+                continue;
+            }
+            let free = |c: usize, r: usize| !meshed[r * bitmap.width + c] && filled(c, r);
+
+            let mut width = 1;
+            while col + width < bitmap.width && free(col + width, row) {
+                width += 1;
+            }
+            let mut height = 1;
+            while row + height < bitmap.height && (col..col + width).all(|c| free(c, row + height))
+            {
+                height += 1;
+            }
+
+            for r in row..row + height {
+                for c in col..col + width {
+                    meshed[r * bitmap.width + c] = true;
+                }
+            }
+            let (x, y) = grid.point_in_cell(col, row, (0.0, 0.0));
+            rects.push(Rect {
+                x,
+                y,
+                height: height as f32 * grid.cell_size,
+                width: width as f32 * grid.cell_size,
+            });
+        }
+    }
+    rects
+}
+
+// == PGM / PBM (netpbm) ==
+/* P5 (binary PGM) keeps the coverage, P4 (binary PBM) keeps cells >= threshold as black (1).
+ * Reading accepts P1/P2 (plain) and P4/P5 (binary), with `#` comments in the header, and
+ * scales greymaps to 0..=255.
+ */
+
+impl Bitmap {
+    pub fn write_pgm(&self, mut out: impl Write) -> std::io::Result<()> {
+        write!(out, "P5\n{} {}\n255\n", self.width, self.height)?;
+        out.write_all(&self.pixels)
+    }
+
+    pub fn write_pbm(&self, mut out: impl Write, threshold: u8) -> std::io::Result<()> {
+        write!(out, "P4\n{} {}\n", self.width, self.height)?;
+        for row in self.pixels.chunks(self.width.max(1)).take(self.height) {
+            // each row is padded to whole bytes, most significant bit first
+            let mut packed = vec![0u8; self.width.div_ceil(8)];
+            row.iter()
+                .enumerate()
+                .filter(|(_, &value)| value >= threshold)
+                .for_each(|(col, _)| packed[col / 8] |= 0x80 >> (col % 8));
+            out.write_all(&packed)?;
+        }
+        Ok(())
+    }
+
+    pub fn read_pnm(mut input: impl Read) -> anyhow::Result<Bitmap> {
+        let mut data = vec![];
+        input.read_to_end(&mut data)?;
+        let mut header = PnmHeader { data: &data, at: 0 };
+
+        let magic = header.token()?;
+        let (width, height) = (header.number()?, header.number()?);
+        let max_value = match magic.as_str() {
+            "P2" | "P5" => header.number()?,
+            "P1" | "P4" => 1,
+            _ => return Err(anyhow::anyhow!("not a PBM/PGM file (magic `{magic}`)")),
+        };
+        if max_value == 0 || max_value > 255 {
+            return Err(anyhow::anyhow!("unsupported maxval {max_value}"));
+        }
+        let scale = |value: usize| -> anyhow::Result<u8> {
+            if value > max_value {
+                return Err(anyhow::anyhow!(
+                    "sample {value} is above maxval {max_value}"
+                ));
+            }
+            Ok(((value * 255 + max_value / 2) / max_value) as u8)
+        };
+
+        // the header is untrusted, so check the size against the data before allocating: every
+        // format needs at least one byte per sample, except P4 which packs 8 per byte
+        let too_large = || anyhow::anyhow!("image size {width}x{height} is too large");
+        let sample_count = width.checked_mul(height).ok_or_else(too_large)?;
+        let min_bytes = match magic.as_str() {
+            "P4" => width
+                .div_ceil(8)
+                .checked_mul(height)
+                .ok_or_else(too_large)?,
+            _ => sample_count,
+        };
+        let body_len = data.len().saturating_sub(header.at + 1);
+        if body_len < min_bytes {
+            return Err(anyhow::anyhow!(
+                "truncated image data, a {width}x{height} image needs at least {min_bytes} bytes, \
+                 found {body_len}"
+            ));
+        }
+
+        let mut bitmap = Bitmap::new(width, height);
+        match magic.as_str() {
+            // plain formats, whitespace separated samples (P1 doesn't even need the whitespace)
+            "P1" => {
+                let bits = data[header.at..]
+                    .iter()
+                    .filter(|b| matches!(b, b'0' | b'1'))
+                    .map(|b| if *b == b'1' { 255 } else { 0 });
+                fill(&mut bitmap, bits)?;
+            }
+            "P2" => {
+                let samples = (0..sample_count)
+                    .map(|_| header.number().and_then(scale))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                fill(&mut bitmap, samples.into_iter())?;
+            }
+            // binary formats, exactly one whitespace byte after the header
+            "P4" => {
+                let body = data.get(header.at + 1..).unwrap_or_default();
+                let row_bytes = width.div_ceil(8);
+                for row in 0..height {
+                    for col in 0..width {
+                        let bit = body[row * row_bytes + col / 8] & (0x80 >> (col % 8));
+                        bitmap.set(col, row, if bit != 0 { 255 } else { 0 });
+                    }
+                }
+            }
+            _ => {
+                let body = data.get(header.at + 1..).unwrap_or_default();
+                let samples = body
+                    .iter()
+                    .map(|&value| scale(value as usize))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                fill(&mut bitmap, samples.into_iter())?;
+            }
+        }
+        Ok(bitmap)
+    }
+}
+
+fn fill(bitmap: &mut Bitmap, values: impl Iterator<Item = u8>) -> anyhow::Result<()> {
+    let expected = bitmap.pixels.len();
+    let mut count = 0;
+    bitmap
+        .pixels
+        .iter_mut()
+        .zip(values)
+        .for_each(|(pixel, value)| {
+            *pixel = value;
+            count += 1;
+        });
+    if count < expected {
+        return Err(anyhow::anyhow!(
+            "truncated image data, {count} of {expected} samples"
+        ));
+    }
+    Ok(())
+}
+
+struct PnmHeader<'a> {
+    data: &'a [u8],
+    at: usize,
+}
+
+impl PnmHeader<'_> {
+    fn token(&mut self) -> anyhow::Result<String> {
+        loop {
+            match self.data.get(self.at) {
+                Some(b'#') => {
+                    while !matches!(self.data.get(self.at), Some(b'\n') | None) {
+                        self.at += 1;
+                    }
+                }
+                Some(b) if b.is_ascii_whitespace() => self.at += 1,
+                Some(_) => break,
+                None => return Err(anyhow::anyhow!("unexpected end of PBM/PGM header")),
+            }
+        }
+        let start = self.at;
+        while matches!(self.data.get(self.at), Some(b) if !b.is_ascii_whitespace() && *b != b'#') {
+            self.at += 1;
+        }
+        Ok(String::from_utf8_lossy(&self.data[start..self.at]).into_owned())
+    }
+
+    fn number(&mut self) -> anyhow::Result<usize> {
+        let token = self.token()?;
+        token
+            .parse()
+            .map_err(|_| anyhow::anyhow!("expected a number in PBM/PGM data, found `{token}`"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scene(lines: &str) -> Vec<Shape> {
+        lines.lines().map(|line| line.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn rasterizes_rect_and_circle() {
+        let shapes = scene("rect 0 0 2 3\ncircle 6 1 1");
+        let grid = Grid::fit(&shapes, 1.0).unwrap();
+        assert_eq!((grid.origin, grid.width, grid.height), ((0.0, 0.0), 7, 2));
+
+        let bitmap = rasterize(&shapes, &grid, Coverage::Binary);
+        let rows = bitmap
+            .pixels
+            .chunks(bitmap.width)
+            .map(|row| {
+                row.iter()
+                    .map(|&v| if v == 255 { '#' } else { '.' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(rows, vec!["###..##", "###..##"]);
+    }
+
+    #[test]
+    fn anti_aliasing_gives_partial_coverage() {
+        let shapes = scene("rect 0 0 1 0.5");
+        let grid = Grid {
+            origin: (0.0, 0.0),
+            cell_size: 1.0,
+            width: 1,
+            height: 1,
+        };
+        let bitmap = rasterize(&shapes, &grid, Coverage::AntiAliased { samples: 4 });
+        assert_eq!(bitmap.pixels, vec![128]); // half of the cell, rounded
+    }
+
+    #[test]
+    fn vectorize_merges_cells_greedily() {
+        let shapes = scene("rect 0 0 1 2\nrect 2 0 1 2\nrect 0 1 2 1");
+        let grid = Grid::fit(&shapes, 1.0).unwrap();
+        let bitmap = rasterize(&shapes, &grid, Coverage::Binary);
+
+        let rects = vectorize(&bitmap, &grid, 128);
+        assert_eq!(
+            rects
+                .iter()
+                .map(|r| (r.x, r.y, r.height, r.width))
+                .collect::<Vec<_>>(),
+            vec![(0.0, 0.0, 1.0, 4.0), (0.0, 1.0, 2.0, 1.0)]
+        );
+        let remeshed = rects.into_iter().map(Shape::Rect).collect::<Vec<_>>();
+        assert_eq!(rasterize(&remeshed, &grid, Coverage::Binary), bitmap);
+    }
+
+    #[test]
+    fn pgm_and_pbm_round_trip() {
+        let bitmap = Bitmap {
+            width: 10,
+            height: 2,
+            pixels: (0..20).map(|i| (i * 13) as u8).collect(),
+        };
+
+        let mut pgm = vec![];
+        bitmap.write_pgm(&mut pgm).unwrap();
+        assert_eq!(Bitmap::read_pnm(pgm.as_slice()).unwrap(), bitmap);
+
+        let mut pbm = vec![];
+        bitmap.write_pbm(&mut pbm, 128).unwrap();
+        let thresholded = Bitmap::read_pnm(pbm.as_slice()).unwrap();
+        assert_eq!(
+            thresholded.pixels,
+            bitmap
+                .pixels
+                .iter()
+                .map(|&v| if v >= 128 { 255 } else { 0 })
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn reads_plain_formats_with_comments() {
+        let plain_pgm = "P2\n# painted by hand\n3 1\n4\n0 2 4\n";
+        assert_eq!(
+            Bitmap::read_pnm(plain_pgm.as_bytes()).unwrap().pixels,
+            vec![0, 128, 255]
+        );
+        let plain_pbm = "P1 3 2 101\n010";
+        assert_eq!(
+            Bitmap::read_pnm(plain_pbm.as_bytes()).unwrap().pixels,
+            vec![255, 0, 255, 0, 255, 0]
+        );
+        assert!(Bitmap::read_pnm("P2 2 1 4 0".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn rejects_sizes_the_data_cant_hold() {
+        let huge = format!("P5\n{} {}\n255\n\0\0", usize::MAX, 2);
+        assert!(Bitmap::read_pnm(huge.as_bytes()).is_err());
+        let large = "P5\n100000 100000\n255\n\0\0\0";
+        assert!(Bitmap::read_pnm(large.as_bytes()).is_err());
+        let wide = format!("P4\n{} 3\n\0", usize::MAX);
+        assert!(Bitmap::read_pnm(wide.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn fit_rejects_bad_cell_sizes_and_huge_scenes() {
+        assert!(Grid::fit(&[], 0.0).is_err());
+        assert!(Grid::fit(&[], f32::NAN).is_err());
+        // regression: the extent used to saturate to usize::MAX cells (or overflow to 0 cells)
+        let far_apart = scene("circle -3e38 0 1\ncircle 3e38 0 1");
+        assert!(Grid::fit(&far_apart, 1.0).is_err());
+        let huge = scene("rect 0 0 3e38 3e38");
+        assert!(Grid::fit(&huge, 1.0).is_err());
+        let big = scene("rect 0 0 10000 10000");
+        assert!(Grid::fit(&big, 1.0).is_err());
+        assert_eq!(Grid::fit(&big, 10.0).unwrap().width, 1000);
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't fit in memory")]
+    fn bitmap_size_overflow_panics() {
+        Bitmap::new(usize::MAX, 2);
+    }
+}
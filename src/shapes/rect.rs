@@ -1,9 +1,12 @@
 use super::{
     area::Area,
-    collisions::{Contains, Points, PointsIter},
+    circle::Circle,
+    collisions::{Collidable, Contains, Points, PointsIter},
+    common::{parse_coordinate, parse_size},
 };
 use std::{fmt::Display, str::FromStr};
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Rect {
     pub x: f32,
     pub y: f32,
@@ -17,21 +20,45 @@ impl FromStr for Rect {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let parts = s.split(" ").collect::<Vec<_>>();
         if parts.len() != 4 {
-            return Err(anyhow::anyhow!("Badly formed rectangle"));
+            return Err(anyhow::anyhow!(
+                "Badly formed rectangle, expected `x y height width`"
+            ));
         }
 
         return Ok(Rect {
-            x: parts[0].parse()?,
-            y: parts[1].parse()?,
-            height: parts[2].parse()?,
-            width: parts[3].parse()?,
+            x: parse_coordinate(parts[0], "x")?,
+            y: parse_coordinate(parts[1], "y")?,
+            height: parse_size(parts[2], "height")?,
+            width: parse_size(parts[3], "width")?,
         });
     }
 }
 
 impl Contains for Rect {
     fn contains_point(&self, (x, y): (f32, f32)) -> bool {
-        return self.x <= x && self.width + self.x >= x && self.y <= y && self.height + self.y <= y;
+        return self.x <= x && self.width + self.x >= x && self.y <= y && self.height + self.y >= y;
+    }
+}
+
+// overlapping (closed) intervals on both axes
+impl Collidable<Rect> for Rect {
+    fn collide(&self, other: &Rect) -> bool {
+        self.x <= other.x + other.width
+            && other.x <= self.x + self.width
+            && self.y <= other.y + other.height
+            && other.y <= self.y + self.height
+    }
+}
+
+// the point of the rect closest to the circle's center has to be inside the circle
+// NOTE: max/min instead of clamp, clamp panics on a negative size or NaN and the fields are pub
+impl Collidable<Circle> for Rect {
+    fn collide(&self, other: &Circle) -> bool {
+        let closest = (
+            other.x.max(self.x).min(self.x + self.width),
+            other.y.max(self.y).min(self.y + self.height),
+        );
+        other.contains_point(closest)
     }
 }
 
@@ -0,0 +1,291 @@
+// == Shapes REPL ==
+/* The command interpreter behind the `shapes` binary. It only turns a line of input into a reply
+ * (or an error), the binary owns stdin/stdout, so a whole session can be replayed in tests.
+ *
+ * Shapes are numbered from 1 in every command, like `list` shows them.
+ */
+
+use std::{fs::File, io::Write};
+
+use anyhow::Context;
+
+use super::{
+    area::Area, collisions::Collidable, common::parse_coordinate, scene_reader::SceneReader,
+    shape::Shape,
+};
+
+pub const HELP: &str = "commands:
+  rect <x> <y> <height> <width>   add a rectangle
+  circle <x> <y> <radius>         add a circle
+  list                            show the scene
+  area <n>                        area of shape n
+  collide <n> <m>                 do shapes n and m collide?
+  move <n> <x> <y>                move shape n to (x, y)
+  remove <n>                      remove shape n
+  clear                           remove all shapes
+  save <file> / load <file>       write / replace the scene (same format as shapes_input)
+  history                         show previous commands, `!n` reruns command n, `!!` the last one
+  help                            show this message
+  quit                            leave";
+
+#[derive(Default)]
+pub struct Repl {
+    shapes: Vec<Shape>,
+    history: Vec<String>,
+    finished: bool,
+}
+
+impl Repl {
+    pub fn new() -> Self {
+        Repl::default()
+    }
+
+    pub fn shapes(&self) -> &[Shape] {
+        &self.shapes
+    }
+
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// Whether `quit` has been run, the caller should stop feeding lines after that
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Runs one line of input, and records it in the history (`!n` is recorded as the command it
+    /// expanded to, and `history` itself isn't recorded)
+    pub fn execute(&mut self, line: &str) -> anyhow::Result<String> {
+        let line = line.trim();
+        let line = match line.strip_prefix('!') {
+            Some(reference) => self.expand_history(reference)?,
+            None => line.to_string(),
+        };
+        if line.is_empty() {
+            return Ok(String::new());
+        }
+        if line != "history" {
+            self.history.push(line.clone());
+        }
+        self.run(&line)
+    }
+
+    fn expand_history(&self, reference: &str) -> anyhow::Result<String> {
+        let found = if reference == "!" {
+            self.history.last()
+        } else {
+            let n = reference
+                .parse::<usize>()
+                .map_err(|_| anyhow::anyhow!("`!{reference}`: expected `!!` or `!<n>`"))?;
+            n.checked_sub(1).and_then(|i| self.history.get(i))
+        };
+        found
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("`!{reference}`: no such command in history"))
+    }
+
+    fn run(&mut self, line: &str) -> anyhow::Result<String> {
+        let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        // `rest` is kept as typed for the commands taking a path, which may contain spaces
+        let rest = rest.trim();
+        let args = rest.split_whitespace().collect::<Vec<_>>();
+
+        match command {
+            "rect" | "circle" => {
+                // reuse the scene file parsers, the REPL syntax is the file syntax
+                let shape = format!("{command} {}", args.join(" "))
+                    .parse::<Shape>()
+                    .with_context(|| format!("couldn't add `{line}`"))?;
+                self.shapes.push(shape);
+                Ok(format!(
+                    "added #{}: {}",
+                    self.shapes.len(),
+                    self.shapes.last().unwrap()
+                ))
+            }
+            "list" => {
+                expect_args(command, &args, 0, "list")?;
+                if self.shapes.is_empty() {
+                    return Ok(String::from("the scene is empty"));
+                }
+                Ok(self
+                    .shapes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, shape)| format!("{}: {}", i + 1, shape))
+                    .collect::<Vec<_>>()
+                    .join("\n"))
+            }
+            "area" => {
+                expect_args(command, &args, 1, "area 2")?;
+                let n = self.shape_number(args[0])?;
+                Ok(format!("area of #{n}: {}", self.shapes[n - 1].area()))
+            }
+            "collide" => {
+                expect_args(command, &args, 2, "collide 1 3")?;
+                let (n, m) = (self.shape_number(args[0])?, self.shape_number(args[1])?);
+                let (a, b) = (&self.shapes[n - 1], &self.shapes[m - 1]);
+                let collide = a.collide(b);
+                Ok(format!(
+                    "#{n} and #{m} {}",
+                    if collide { "collide" } else { "don't collide" }
+                ))
+            }
+            "move" => {
+                expect_args(command, &args, 3, "move 2 5 5")?;
+                let n = self.shape_number(args[0])?;
+                let x = parse_coordinate(args[1], "x")?;
+                let y = parse_coordinate(args[2], "y")?;
+                self.shapes[n - 1].move_to((x, y));
+                Ok(format!("moved #{n}: {}", self.shapes[n - 1]))
+            }
+            "remove" => {
+                expect_args(command, &args, 1, "remove 2")?;
+                let n = self.shape_number(args[0])?;
+                let removed = self.shapes.remove(n - 1);
+                Ok(format!("removed #{n}: {removed}"))
+            }
+            "clear" => {
+                expect_args(command, &args, 0, "clear")?;
+                self.shapes.clear();
+                Ok(String::from("the scene is empty"))
+            }
+            "save" => {
+                let path = expect_path(command, rest, "save scene.txt")?;
+                let mut file =
+                    File::create(path).with_context(|| format!("couldn't create {path}"))?;
+                self.shapes
+                    .iter()
+                    .try_for_each(|shape| writeln!(file, "{shape}"))
+                    .with_context(|| format!("couldn't write {path}"))?;
+                Ok(format!("saved {} shapes to {path}", self.shapes.len()))
+            }
+            "load" => {
+                let path = expect_path(command, rest, "load scene.txt")?;
+                // all or nothing, a bad line leaves the current scene untouched
+                let shapes = SceneReader::open(path)?
+                    .collect::<anyhow::Result<Vec<_>>>()
+                    .with_context(|| format!("couldn't load {path}"))?;
+                self.shapes = shapes;
+                Ok(format!("loaded {} shapes from {path}", self.shapes.len()))
+            }
+            "history" => Ok(self
+                .history
+                .iter()
+                .enumerate()
+                .map(|(i, line)| format!("{:>4}  {}", i + 1, line))
+                .collect::<Vec<_>>()
+                .join("\n")),
+            "help" => Ok(String::from(HELP)),
+            "quit" | "exit" => {
+                expect_args(command, &args, 0, "quit")?;
+                self.finished = true;
+                Ok(String::new())
+            }
+            _ => Err(anyhow::anyhow!("unknown command `{command}`, try `help`")),
+        }
+    }
+
+    fn shape_number(&self, arg: &str) -> anyhow::Result<usize> {
+        let n = arg
+            .parse::<usize>()
+            .map_err(|_| anyhow::anyhow!("`{arg}` isn't a shape number, see `list`"))?;
+        if n == 0 || n > self.shapes.len() {
+            return Err(anyhow::anyhow!(
+                "no shape #{n}, the scene has {} shapes",
+                self.shapes.len()
+            ));
+        }
+        Ok(n)
+    }
+}
+
+fn expect_args(command: &str, args: &[&str], count: usize, example: &str) -> anyhow::Result<()> {
+    if args.len() != count {
+        return Err(anyhow::anyhow!(
+            "`{command}` takes {count} arguments, e.g. `{example}`"
+        ));
+    }
+    Ok(())
+}
+
+/// The path is the rest of the line, so file names with spaces work without quoting
+fn expect_path<'a>(command: &str, rest: &'a str, example: &str) -> anyhow::Result<&'a str> {
+    if rest.is_empty() {
+        return Err(anyhow::anyhow!(
+            "`{command}` takes a file name, e.g. `{example}`"
+        ));
+    }
+    Ok(rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripted_session() {
+        let mut repl = Repl::new();
+        let mut run = |line: &str| match repl.execute(line) {
+            Ok(reply) => reply,
+            Err(e) => format!("error: {e:#}"),
+        };
+
+        assert_eq!(run("rect 0 0 3 4"), "added #1: rect 0 0 3 4");
+        assert_eq!(run("circle  1 1   2"), "added #2: circle 1 1 2");
+        assert_eq!(run("circle 20 20 1"), "added #3: circle 20 20 1");
+        assert_eq!(run("area 1"), "area of #1: 12");
+        assert_eq!(run("collide 1 2"), "#1 and #2 collide");
+        assert_eq!(run("collide 1 3"), "#1 and #3 don't collide");
+        assert_eq!(run("move 3 0.5 0.5"), "moved #3: circle 0.5 0.5 1");
+        assert_eq!(run("!6"), "#1 and #3 collide");
+        assert_eq!(
+            run("list"),
+            "1: rect 0 0 3 4\n2: circle 1 1 2\n3: circle 0.5 0.5 1"
+        );
+
+        assert_eq!(run("area 4"), "error: no shape #4, the scene has 3 shapes");
+        assert_eq!(
+            run("area"),
+            "error: `area` takes 1 arguments, e.g. `area 2`"
+        );
+        assert_eq!(
+            run("rect 0 0 three 4"),
+            "error: couldn't add `rect 0 0 three 4`: `three` isn't a valid height"
+        );
+        // regression: a negative rect used to get in and make `collide` panic
+        assert_eq!(
+            run("rect 0 0 -1 2"),
+            "error: couldn't add `rect 0 0 -1 2`: height can't be negative, got `-1`"
+        );
+        assert_eq!(
+            run("triangle 1 2 3"),
+            "error: unknown command `triangle`, try `help`"
+        );
+        assert_eq!(run("!42"), "error: `!42`: no such command in history");
+
+        assert_eq!(run("history").lines().nth(7), Some("   8  collide 1 3"));
+
+        assert_eq!(run("quit"), "");
+        assert!(repl.is_finished());
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let path = std::env::temp_dir().join(format!("shapes repl {}", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let mut repl = Repl::new();
+        repl.execute("rect 0.5 0 3 4").unwrap();
+        repl.execute("circle 1 1 2").unwrap();
+        repl.execute(&format!("save {path}")).unwrap();
+
+        let mut other = Repl::new();
+        assert_eq!(
+            other.execute(&format!("load {path}")).unwrap(),
+            format!("loaded 2 shapes from {path}")
+        );
+        assert_eq!(other.shapes(), repl.shapes());
+        std::fs::remove_file(path).unwrap();
+    }
+}
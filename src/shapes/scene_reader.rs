@@ -0,0 +1,271 @@
+// == Streaming scene reader ==
+/* `read_to_string` + `collect::<Vec<Shape>>()` keeps the whole scene in memory twice (the file
+ * contents and the parsed shapes). Instead we pull one line at a time from any `BufRead`
+ * (or `AsyncBufRead`) into a single reused `String` buffer and parse it lazily, so memory use is
+ * bounded by the longest line (plus whatever window the consumer decides to keep around).
+ */
+
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use anyhow::Context;
+use futures::Stream;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+use super::{collisions::Collidable, shape::Shape};
+
+/// Bookkeeping about a read, mostly to show that memory use stays bounded
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReaderStats {
+    pub lines_read: usize,
+    pub shapes_parsed: usize,
+    pub blank_lines: usize,
+    /// Largest capacity the shared line buffer ever needed, i.e. the peak memory of the reader
+    pub peak_line_bytes: usize,
+}
+
+impl ReaderStats {
+    fn record_line(&mut self, buffer: &str) {
+        self.lines_read += 1;
+        self.peak_line_bytes = self.peak_line_bytes.max(buffer.len());
+    }
+}
+
+// shared by the sync and async readers, None for lines that don't hold a shape
+fn parse_line(
+    line_number: usize,
+    line: &str,
+    stats: &mut ReaderStats,
+) -> Option<anyhow::Result<Shape>> {
+    let line = line.trim_end_matches(['\n', '\r']);
+    if line.trim().is_empty() {
+        stats.blank_lines += 1;
+        return None;
+    }
+    let parsed = line
+        .parse::<Shape>()
+        .with_context(|| format!("line {line_number}: `{line}`"));
+    if parsed.is_ok() {
+        stats.shapes_parsed += 1;
+    }
+    Some(parsed)
+}
+
+/// Lazily parses shapes from any `BufRead`, one line at a time.
+///
+/// Yields `Err` (with the line number) for malformed lines instead of stopping, so callers can
+/// decide between being strict (`collect::<Result<_, _>>()`) or lenient (`filter_map(Result::ok)`).
+pub struct SceneReader<R> {
+    reader: R,
+    line: String,
+    line_number: usize,
+    stats: ReaderStats,
+}
+
+impl<R: BufRead> SceneReader<R> {
+    pub fn new(reader: R) -> Self {
+        SceneReader {
+            reader,
+            line: String::new(),
+            line_number: 0,
+            stats: ReaderStats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> ReaderStats {
+        self.stats
+    }
+}
+
+impl SceneReader<BufReader<File>> {
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).with_context(|| format!("opening {}", path.display()))?;
+        Ok(SceneReader::new(BufReader::new(file)))
+    }
+}
+
+impl<R: BufRead> Iterator for SceneReader<R> {
+    type Item = anyhow::Result<Shape>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line.clear(); // keeps the capacity, so the buffer is allocated once per peak line
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => return None,
+                Ok(_) => {
+                    self.line_number += 1;
+                    self.stats.record_line(&self.line);
+                    if let Some(parsed) = parse_line(self.line_number, &self.line, &mut self.stats)
+                    {
+                        return Some(parsed);
+                    }
+                }
+                Err(e) => {
+                    self.line_number += 1;
+                    return Some(Err(anyhow::Error::new(e)
+                        .context(format!("line {}: read failed", self.line_number))));
+                }
+            }
+        }
+    }
+}
+
+/// Async counterpart of [`SceneReader`] for any `AsyncBufRead` (tokio files, sockets, ..)
+pub struct AsyncSceneReader<R> {
+    reader: R,
+    line: String,
+    line_number: usize,
+    stats: ReaderStats,
+}
+
+impl<R: AsyncBufRead + Unpin> AsyncSceneReader<R> {
+    pub fn new(reader: R) -> Self {
+        AsyncSceneReader {
+            reader,
+            line: String::new(),
+            line_number: 0,
+            stats: ReaderStats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> ReaderStats {
+        self.stats
+    }
+
+    /// Async analogue of `Iterator::next`
+    pub async fn next_shape(&mut self) -> Option<anyhow::Result<Shape>> {
+        loop {
+            self.line.clear();
+            match self.reader.read_line(&mut self.line).await {
+                Ok(0) => return None,
+                Ok(_) => {
+                    self.line_number += 1;
+                    self.stats.record_line(&self.line);
+                    if let Some(parsed) = parse_line(self.line_number, &self.line, &mut self.stats)
+                    {
+                        return Some(parsed);
+                    }
+                }
+                Err(e) => {
+                    self.line_number += 1;
+                    return Some(Err(anyhow::Error::new(e)
+                        .context(format!("line {}: read failed", self.line_number))));
+                }
+            }
+        }
+    }
+
+    // NOTE: unfold threads the reader through each future, so the stream owns it and is 'static
+    // whenever R is (no self-referential future to pin by hand)
+    pub fn into_stream(self) -> impl Stream<Item = anyhow::Result<Shape>> {
+        futures::stream::unfold(self, |mut reader| async move {
+            let next = reader.next_shape().await?;
+            Some((next, reader))
+        })
+    }
+}
+
+/// Stats about a windowed collision check
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WindowStats {
+    pub shapes_seen: usize,
+    /// Most shapes that were ever held in memory at once, never more than `window + 1`
+    pub peak_resident: usize,
+}
+
+/// Checks every shape against the `window` shapes read right before it, calling `on_collision`
+/// with `(index, shape)` of the later and the earlier shape for each collision.
+///
+/// `window = 1` is the old "adjacent shapes" check. Only the window is kept in memory, so this
+/// works on scenes that would never fit into a `Vec<Shape>`.
+pub fn for_each_collision<I, F>(shapes: I, window: usize, mut on_collision: F) -> WindowStats
+where
+    I: IntoIterator<Item = Shape>,
+    F: FnMut((usize, &Shape), (usize, &Shape)),
+{
+    let mut stats = WindowStats::default();
+    let mut recent: VecDeque<(usize, Shape)> = VecDeque::with_capacity(window);
+
+    for (index, shape) in shapes.into_iter().enumerate() {
+        stats.shapes_seen += 1;
+        stats.peak_resident = stats.peak_resident.max(recent.len() + 1);
+
+        recent
+            .iter()
+            .filter(|(_, earlier)| shape.collide(earlier))
+            .for_each(|(earlier_index, earlier)| {
+                on_collision((index, &shape), (*earlier_index, earlier))
+            });
+
+        if window == 0 {
+            continue;
+        }
+        if recent.len() == window {
+            recent.pop_front();
+        }
+        recent.push_back((index, shape));
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    const SCENE: &str = "rect 1 2 3 4\ncircle 0 0 2\n\nnot a shape\ncircle 0 0 1\r\nrect 2 1 4 5\n";
+
+    #[test]
+    fn reads_lazily_and_reports_bad_lines() {
+        let mut reader = SceneReader::new(SCENE.as_bytes());
+        let results = reader.by_ref().collect::<Vec<_>>();
+
+        assert_eq!(results.len(), 5); // blank line skipped, bad line reported
+        let err = results[2].as_ref().err().unwrap();
+        assert!(format!("{err:#}").starts_with("line 4: `not a shape`"));
+
+        let stats = reader.stats();
+        assert_eq!(stats.lines_read, 6);
+        assert_eq!(stats.shapes_parsed, 4);
+        assert_eq!(stats.blank_lines, 1);
+        assert_eq!(stats.peak_line_bytes, "circle 0 0 1\r\n".len());
+    }
+
+    #[test]
+    fn async_reader_matches_sync_reader() {
+        let sync_shapes = SceneReader::new(SCENE.as_bytes())
+            .map(|shape| shape.map(|s| s.to_string()).map_err(|e| e.to_string()))
+            .collect::<Vec<_>>();
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let async_shapes = rt.block_on(async {
+            AsyncSceneReader::new(SCENE.as_bytes())
+                .into_stream()
+                .map(|shape| shape.map(|s| s.to_string()).map_err(|e| e.to_string()))
+                .collect::<Vec<_>>()
+                .await
+        });
+
+        assert_eq!(sync_shapes, async_shapes);
+    }
+
+    #[test]
+    fn window_bounds_resident_shapes() {
+        // a long generated scene of identical circles, every shape collides with its window
+        let shapes = (0..1000).map(|_| "circle 0 0 1".parse::<Shape>().unwrap());
+        let mut collisions = 0;
+        let stats = for_each_collision(shapes, 3, |(later, _), (earlier, _)| {
+            assert!(later > earlier && later - earlier <= 3);
+            collisions += 1;
+        });
+
+        assert_eq!(stats.shapes_seen, 1000);
+        assert_eq!(stats.peak_resident, 4);
+        assert_eq!(collisions, 3 * 1000 - (1 + 2 + 3));
+    }
+}
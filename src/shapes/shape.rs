@@ -1,21 +1,63 @@
 use std::{fmt::Display, str::FromStr};
 
 use super::{
+    area::Area,
     circle::Circle,
-    collisions::{Contains, Points},
+    collisions::{Collidable, Contains, Points},
     rect::Rect,
 };
 
+#[derive(Debug, Clone, PartialEq)]
 pub enum Shape {
     Rect(Rect),
     Circle(Circle),
 }
 
+impl Shape {
+    /// The keyword used for this shape in scene files
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Shape::Rect(_) => "rect",
+            Shape::Circle(_) => "circle",
+        }
+    }
+
+    /// Anchor point of the shape, (x, y) for both rects and circles
+    pub fn position(&self) -> (f32, f32) {
+        match self {
+            Shape::Rect(r) => (r.x, r.y),
+            Shape::Circle(c) => (c.x, c.y),
+        }
+    }
+
+    /// Axis aligned bounding box as (min_x, min_y, max_x, max_y)
+    pub fn bounds(&self) -> (f32, f32, f32, f32) {
+        match self {
+            Shape::Rect(r) => (r.x, r.y, r.x + r.width, r.y + r.height),
+            Shape::Circle(c) => (
+                c.x - c.radius,
+                c.y - c.radius,
+                c.x + c.radius,
+                c.y + c.radius,
+            ),
+        }
+    }
+
+    pub fn move_to(&mut self, (x, y): (f32, f32)) {
+        match self {
+            Shape::Rect(r) => (r.x, r.y) = (x, y),
+            Shape::Circle(c) => (c.x, c.y) = (x, y),
+        }
+    }
+}
+
+// NOTE: unlike Rect and Circle, a Shape displays in the same format it's parsed from
+// (`rect 1 2 3 4`), so a scene can be written back to a file line by line
 impl Display for Shape {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Shape::Rect(r) => write!(f, "{}", r),
-            Shape::Circle(c) => write!(f, "{}", c),
+            Shape::Rect(r) => write!(f, "rect {} {} {} {}", r.x, r.y, r.height, r.width),
+            Shape::Circle(c) => write!(f, "circle {} {} {}", c.x, c.y, c.radius),
         }
     }
 }
@@ -27,7 +69,10 @@ impl FromStr for Shape {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         // match the string for shape, rest of the input provide to respective impl of FromStr
-        let (shape_info, data) = s.split_once(" ").expect("Incorrect file format");
+        // NOTE: no `expect` here, a single malformed line shouldn't bring down a whole scene read
+        let (shape_info, data) = s
+            .split_once(" ")
+            .ok_or_else(|| anyhow::anyhow!("Incorrect file format, expected `<shape> <data>`"))?;
         match shape_info {
             "rect" => Ok(Shape::Rect(Rect::from_str(data)?)),
             "circle" => Ok(Shape::Circle(Circle::from_str(data)?)),
@@ -56,3 +101,23 @@ impl Contains for Shape {
         }
     }
 }
+
+impl Area for Shape {
+    fn area(&self) -> f32 {
+        match self {
+            Shape::Rect(r) => r.area(),
+            Shape::Circle(c) => c.area(),
+        }
+    }
+}
+
+impl Collidable<Shape> for Shape {
+    fn collide(&self, other: &Shape) -> bool {
+        match (self, other) {
+            (Shape::Rect(a), Shape::Rect(b)) => a.collide(b),
+            (Shape::Rect(a), Shape::Circle(b)) => a.collide(b),
+            (Shape::Circle(a), Shape::Rect(b)) => a.collide(b),
+            (Shape::Circle(a), Shape::Circle(b)) => a.collide(b),
+        }
+    }
+}
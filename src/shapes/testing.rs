@@ -0,0 +1,344 @@
+// == Randomised (property based) testing for shapes ==
+/* A tiny quickcheck: generate random inputs from a seed, check a property on each of them and,
+ * on failure, shrink the input to a minimal counterexample before reporting it with the seed.
+ *
+ * By default ShapeGen keeps every number on a 1/4 grid with small magnitudes, so sums, squares and
+ * translations are exact in f32 and properties can be checked with `==` instead of epsilons.
+ *
+ * It's public (not #[cfg(test)]) so that other crates can generate shapes for their own tests:
+ *
+ *     check("my property", Config::default(), |g| (g.shape(), g.shape()), |(a, b)| ...);
+ *
+ * Set SEED=<n> to replay a failing run (see crate::testing, shared with dsa::stress).
+ */
+
+use std::fmt::Debug;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::{circle::Circle, rect::Rect, shape::Shape};
+
+// re-exported, so users of the generator get everything `check` takes from one place
+pub use crate::testing::{shrink_failure, Config, Shrink};
+
+pub struct ShapeGen {
+    rng: StdRng,
+    /// coordinates are in [-max_coordinate, max_coordinate]
+    pub max_coordinate: f32,
+    /// sizes (height, width, radius) are in [0, max_size]
+    pub max_size: f32,
+    /// values are rounded to multiples of this, None for arbitrary floats
+    pub grid: Option<f32>,
+}
+
+impl ShapeGen {
+    pub fn new(seed: u64) -> Self {
+        ShapeGen {
+            rng: StdRng::seed_from_u64(seed),
+            max_coordinate: 64.0,
+            max_size: 32.0,
+            grid: Some(0.25),
+        }
+    }
+
+    pub fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+
+    fn snap(&self, value: f32) -> f32 {
+        match self.grid {
+            Some(step) => (value / step).round() * step,
+            None => value,
+        }
+    }
+
+    pub fn coordinate(&mut self) -> f32 {
+        let value = self
+            .rng
+            .gen_range(-self.max_coordinate..=self.max_coordinate);
+        self.snap(value)
+    }
+
+    pub fn size(&mut self) -> f32 {
+        let value = self.rng.gen_range(0.0..=self.max_size);
+        self.snap(value)
+    }
+
+    pub fn point(&mut self) -> (f32, f32) {
+        (self.coordinate(), self.coordinate())
+    }
+
+    pub fn rect(&mut self) -> Rect {
+        Rect {
+            x: self.coordinate(),
+            y: self.coordinate(),
+            height: self.size(),
+            width: self.size(),
+        }
+    }
+
+    pub fn circle(&mut self) -> Circle {
+        Circle {
+            x: self.coordinate(),
+            y: self.coordinate(),
+            radius: self.size(),
+        }
+    }
+
+    pub fn shape(&mut self) -> Shape {
+        if self.rng.gen_bool(0.5) {
+            Shape::Rect(self.rect())
+        } else {
+            Shape::Circle(self.circle())
+        }
+    }
+
+    pub fn scene(&mut self, max_len: usize) -> Vec<Shape> {
+        let len = self.rng.gen_range(0..=max_len);
+        (0..len).map(|_| self.shape()).collect()
+    }
+}
+
+impl Shrink for Rect {
+    fn shrink(&self) -> Vec<Rect> {
+        let mut candidates = vec![];
+        candidates.extend(self.x.shrink().into_iter().map(|x| Rect { x, ..*self }));
+        candidates.extend(self.y.shrink().into_iter().map(|y| Rect { y, ..*self }));
+        candidates.extend(
+            self.height
+                .shrink()
+                .into_iter()
+                .map(|height| Rect { height, ..*self }),
+        );
+        candidates.extend(
+            self.width
+                .shrink()
+                .into_iter()
+                .map(|width| Rect { width, ..*self }),
+        );
+        candidates
+    }
+}
+
+impl Shrink for Circle {
+    fn shrink(&self) -> Vec<Circle> {
+        let mut candidates = vec![];
+        candidates.extend(self.x.shrink().into_iter().map(|x| Circle { x, ..*self }));
+        candidates.extend(self.y.shrink().into_iter().map(|y| Circle { y, ..*self }));
+        candidates.extend(
+            self.radius
+                .shrink()
+                .into_iter()
+                .map(|radius| Circle { radius, ..*self }),
+        );
+        candidates
+    }
+}
+
+impl Shrink for Shape {
+    fn shrink(&self) -> Vec<Shape> {
+        match self {
+            Shape::Rect(r) => r.shrink().into_iter().map(Shape::Rect).collect(),
+            Shape::Circle(c) => c.shrink().into_iter().map(Shape::Circle).collect(),
+        }
+    }
+}
+
+/// Runs `property` on `config.cases` generated inputs, panics with the shrunk counterexample and
+/// the seed to replay it
+pub fn check<T, G, P>(name: &str, config: Config, mut generate: G, property: P)
+where
+    T: Shrink + Debug,
+    G: FnMut(&mut ShapeGen) -> T,
+    P: Fn(&T) -> bool,
+{
+    let mut gen = ShapeGen::new(config.seed);
+    for case in 0..config.cases {
+        let input = generate(&mut gen);
+        if property(&input) {
+            continue;
+        }
+        let original = format!("{input:?}");
+        let smallest = shrink_failure(input, &property, config.max_shrinks);
+        panic!(
+            "property `{name}` failed on case {case} (replay with SEED={})\n  minimal: {smallest:?}\n  original: {original}",
+            config.seed
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shapes::{
+        area::Area,
+        collisions::{Collidable, Contains},
+        diff::{diff, DiffOptions},
+    };
+
+    fn translated(shape: &Shape, (dx, dy): (f32, f32)) -> Shape {
+        let (x, y) = shape.position();
+        let mut moved = shape.clone();
+        moved.move_to((x + dx, y + dy));
+        moved
+    }
+
+    #[test]
+    fn rect_contains_its_interior() {
+        // regression: the vertical check used to be inverted (`height + y <= y`)
+        let rect = "rect 0 0 3 4".parse::<Shape>().unwrap();
+        assert!(rect.contains_point((2.0, 1.5)));
+        assert!(!rect.contains_point((2.0, 3.5)));
+        assert!(!rect.contains_point((4.5, 1.5)));
+    }
+
+    #[test]
+    fn collision_is_symmetric() {
+        check(
+            "a collides with b <=> b collides with a",
+            Config::default(),
+            |g| (g.shape(), g.shape()),
+            |(a, b)| a.collide(b) == b.collide(a),
+        );
+    }
+
+    #[test]
+    fn shared_point_implies_collision() {
+        check(
+            "a and b contain p => a collides with b",
+            Config {
+                cases: 2048,
+                ..Config::default()
+            },
+            // small coordinates, otherwise a random point is rarely inside both shapes
+            |g| {
+                g.max_coordinate = 8.0;
+                (g.shape(), g.shape(), g.point())
+            },
+            |(a, b, p)| !(a.contains_point(*p) && b.contains_point(*p)) || a.collide(b),
+        );
+    }
+
+    #[test]
+    fn containing_an_anchor_implies_collision() {
+        check(
+            "a contains b's anchor => a collides with b",
+            Config::default(),
+            |g| (g.shape(), g.shape()),
+            |(a, b)| !a.contains_point(b.position()) || a.collide(b),
+        );
+    }
+
+    #[test]
+    fn shapes_collide_with_themselves() {
+        check(
+            "a collides with a",
+            Config::default(),
+            |g| g.shape(),
+            |a| a.collide(a),
+        );
+    }
+
+    #[test]
+    fn area_is_never_negative() {
+        check(
+            "area >= 0",
+            Config::default(),
+            |g| g.shape(),
+            |a| a.area() >= 0.0,
+        );
+        // and the parser doesn't let negative sizes in
+        assert!("rect 0 0 -1 2".parse::<Shape>().is_err());
+        assert!("circle 0 0 -1".parse::<Shape>().is_err());
+        assert!("circle 0 NaN 1".parse::<Shape>().is_err());
+    }
+
+    #[test]
+    fn negative_size_rect_collides_without_panicking() {
+        // regression: clamp panicked with "min > max" when the width or height was negative
+        let rect = Rect {
+            x: 0.0,
+            y: 0.0,
+            height: 2.0,
+            width: -1.0,
+        };
+        assert!(rect.collide(&Circle {
+            x: 0.0,
+            y: 0.0,
+            radius: 1.0
+        }));
+        assert!(!rect.collide(&Circle {
+            x: 5.0,
+            y: 0.0,
+            radius: 1.0
+        }));
+    }
+
+    #[test]
+    fn display_and_from_str_round_trip() {
+        check(
+            "s.to_string().parse() == s",
+            Config::default(),
+            |g| {
+                g.grid = None; // any float, Display prints the shortest repr that parses back
+                g.shape()
+            },
+            |s| s.to_string().parse::<Shape>().ok().as_ref() == Some(s),
+        );
+    }
+
+    #[test]
+    fn translation_doesnt_change_geometry() {
+        check(
+            "translating everything by d keeps collisions, containment and area",
+            Config::default(),
+            |g| (g.shape(), g.shape(), g.point()),
+            |(a, b, d)| {
+                let (moved_a, moved_b) = (translated(a, *d), translated(b, *d));
+                let p = b.position();
+                a.collide(b) == moved_a.collide(&moved_b)
+                    && a.contains_point(p) == moved_a.contains_point((p.0 + d.0, p.1 + d.1))
+                    && a.area() == moved_a.area()
+            },
+        );
+    }
+
+    #[test]
+    fn diff_of_a_scene_with_itself_is_empty() {
+        check(
+            "diff(s, s) is empty",
+            Config {
+                cases: 64,
+                ..Config::default()
+            },
+            |g| g.scene(20),
+            |scene| {
+                let d = diff(scene, scene, &DiffOptions::default());
+                d.is_empty() && d.unchanged == scene.len()
+            },
+        );
+    }
+
+    #[test]
+    fn shrinks_to_a_minimal_counterexample() {
+        // a deliberately wrong property: "no circle is bigger than 10"
+        let big = Shape::Circle(Circle {
+            x: -37.25,
+            y: 12.5,
+            radius: 27.75,
+        });
+        let smallest = shrink_failure(
+            big,
+            |s| !matches!(s, Shape::Circle(c) if c.radius > 10.0),
+            1000,
+        );
+        assert_eq!(
+            smallest,
+            Shape::Circle(Circle {
+                x: 0.0,
+                y: 0.0,
+                radius: 13.0,
+            })
+        );
+    }
+}
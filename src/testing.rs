@@ -0,0 +1,144 @@
+// == Shared pieces of the randomised testing helpers ==
+/* shapes::testing (properties of shapes) and dsa::stress (solutions against a reference) both
+ * generate inputs from a seed, and on failure shrink the input to a minimal counterexample:
+ * the Shrink trait, shrink_failure and the run Config live here so neither depends on the other.
+ *
+ * Set SEED=<n> to replay a failing run, both print the seed to use.
+ */
+
+/// Simpler versions of a value, tried in order when shrinking a counterexample
+pub trait Shrink: Sized {
+    fn shrink(&self) -> Vec<Self>;
+}
+
+impl Shrink for bool {
+    fn shrink(&self) -> Vec<bool> {
+        if *self {
+            vec![false]
+        } else {
+            vec![]
+        }
+    }
+}
+
+impl Shrink for f32 {
+    // towards 0, through integers, every candidate stays exact on a 1/4 grid (or is 0)
+    fn shrink(&self) -> Vec<f32> {
+        let mut candidates = vec![0.0, self.trunc(), (self / 2.0).trunc()];
+        candidates.retain(|candidate| candidate != self);
+        candidates.dedup();
+        candidates
+    }
+}
+
+// towards 0: straight to it, then halving, then one step
+macro_rules! shrink_integer {
+    ($($t:ty),*) => {$(
+        impl Shrink for $t {
+            fn shrink(&self) -> Vec<$t> {
+                let mut candidates = vec![0, self / 2, self - self.signum()];
+                candidates.retain(|candidate| candidate != self);
+                candidates.dedup();
+                candidates
+            }
+        }
+    )*};
+}
+
+shrink_integer!(i32, i64);
+
+macro_rules! shrink_unsigned {
+    ($($t:ty),*) => {$(
+        impl Shrink for $t {
+            fn shrink(&self) -> Vec<$t> {
+                let mut candidates = vec![0, self / 2, self.saturating_sub(1)];
+                candidates.retain(|candidate| candidate != self);
+                candidates.dedup();
+                candidates
+            }
+        }
+    )*};
+}
+
+shrink_unsigned!(u32, u64, usize);
+
+impl<A: Shrink + Clone, B: Shrink + Clone> Shrink for (A, B) {
+    fn shrink(&self) -> Vec<(A, B)> {
+        let firsts = self.0.shrink().into_iter().map(|a| (a, self.1.clone()));
+        let seconds = self.1.shrink().into_iter().map(|b| (self.0.clone(), b));
+        firsts.chain(seconds).collect()
+    }
+}
+
+impl<A: Shrink + Clone, B: Shrink + Clone, C: Shrink + Clone> Shrink for (A, B, C) {
+    fn shrink(&self) -> Vec<(A, B, C)> {
+        ((self.0.clone(), self.1.clone()), self.2.clone())
+            .shrink()
+            .into_iter()
+            .map(|((a, b), c)| (a, b, c))
+            .collect()
+    }
+}
+
+impl<T: Shrink + Clone> Shrink for Vec<T> {
+    // dropping elements first (shorter is simpler), then shrinking them one at a time
+    fn shrink(&self) -> Vec<Vec<T>> {
+        let mut candidates = (0..self.len())
+            .map(|i| {
+                let mut shorter = self.clone();
+                shorter.remove(i);
+                shorter
+            })
+            .collect::<Vec<_>>();
+        for (i, item) in self.iter().enumerate() {
+            candidates.extend(item.shrink().into_iter().map(|smaller| {
+                let mut candidate = self.clone();
+                candidate[i] = smaller;
+                candidate
+            }));
+        }
+        candidates
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    pub cases: usize,
+    pub seed: u64,
+    /// upper bound on shrinking steps, each step takes the first simpler input that still fails
+    pub max_shrinks: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let seed = std::env::var("SEED")
+            .ok()
+            .and_then(|seed| seed.parse().ok())
+            .unwrap_or(0x5eed);
+        Config {
+            cases: 500,
+            seed,
+            max_shrinks: 1000,
+        }
+    }
+}
+
+/// Shrinks a failing input as long as some simpler candidate still fails
+pub fn shrink_failure<T, P>(input: T, property: P, max_shrinks: usize) -> T
+where
+    T: Shrink,
+    P: Fn(&T) -> bool,
+{
+    let mut smallest = input;
+    for _ in 0..max_shrinks {
+        match smallest
+            .shrink()
+            .into_iter()
+            .find(|candidate| !property(candidate))
+        {
+            Some(smaller) => smallest = smaller,
+            None => break,
+        }
+    }
+    smallest
+}